@@ -1,7 +1,7 @@
+use ic_cbor::HashTreeToCbor;
 use ic_cdk::export::candid::CandidType;
 use ic_cdk::*;
 use ic_certification::{AsHashTree, RbTree};
-use serde::Serialize;
 use std::cell::*;
 
 thread_local! {
@@ -40,19 +40,11 @@ struct CertifiedCounter {
     witness: Vec<u8>,
 }
 
-fn get_count_witness() -> anyhow::Result<Vec<u8>> {
+fn get_count_witness() -> Vec<u8> {
     TREE.with(|tree| {
         let tree = tree.borrow();
-        let mut witness = vec![];
-        let mut witness_serializer = serde_cbor::Serializer::new(&mut witness);
 
-        witness_serializer.self_describe()?;
-
-        tree.witness(b"count")
-            .serialize(&mut witness_serializer)
-            .unwrap();
-
-        Ok(witness)
+        tree.witness(b"count").to_cbor()
     })
 }
 
@@ -60,12 +52,7 @@ fn get_count_witness() -> anyhow::Result<Vec<u8>> {
 fn get_count() -> CertifiedCounter {
     let certificate = ic_cdk::api::data_certificate().expect("No data certificate available");
 
-    let witness = match get_count_witness() {
-        Ok(tree) => tree,
-        Err(err) => {
-            ic_cdk::trap(&format!("Error getting count witness: {:?}", err));
-        }
-    };
+    let witness = get_count_witness();
 
     let count = COUNTER.with(|counter| counter.get());
 
@@ -0,0 +1,182 @@
+use ic_certification::{hash_tree::HashTreeNode, Certificate, Delegation, HashTree};
+
+/// The self-describing CBOR tag (55799) that is prepended to every top level CBOR value.
+const SELF_DESCRIBE_TAG: [u8; 3] = [0xd9, 0xd9, 0xf7];
+
+const MAJOR_TYPE_UNSIGNED_INT: u8 = 0;
+const MAJOR_TYPE_BYTE_STRING: u8 = 2;
+const MAJOR_TYPE_TEXT_STRING: u8 = 3;
+const MAJOR_TYPE_ARRAY: u8 = 4;
+const MAJOR_TYPE_MAP: u8 = 5;
+
+/// Encodes an array of strings as self-describing CBOR, the inverse of
+/// [parse_cbor_string_array](crate::parse_cbor_string_array).
+pub fn encode_cbor_string_array<T: AsRef<str>>(strings: &[T]) -> Vec<u8> {
+    let mut cbor = SELF_DESCRIBE_TAG.to_vec();
+
+    encode_head(&mut cbor, MAJOR_TYPE_ARRAY, strings.len() as u64);
+    for string in strings {
+        encode_text_string(&mut cbor, string.as_ref());
+    }
+
+    cbor
+}
+
+pub(crate) fn encode_hash_tree(tree: &HashTree) -> Vec<u8> {
+    let mut cbor = SELF_DESCRIBE_TAG.to_vec();
+    encode_hash_tree_node(&mut cbor, tree.as_ref());
+
+    cbor
+}
+
+pub(crate) fn encode_certificate(certificate: &Certificate) -> Vec<u8> {
+    let mut cbor = SELF_DESCRIBE_TAG.to_vec();
+
+    let map_len = if certificate.delegation.is_some() {
+        3
+    } else {
+        2
+    };
+    encode_head(&mut cbor, MAJOR_TYPE_MAP, map_len);
+
+    encode_text_string(&mut cbor, "tree");
+    encode_hash_tree_node(&mut cbor, certificate.tree.as_ref());
+
+    encode_text_string(&mut cbor, "signature");
+    encode_byte_string(&mut cbor, &certificate.signature);
+
+    if let Some(delegation) = &certificate.delegation {
+        encode_text_string(&mut cbor, "delegation");
+        encode_delegation(&mut cbor, delegation);
+    }
+
+    cbor
+}
+
+fn encode_delegation(cbor: &mut Vec<u8>, delegation: &Delegation) {
+    encode_head(cbor, MAJOR_TYPE_MAP, 2);
+
+    encode_text_string(cbor, "subnet_id");
+    encode_byte_string(cbor, &delegation.subnet_id);
+
+    encode_text_string(cbor, "certificate");
+    encode_byte_string(cbor, &delegation.certificate);
+}
+
+/// Encodes a hash tree node as an array that starts with the node's tag, as defined in
+/// <https://internetcomputer.org/docs/current/references/ic-interface-spec/#certificate-encoding>
+fn encode_hash_tree_node(cbor: &mut Vec<u8>, node: &HashTreeNode<Vec<u8>>) {
+    match node {
+        HashTreeNode::Empty() => {
+            encode_head(cbor, MAJOR_TYPE_ARRAY, 1);
+            encode_head(cbor, MAJOR_TYPE_UNSIGNED_INT, 0);
+        }
+        HashTreeNode::Fork(children) => {
+            encode_head(cbor, MAJOR_TYPE_ARRAY, 3);
+            encode_head(cbor, MAJOR_TYPE_UNSIGNED_INT, 1);
+            encode_hash_tree_node(cbor, &children.0);
+            encode_hash_tree_node(cbor, &children.1);
+        }
+        HashTreeNode::Labeled(label, child) => {
+            encode_head(cbor, MAJOR_TYPE_ARRAY, 3);
+            encode_head(cbor, MAJOR_TYPE_UNSIGNED_INT, 2);
+            encode_byte_string(cbor, label.as_bytes());
+            encode_hash_tree_node(cbor, child);
+        }
+        HashTreeNode::Leaf(data) => {
+            encode_head(cbor, MAJOR_TYPE_ARRAY, 2);
+            encode_head(cbor, MAJOR_TYPE_UNSIGNED_INT, 3);
+            encode_byte_string(cbor, data);
+        }
+        HashTreeNode::Pruned(digest) => {
+            encode_head(cbor, MAJOR_TYPE_ARRAY, 2);
+            encode_head(cbor, MAJOR_TYPE_UNSIGNED_INT, 4);
+            encode_byte_string(cbor, digest);
+        }
+    }
+}
+
+fn encode_byte_string(cbor: &mut Vec<u8>, bytes: &[u8]) {
+    encode_head(cbor, MAJOR_TYPE_BYTE_STRING, bytes.len() as u64);
+    cbor.extend_from_slice(bytes);
+}
+
+fn encode_text_string(cbor: &mut Vec<u8>, text: &str) {
+    encode_head(cbor, MAJOR_TYPE_TEXT_STRING, text.len() as u64);
+    cbor.extend_from_slice(text.as_bytes());
+}
+
+/// Encodes the initial byte of a CBOR data item, followed by its argument in the
+/// shortest possible form.
+fn encode_head(cbor: &mut Vec<u8>, major_type: u8, argument: u64) {
+    let major_type = major_type << 5;
+
+    if argument < 24 {
+        cbor.push(major_type | argument as u8);
+    } else if argument <= u8::MAX as u64 {
+        cbor.push(major_type | 24);
+        cbor.push(argument as u8);
+    } else if argument <= u16::MAX as u64 {
+        cbor.push(major_type | 25);
+        cbor.extend_from_slice(&(argument as u16).to_be_bytes());
+    } else if argument <= u32::MAX as u64 {
+        cbor.push(major_type | 26);
+        cbor.extend_from_slice(&(argument as u32).to_be_bytes());
+    } else {
+        cbor.push(major_type | 27);
+        cbor.extend_from_slice(&argument.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_cbor_string_array;
+
+    #[test]
+    fn encodes_heads_in_shortest_form() {
+        let mut cbor = vec![];
+        encode_head(&mut cbor, MAJOR_TYPE_UNSIGNED_INT, 23);
+        encode_head(&mut cbor, MAJOR_TYPE_BYTE_STRING, 24);
+        encode_head(&mut cbor, MAJOR_TYPE_TEXT_STRING, 256);
+        encode_head(&mut cbor, MAJOR_TYPE_ARRAY, 65_536);
+        encode_head(&mut cbor, MAJOR_TYPE_MAP, 4_294_967_296);
+
+        assert_eq!(
+            cbor,
+            vec![
+                0x17, // unsigned int 23
+                0x58, 0x18, // byte string of length 24
+                0x79, 0x01, 0x00, // text string of length 256
+                0x9a, 0x00, 0x01, 0x00, 0x00, // array of length 65536
+                0xbb, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, // map of length 2^32
+            ]
+        );
+    }
+
+    #[test]
+    fn string_array_round_trip() {
+        let strings = vec![
+            "http_expr".to_string(),
+            "".to_string(),
+            "a".repeat(300),
+            "<*>".to_string(),
+        ];
+
+        let cbor = encode_cbor_string_array(&strings);
+
+        assert_eq!(&cbor[..3], &SELF_DESCRIBE_TAG);
+        assert_eq!(parse_cbor_string_array(&cbor).unwrap(), strings);
+    }
+
+    #[test]
+    fn empty_string_array_round_trip() {
+        let cbor = encode_cbor_string_array::<&str>(&[]);
+
+        assert_eq!(cbor, vec![0xd9, 0xd9, 0xf7, 0x80]);
+        assert_eq!(
+            parse_cbor_string_array(&cbor).unwrap(),
+            Vec::<String>::new()
+        );
+    }
+}
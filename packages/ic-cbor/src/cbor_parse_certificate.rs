@@ -1,26 +1,41 @@
-use crate::{parse_cbor, parsed_cbor_to_tree, CborError, CborResult, CborValue};
+use crate::{
+    encode_certificate, parse_cbor_with_limits, parsed_cbor_to_tree, CborError, CborLimits,
+    CborResult, CborValue,
+};
 use ic_certification::{Certificate, Delegation};
 
 pub trait CertificateToCbor {
-    fn from_cbor(cbor: &[u8]) -> CborResult<Certificate>;
+    /// Decodes the CBOR with the [default limits](CborLimits::default).
+    fn from_cbor(cbor: &[u8]) -> CborResult<Certificate> {
+        Self::from_cbor_with_limits(cbor, &CborLimits::default())
+    }
+
+    /// Decodes the CBOR within the provided limits.
+    fn from_cbor_with_limits(cbor: &[u8], limits: &CborLimits) -> CborResult<Certificate>;
+
+    fn to_cbor(&self) -> Vec<u8>;
 }
 
 impl CertificateToCbor for Certificate {
-    fn from_cbor(cbor: &[u8]) -> CborResult<Certificate> {
-        let parsed_cbor = parse_cbor(cbor).map_err(|e| CborError::MalformedCbor(e.to_string()))?;
+    fn from_cbor_with_limits(cbor: &[u8], limits: &CborLimits) -> CborResult<Certificate> {
+        let parsed_cbor = parse_cbor_with_limits(cbor, limits)?.without_self_describe_tag();
 
         parsed_cbor_to_certificate(parsed_cbor)
     }
+
+    fn to_cbor(&self) -> Vec<u8> {
+        encode_certificate(self)
+    }
 }
 
 fn parsed_cbor_to_certificate(parsed_cbor: CborValue) -> CborResult<Certificate> {
-    let CborValue::Map(map) = parsed_cbor else {
+    let CborValue::Map(_) = parsed_cbor else {
         return Err(CborError::MalformedCertificate(
             "Expected Map when parsing Certificate Cbor".into()
         ));
     };
 
-    let Some(tree_cbor) = map.get("tree") else {
+    let Some(tree_cbor) = parsed_cbor.get("tree") else {
         return Err(CborError::MalformedCertificate(
             "Expected Tree when parsing Certificate Cbor".into()
         ));
@@ -28,7 +43,7 @@ fn parsed_cbor_to_certificate(parsed_cbor: CborValue) -> CborResult<Certificate>
 
     let tree = parsed_cbor_to_tree(tree_cbor)?;
 
-    let signature = if let Some(CborValue::ByteString(signature)) = map.get("signature") {
+    let signature = if let Some(CborValue::ByteString(signature)) = parsed_cbor.get("signature") {
         signature.to_owned()
     } else {
         return Err(CborError::MalformedCertificate(
@@ -36,7 +51,8 @@ fn parsed_cbor_to_certificate(parsed_cbor: CborValue) -> CborResult<Certificate>
         ));
     };
 
-    let delegation = if let Some(CborValue::Map(delegation_map)) = map.get("delegation") {
+    let delegation = if let Some(delegation_map @ CborValue::Map(_)) = parsed_cbor.get("delegation")
+    {
         let Some(CborValue::ByteString(subnet_id)) = delegation_map.get("subnet_id") else {
             return Err(CborError::MalformedCertificate(
                 "Expected Delegation Map to contain a Subnet ID when parsing Certificate Cbor".into()
@@ -93,4 +109,39 @@ mod tests {
 
         assert_eq!(result, certificate);
     }
+
+    #[test]
+    fn serialize_to_cbor() {
+        let certificate = create_certificate(None);
+
+        let cbor = certificate.to_cbor();
+
+        assert_eq!(cbor, cbor_encode(&certificate));
+        assert_eq!(Certificate::from_cbor(&cbor).unwrap(), certificate);
+    }
+
+    #[test]
+    fn serialize_to_cbor_with_delegation() {
+        let mut certificate = create_certificate(None);
+        certificate.delegation = Some(create_certificate_delegation());
+
+        let cbor = certificate.to_cbor();
+
+        assert_eq!(cbor, cbor_encode(&certificate));
+        assert_eq!(Certificate::from_cbor(&cbor).unwrap(), certificate);
+    }
+
+    #[test]
+    fn deserialize_from_cbor_with_limits() {
+        let certificate = create_certificate(None);
+        let cbor = cbor_encode(&certificate);
+        let limits = CborLimits {
+            max_size: cbor.len() - 1,
+            ..CborLimits::default()
+        };
+
+        let result = Certificate::from_cbor_with_limits(&cbor, &limits);
+
+        assert!(matches!(result, Err(CborError::MaxSizeExceeded { .. })));
+    }
 }
@@ -1,89 +1,114 @@
-use crate::{parse_cbor, CborError, CborHashTree, CborResult, CborValue};
+use crate::{
+    encode_hash_tree, parse_cbor_with_limits, CborError, CborLimits, CborResult, CborUnsignedInt,
+    CborValue,
+};
 use ic_certification::{
     hash_tree::{empty, fork, label, leaf, pruned, Hash, Label},
     HashTree,
 };
 
 pub trait HashTreeToCbor {
-    fn from_cbor(cbor: &[u8]) -> CborResult<HashTree>;
+    /// Decodes the CBOR with the [default limits](CborLimits::default).
+    fn from_cbor(cbor: &[u8]) -> CborResult<HashTree> {
+        Self::from_cbor_with_limits(cbor, &CborLimits::default())
+    }
+
+    /// Decodes the CBOR within the provided limits.
+    fn from_cbor_with_limits(cbor: &[u8], limits: &CborLimits) -> CborResult<HashTree>;
+
+    fn to_cbor(&self) -> Vec<u8>;
 }
 
 impl HashTreeToCbor for HashTree {
-    fn from_cbor(cbor: &[u8]) -> CborResult<HashTree> {
-        let parsed_cbor = parse_cbor(cbor).map_err(|e| CborError::MalformedCbor(e.to_string()))?;
+    fn from_cbor_with_limits(cbor: &[u8], limits: &CborLimits) -> CborResult<HashTree> {
+        let parsed_cbor = parse_cbor_with_limits(cbor, limits)?.without_self_describe_tag();
 
         parsed_cbor_to_tree(&parsed_cbor)
     }
+
+    fn to_cbor(&self) -> Vec<u8> {
+        encode_hash_tree(self)
+    }
 }
 
-pub fn parsed_cbor_to_tree(parsed_cbor: &CborValue) -> CborResult<HashTree> {
-    if let CborValue::Array(mut cbor_tags) = parsed_cbor.to_owned() {
-        cbor_tags.reverse();
-
-        if let Some(CborValue::HashTree(hash_tree_tag)) = cbor_tags.pop() {
-            match hash_tree_tag {
-                CborHashTree::Empty => Ok(empty()),
-
-                CborHashTree::Leaf => {
-                    if let Some(CborValue::ByteString(data)) = cbor_tags.pop() {
-                        Ok(leaf(data))
-                    } else {
-                        Err(CborError::MalformedHashTree(String::from(
-                            "Missing ByteString for Leaf node",
-                        )))
-                    }
-                }
-
-                CborHashTree::Pruned => {
-                    if let Some(CborValue::ByteString(data)) = cbor_tags.pop() {
-                        let digest: Hash = TryFrom::<&[u8]>::try_from(data.as_ref())
-                            .map_err(CborError::IncorrectPrunedDataLength)?;
-
-                        Ok(pruned(digest))
-                    } else {
-                        Err(CborError::MalformedHashTree(String::from(
-                            "Missing ByteString for Pruned node",
-                        )))
-                    }
-                }
-
-                CborHashTree::Labelled => {
-                    if let (Some(CborValue::ByteString(data)), Some(child_tag)) =
-                        (cbor_tags.pop(), cbor_tags.pop())
-                    {
-                        let node_label = Label::from(data);
-                        let child_node = parsed_cbor_to_tree(&child_tag)?;
-
-                        Ok(label(node_label, child_node))
-                    } else {
-                        Err(CborError::MalformedHashTree(String::from(
-                            "Missing ByteString or child node for Labelled node",
-                        )))
-                    }
-                }
-
-                CborHashTree::Fork => {
-                    if let (Some(left_tag), Some(right_tag)) = (cbor_tags.pop(), cbor_tags.pop()) {
-                        let left = parsed_cbor_to_tree(&left_tag)?;
-                        let right = parsed_cbor_to_tree(&right_tag)?;
-
-                        Ok(fork(left, right))
-                    } else {
-                        Err(CborError::MalformedHashTree(String::from(
-                            "Missing child nodes for Fork node",
-                        )))
-                    }
-                }
-            }
-        } else {
-            Err(CborError::MalformedHashTree(String::from(
+/// The tag of a hash tree node, the first element of the array that a node is encoded as.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CborHashTree {
+    Empty,
+    Fork,
+    Labelled,
+    Leaf,
+    Pruned,
+}
+
+impl TryFrom<&CborValue> for CborHashTree {
+    type Error = CborError;
+
+    fn try_from(value: &CborValue) -> CborResult<Self> {
+        match value {
+            CborValue::Unsigned(CborUnsignedInt::UInt8(0)) => Ok(CborHashTree::Empty),
+            CborValue::Unsigned(CborUnsignedInt::UInt8(1)) => Ok(CborHashTree::Fork),
+            CborValue::Unsigned(CborUnsignedInt::UInt8(2)) => Ok(CborHashTree::Labelled),
+            CborValue::Unsigned(CborUnsignedInt::UInt8(3)) => Ok(CborHashTree::Leaf),
+            CborValue::Unsigned(CborUnsignedInt::UInt8(4)) => Ok(CborHashTree::Pruned),
+            _ => Err(CborError::MalformedHashTree(String::from(
                 "Expected Hash Tree cbor tag",
-            )))
+            ))),
         }
-    } else {
-        Err(CborError::MalformedHashTree(String::from(
+    }
+}
+
+/// Decodes a hash tree from a generic [CborValue], where each node is an array whose first
+/// element is the node's [CborHashTree] tag, followed by the node's contents.
+pub fn parsed_cbor_to_tree(parsed_cbor: &CborValue) -> CborResult<HashTree> {
+    let CborValue::Array(cbor_tags) = parsed_cbor else {
+        return Err(CborError::MalformedHashTree(String::from(
             "Expected Array cbor tag",
-        )))
+        )));
+    };
+
+    let Some(hash_tree_tag) = cbor_tags.first() else {
+        return Err(CborError::MalformedHashTree(String::from(
+            "Expected Hash Tree cbor tag",
+        )));
+    };
+
+    match (CborHashTree::try_from(hash_tree_tag)?, &cbor_tags[1..]) {
+        (CborHashTree::Empty, []) => Ok(empty()),
+        (CborHashTree::Leaf, [CborValue::ByteString(data)]) => Ok(leaf(data.to_owned())),
+        (CborHashTree::Leaf, _) => Err(CborError::MalformedHashTree(String::from(
+            "Missing ByteString for Leaf node",
+        ))),
+        (CborHashTree::Pruned, [CborValue::ByteString(data)]) => {
+            let digest: Hash = TryFrom::<&[u8]>::try_from(data.as_ref())
+                .map_err(CborError::IncorrectPrunedDataLength)?;
+
+            Ok(pruned(digest))
+        }
+        (CborHashTree::Pruned, _) => Err(CborError::MalformedHashTree(String::from(
+            "Missing ByteString for Pruned node",
+        ))),
+        (CborHashTree::Labelled, [CborValue::ByteString(data), child_tag]) => {
+            let node_label = Label::from(data.to_owned());
+            let child_node = parsed_cbor_to_tree(child_tag)?;
+
+            Ok(label(node_label, child_node))
+        }
+        (CborHashTree::Labelled, _) => Err(CborError::MalformedHashTree(String::from(
+            "Missing ByteString or child node for Labelled node",
+        ))),
+        (CborHashTree::Fork, [left_tag, right_tag]) => {
+            let left = parsed_cbor_to_tree(left_tag)?;
+            let right = parsed_cbor_to_tree(right_tag)?;
+
+            Ok(fork(left, right))
+        }
+        (CborHashTree::Fork, _) => Err(CborError::MalformedHashTree(String::from(
+            "Missing child nodes for Fork node",
+        ))),
+        (CborHashTree::Empty, _) => Err(CborError::MalformedHashTree(String::from(
+            "Unexpected contents for Empty node",
+        ))),
     }
 }
 
@@ -93,7 +118,7 @@ mod tests {
     use ic_certification::hash_tree::{
         empty, fork, label, leaf, pruned, pruned_from_hex, Label, LookupResult,
     };
-    use ic_response_verification_test_utils::{cbor_encode, hex_encode};
+    use ic_response_verification_test_utils::{cbor_encode, hex_decode, hex_encode};
 
     fn lookup_path<'a, P: AsRef<[&'static str]>>(tree: &'a HashTree, path: P) -> LookupResult<'a> {
         let path: Vec<Label<Vec<u8>>> = path
@@ -105,6 +130,54 @@ mod tests {
         tree.lookup_path(&path)
     }
 
+    #[test]
+    fn encodes_to_cbor() {
+        let original_tree: HashTree = fork(
+            fork(
+                label(
+                    "a",
+                    fork(
+                        fork(label("x", leaf(b"hello".to_vec())), empty()),
+                        label("y", leaf(b"world".to_vec())),
+                    ),
+                ),
+                label("b", pruned([1; 32])),
+            ),
+            fork(label("c", empty()), label("d", leaf(vec![0; 300]))),
+        );
+
+        let tree_cbor = original_tree.to_cbor();
+
+        assert_eq!(tree_cbor, cbor_encode(&original_tree));
+        assert_eq!(HashTree::from_cbor(&tree_cbor).unwrap(), original_tree);
+    }
+
+    #[test]
+    fn fails_to_decode_malformed_nodes() {
+        let cases = [
+            // unknown node tag
+            "d9d9f7820543010203",
+            // node tag is not an unsigned int
+            "d9d9f782613343010203",
+            // leaf with a text string
+            "d9d9f782036161",
+            // fork with a single child
+            "d9d9f782018100",
+        ];
+
+        for cbor_hex in cases {
+            let cbor = hex_decode(cbor_hex);
+
+            assert!(
+                matches!(
+                    HashTree::from_cbor(&cbor),
+                    Err(CborError::MalformedHashTree(_))
+                ),
+                "{cbor_hex}"
+            );
+        }
+    }
+
     #[test]
     fn works_with_simple_tree() {
         let original_tree: HashTree = fork(
@@ -1,22 +1,21 @@
 use crate::{CborError, CborResult};
 use candid::Principal;
 use nom::{
-    bytes::complete::take,
-    combinator::{eof, map, peek},
+    combinator::{map, peek},
     error::{Error, ErrorKind},
-    multi::{count, fold_many_m_n},
     number::complete::{be_u16, be_u32, be_u64, be_u8},
-    sequence::terminated,
     Err, IResult,
 };
-use std::{collections::HashMap, fmt};
+use std::fmt;
 
+/// A negative integer, CBOR major type 1. The variant is one size wider than the encoded argument,
+/// so that every value fits, from -256 for a one byte argument to -2^64 for an eight byte one.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum CborNegativeInt {
-    Int8(i8),
     Int16(i16),
     Int32(i32),
     Int64(i64),
+    Int128(i128),
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -28,26 +27,28 @@ pub enum CborUnsignedInt {
 }
 
 impl CborUnsignedInt {
-    fn to_usize(self) -> usize {
-        match self {
-            CborUnsignedInt::UInt8(v) => v as usize,
-            CborUnsignedInt::UInt16(v) => v as usize,
-            CborUnsignedInt::UInt32(v) => v as usize,
-            CborUnsignedInt::UInt64(v) => v as usize,
-        }
-    }
-
     // https://www.rfc-editor.org/rfc/rfc8949.html#section-3.1
     // The value of a Cbor Major type 1 (negative int) is encoded as its positive counterpart - 1
     // For example: -5 is encoded as 4
     // So to decode the value we take -1 - n where n is the encoded value
     // For example: -1 - 4 = -5
+    // The result is widened, since -1 - n does not fit in a signed integer of the same size as n
+    // For example: the largest u8 is 255, which decodes to -256
     fn to_negative(self) -> CborNegativeInt {
         match self {
-            CborUnsignedInt::UInt8(n) => CborNegativeInt::Int8(-1 - (n as i8)),
-            CborUnsignedInt::UInt16(n) => CborNegativeInt::Int16(-1 - (n as i16)),
-            CborUnsignedInt::UInt32(n) => CborNegativeInt::Int32(-1 - (n as i32)),
-            CborUnsignedInt::UInt64(n) => CborNegativeInt::Int64(-1 - (n as i64)),
+            CborUnsignedInt::UInt8(n) => CborNegativeInt::Int16(-1 - i16::from(n)),
+            CborUnsignedInt::UInt16(n) => CborNegativeInt::Int32(-1 - i32::from(n)),
+            CborUnsignedInt::UInt32(n) => CborNegativeInt::Int64(-1 - i64::from(n)),
+            CborUnsignedInt::UInt64(n) => CborNegativeInt::Int128(-1 - i128::from(n)),
+        }
+    }
+
+    fn to_u64(self) -> u64 {
+        match self {
+            CborUnsignedInt::UInt8(v) => v.into(),
+            CborUnsignedInt::UInt16(v) => v.into(),
+            CborUnsignedInt::UInt32(v) => v.into(),
+            CborUnsignedInt::UInt64(v) => v,
         }
     }
 
@@ -59,23 +60,61 @@ impl CborUnsignedInt {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub enum CborHashTree {
-    Empty,
-    Fork,
-    Labelled,
-    Leaf,
-    Pruned,
+/// A simple value, CBOR major type 7 without floats.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CborSimpleValue {
+    False,
+    True,
+    Null,
+    Undefined,
+    /// A simple value that is unassigned or reserved.
+    Unassigned(u8),
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// A CBOR data item, as described in [RFC 8949](https://www.rfc-editor.org/rfc/rfc8949.html).
+///
+/// The value is decoded as it is encoded, without any knowledge of the schema of the data. Use
+/// [parsed_cbor_to_tree](crate::parsed_cbor_to_tree) to decode a hash tree from a value.
+#[derive(Debug, Clone, PartialEq)]
 pub enum CborValue {
     Unsigned(CborUnsignedInt),
     Signed(CborNegativeInt),
     ByteString(Vec<u8>),
+    TextString(String),
     Array(Vec<CborValue>),
-    Map(HashMap<String, CborValue>),
-    HashTree(CborHashTree),
+    /// The entries of a map, in the order they are encoded.
+    Map(Vec<(CborValue, CborValue)>),
+    /// A tag number and the tagged data item.
+    Tag(u64, Box<CborValue>),
+    Simple(CborSimpleValue),
+    /// A half, single or double precision float, converted to double precision.
+    Float(f64),
+}
+
+impl CborValue {
+    /// Returns the value of the first entry of a map whose key is the text string `key`, or
+    /// `None` if the value is not a map or there is no such entry.
+    pub fn get(&self, key: &str) -> Option<&CborValue> {
+        let CborValue::Map(entries) = self else {
+            return None;
+        };
+
+        entries
+            .iter()
+            .find_map(|(entry_key, value)| match entry_key {
+                CborValue::TextString(entry_key) if entry_key == key => Some(value),
+                _ => None,
+            })
+    }
+
+    /// Removes the [self-describe tag](https://www.rfc-editor.org/rfc/rfc8949.html#section-3.4.6)
+    /// that marks the data as CBOR, which carries no meaning of its own.
+    pub fn without_self_describe_tag(self) -> CborValue {
+        match self {
+            CborValue::Tag(SELF_DESCRIBE_TAG_NUMBER, value) => value.without_self_describe_tag(),
+            value => value,
+        }
+    }
 }
 
 impl fmt::Display for CborValue {
@@ -84,6 +123,9 @@ impl fmt::Display for CborValue {
     }
 }
 
+/// The number of the self-describe tag.
+const SELF_DESCRIBE_TAG_NUMBER: u64 = 55799;
+
 /// Cbor major type information is stored in the high-order 3 bits.
 const fn get_cbor_type(e: u8) -> u8 {
     (e & 0b1110_0000) >> 5
@@ -108,6 +150,10 @@ fn extract_cbor_info(i: &[u8]) -> IResult<&[u8], u8> {
     map(be_u8, get_cbor_info)(i)
 }
 
+fn peek_cbor_info(i: &[u8]) -> IResult<&[u8], u8> {
+    peek(extract_cbor_info)(i)
+}
+
 fn extract_cbor_value(i: &[u8]) -> IResult<&[u8], CborUnsignedInt> {
     let (i, cbor_info) = extract_cbor_info(i)?;
 
@@ -121,91 +167,244 @@ fn extract_cbor_value(i: &[u8]) -> IResult<&[u8], CborUnsignedInt> {
     }
 }
 
-fn extract_key_val_pair(i: &[u8]) -> IResult<&[u8], (String, CborValue)> {
-    let (i, key) = parser(i)?;
+/// Decodes major type 7, where the additional information distinguishes simple values from
+/// floats of different precisions.
+fn extract_simple_or_float(cbor_info: u8, cbor_value: CborUnsignedInt) -> CborValue {
+    match (cbor_info, cbor_value) {
+        (25, CborUnsignedInt::UInt16(bits)) => CborValue::Float(half_to_f64(bits)),
+        (26, CborUnsignedInt::UInt32(bits)) => CborValue::Float(f32::from_bits(bits).into()),
+        (27, CborUnsignedInt::UInt64(bits)) => CborValue::Float(f64::from_bits(bits)),
+        (_, value) => CborValue::Simple(match value.to_u8() {
+            Ok(20) => CborSimpleValue::False,
+            Ok(21) => CborSimpleValue::True,
+            Ok(22) => CborSimpleValue::Null,
+            Ok(23) => CborSimpleValue::Undefined,
+            Ok(n) => CborSimpleValue::Unassigned(n),
+            Err(_) => unreachable!("simple values are at most one byte"),
+        }),
+    }
+}
 
-    let key = match key {
-        CborValue::ByteString(byte_string) => match String::from_utf8(byte_string) {
-            Ok(str) => Ok(str),
-            _ => Err(Err::Error(Error::new(i, ErrorKind::Alt))),
-        },
-        _ => Err(Err::Error(Error::new(i, ErrorKind::Alt))),
-    }?;
+/// Converts a half precision float to double precision, as described in
+/// <https://www.rfc-editor.org/rfc/rfc8949.html#name-half-precision>
+fn half_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+
+    match exponent {
+        0 => sign * mantissa * 2f64.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f64::INFINITY,
+        0x1f => f64::NAN,
+        _ => sign * (mantissa + 1024.0) * 2f64.powi(exponent - 25),
+    }
+}
+
+/// The default maximum nesting depth of arrays, maps and tags, see [CborLimits::max_depth]. This
+/// is the recursion limit of `serde_cbor`, which agents use to decode certificates.
+pub const DEFAULT_MAX_CBOR_DEPTH: usize = 128;
+
+/// The default maximum number of data items, see [CborLimits::max_items].
+pub const DEFAULT_MAX_CBOR_ITEMS: usize = 1_000_000;
 
-    let (i, val) = parser(i)?;
+/// The default maximum size of the encoded CBOR, in bytes, see [CborLimits::max_size].
+pub const DEFAULT_MAX_CBOR_SIZE: usize = 16 * 1024 * 1024;
 
-    Ok((i, (key, val)))
+/// Limits on the CBOR that is parsed, to bound the stack and memory that are used to parse
+/// untrusted input. The defaults are generous enough for real certificates and hash trees.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CborLimits {
+    /// The maximum nesting depth of arrays, maps and tags. A value that is not nested has a
+    /// depth of zero.
+    pub max_depth: usize,
+    /// The maximum number of data items, counting every array element, map key and map value,
+    /// tagged item and the top level item.
+    pub max_items: usize,
+    /// The maximum size of the encoded CBOR, in bytes.
+    pub max_size: usize,
 }
 
-fn parser(i: &[u8]) -> IResult<&[u8], CborValue> {
-    let (i, cbor_type) = peek_cbor_type(i)?;
-    let (i, cbor_value) = extract_cbor_value(i)?;
+impl Default for CborLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_CBOR_DEPTH,
+            max_items: DEFAULT_MAX_CBOR_ITEMS,
+            max_size: DEFAULT_MAX_CBOR_SIZE,
+        }
+    }
+}
 
-    return match cbor_type {
-        0 => {
-            // Hash Tree nodes are encoded as unsigned int instead of tagged data items,
-            // if we ever need to decode an actual unsigned int with a value 0-4 then this will break
-            Ok((
-                i,
-                match cbor_value.to_u8() {
-                    Ok(0) => CborValue::HashTree(CborHashTree::Empty),
-                    Ok(1) => CborValue::HashTree(CborHashTree::Fork),
-                    Ok(2) => CborValue::HashTree(CborHashTree::Labelled),
-                    Ok(3) => CborValue::HashTree(CborHashTree::Leaf),
-                    Ok(4) => CborValue::HashTree(CborHashTree::Pruned),
-                    _ => CborValue::Unsigned(cbor_value),
-                },
-            ))
+struct CborParser {
+    limits: CborLimits,
+    items: usize,
+}
+
+impl CborParser {
+    fn parse<'a>(&mut self, i: &'a [u8], depth: usize) -> CborResult<(&'a [u8], CborValue)> {
+        if depth > self.limits.max_depth {
+            return Err(CborError::MaxDepthExceeded {
+                max_depth: self.limits.max_depth,
+            });
         }
+        self.reserve_items(1)?;
+
+        let (i, cbor_type) = peek_cbor_type(i).map_err(malformed_cbor)?;
+        let (i, cbor_info) = peek_cbor_info(i).map_err(malformed_cbor)?;
+        let (i, cbor_value) = extract_cbor_value(i).map_err(malformed_cbor)?;
+
+        match cbor_type {
+            0 => Ok((i, CborValue::Unsigned(cbor_value))),
+
+            1 => Ok((i, CborValue::Signed(cbor_value.to_negative()))),
+
+            2 => {
+                let (i, data) = take_bytes(i, cbor_value)?;
+
+                Ok((i, CborValue::ByteString(data.to_vec())))
+            }
 
-        1 => Ok((i, CborValue::Signed(cbor_value.to_negative()))),
+            3 => {
+                let (i, data) = take_bytes(i, cbor_value)?;
+                let text = String::from_utf8(data.to_vec())?;
 
-        2 | 3 => {
-            let data_len = cbor_value.to_usize();
-            let (i, data) = take(data_len)(i)?;
+                Ok((i, CborValue::TextString(text)))
+            }
 
-            Ok((i, CborValue::ByteString(data.to_vec())))
+            4 => self.parse_array(i, cbor_value, depth),
+
+            5 => self.parse_map(i, cbor_value, depth),
+
+            6 => {
+                let (i, data) = self.parse(i, depth + 1)?;
+
+                Ok((i, CborValue::Tag(cbor_value.to_u64(), Box::new(data))))
+            }
+
+            7 => Ok((i, extract_simple_or_float(cbor_info, cbor_value))),
+
+            _ => unreachable!("major types are three bits"),
+        }
+    }
+
+    fn parse_array<'a>(
+        &mut self,
+        mut i: &'a [u8],
+        len: CborUnsignedInt,
+        depth: usize,
+    ) -> CborResult<(&'a [u8], CborValue)> {
+        // every element is encoded in at least one byte
+        let len = checked_len(i, len, 1)?;
+        self.reserve_items(len)?;
+
+        let mut data = Vec::with_capacity(len);
+        for _ in 0..len {
+            let (rest, value) = self.parse_reserved(i, depth + 1)?;
+            i = rest;
+            data.push(value);
         }
 
-        4 => {
-            let data_len = cbor_value.to_usize();
-            let (i, data) = count(parser, data_len)(i)?;
+        Ok((i, CborValue::Array(data)))
+    }
 
-            Ok((i, CborValue::Array(data)))
+    fn parse_map<'a>(
+        &mut self,
+        mut i: &'a [u8],
+        len: CborUnsignedInt,
+        depth: usize,
+    ) -> CborResult<(&'a [u8], CborValue)> {
+        // every entry is encoded in at least two bytes
+        let len = checked_len(i, len, 2)?;
+        self.reserve_items(len.saturating_mul(2))?;
+
+        let mut data = Vec::with_capacity(len);
+        for _ in 0..len {
+            let (rest, key) = self.parse_reserved(i, depth + 1)?;
+            let (rest, value) = self.parse_reserved(rest, depth + 1)?;
+            i = rest;
+            data.push((key, value));
         }
 
-        5 => {
-            let data_len = cbor_value.to_usize();
-            let (i, data) = fold_many_m_n(
-                0,
-                data_len,
-                extract_key_val_pair,
-                || HashMap::with_capacity(data_len),
-                |mut acc, (key, val)| {
-                    acc.insert(key, val);
-                    acc
-                },
-            )(i)?;
-
-            Ok((i, CborValue::Map(data)))
+        Ok((i, CborValue::Map(data)))
+    }
+
+    /// Parses an array element or map entry, whose item was already counted by its container.
+    fn parse_reserved<'a>(
+        &mut self,
+        i: &'a [u8],
+        depth: usize,
+    ) -> CborResult<(&'a [u8], CborValue)> {
+        self.items -= 1;
+
+        self.parse(i, depth)
+    }
+
+    fn reserve_items(&mut self, items: usize) -> CborResult {
+        self.items = self.items.saturating_add(items);
+        if self.items > self.limits.max_items {
+            return Err(CborError::MaxItemsExceeded {
+                max_items: self.limits.max_items,
+            });
         }
 
-        // ignore custom data tags and floats, we don't currently need them
-        6 => parser(i),
-        7 => parser(i),
+        Ok(())
+    }
+}
 
-        _ => Err(Err::Error(Error::new(i, ErrorKind::Alt))),
-    };
+/// Checks the length of a string, array or map against the remaining input, before anything is
+/// allocated for it, given the minimum number of bytes that each of its elements is encoded in.
+fn checked_len(i: &[u8], len: CborUnsignedInt, min_element_size: usize) -> CborResult<usize> {
+    let len = usize::try_from(len.to_u64()).map_err(|_| CborError::UnexpectedEndOfInput)?;
+    if len.saturating_mul(min_element_size) > i.len() {
+        return Err(CborError::UnexpectedEndOfInput);
+    }
+
+    Ok(len)
+}
+
+fn take_bytes(i: &[u8], len: CborUnsignedInt) -> CborResult<(&[u8], &[u8])> {
+    let len = checked_len(i, len, 1)?;
+    let (data, i) = i.split_at(len);
+
+    Ok((i, data))
 }
 
-pub fn parse_cbor(i: &[u8]) -> Result<CborValue, nom::Err<Error<&[u8]>>> {
-    let (_remaining, result) = terminated(parser, eof)(i)?;
+fn malformed_cbor(e: Err<Error<&[u8]>>) -> CborError {
+    CborError::MalformedCbor(e.to_string())
+}
+
+/// Parses a single CBOR data item with the [default limits](CborLimits::default), failing if
+/// there is any remaining input.
+pub fn parse_cbor(i: &[u8]) -> CborResult<CborValue> {
+    parse_cbor_with_limits(i, &CborLimits::default())
+}
+
+/// Parses a single CBOR data item within the provided limits, failing if there is any remaining
+/// input.
+pub fn parse_cbor_with_limits(i: &[u8], limits: &CborLimits) -> CborResult<CborValue> {
+    if i.len() > limits.max_size {
+        return Err(CborError::MaxSizeExceeded {
+            max_size: limits.max_size,
+            size: i.len(),
+        });
+    }
+
+    let mut parser = CborParser {
+        limits: *limits,
+        items: 0,
+    };
+    let (i, result) = parser.parse(i, 0)?;
+    if !i.is_empty() {
+        return Err(CborError::MalformedCbor(format!(
+            "{} bytes remaining after the data item",
+            i.len()
+        )));
+    }
 
     Ok(result)
 }
 
 pub fn parse_cbor_principals_array(i: &[u8]) -> CborResult<Vec<(Principal, Principal)>> {
-    let parsed_cbor = parse_cbor(i).map_err(|e| CborError::MalformedCbor(e.to_string()))?;
+    let parsed_cbor = parse_cbor(i)?.without_self_describe_tag();
 
     let CborValue::Array(ranges_entries) = parsed_cbor else {
         return Err(CborError::MalformedCborCanisterRanges);
@@ -232,7 +431,7 @@ pub fn parse_cbor_principals_array(i: &[u8]) -> CborResult<Vec<(Principal, Princ
 }
 
 pub fn parse_cbor_string_array(i: &[u8]) -> CborResult<Vec<String>> {
-    let parsed_cbor = parse_cbor(i).map_err(|e| CborError::MalformedCbor(e.to_string()))?;
+    let parsed_cbor = parse_cbor(i)?.without_self_describe_tag();
 
     let CborValue::Array(elems) = parsed_cbor else {
         return Err(CborError::UnexpectedCborNodeType {
@@ -243,15 +442,15 @@ pub fn parse_cbor_string_array(i: &[u8]) -> CborResult<Vec<String>> {
 
     elems
         .iter()
-        .map(|elem| {
-            let CborValue::ByteString(elem) = elem else {
-                return Err(CborError::UnexpectedCborNodeType {
-                    expected_type: "Array".into(),
-                    found_type: elem.to_string()
-                });
-            };
-
-            String::from_utf8(elem.to_owned()).map_err(CborError::Utf8ConversionError)
+        .map(|elem| match elem {
+            CborValue::TextString(elem) => Ok(elem.to_owned()),
+            CborValue::ByteString(elem) => {
+                String::from_utf8(elem.to_owned()).map_err(CborError::Utf8ConversionError)
+            }
+            _ => Err(CborError::UnexpectedCborNodeType {
+                expected_type: "TextString".into(),
+                found_type: elem.to_string(),
+            }),
         })
         .collect::<Result<_, _>>()
 }
@@ -312,11 +511,11 @@ mod tests {
         assert_eq!(
             result,
             CborValue::Array(vec![
-                CborValue::ByteString(Vec::from("a")),
-                CborValue::Map(HashMap::from([(
-                    String::from("b"),
-                    CborValue::ByteString(Vec::from("c"))
-                )])),
+                CborValue::TextString(String::from("a")),
+                CborValue::Map(vec![(
+                    CborValue::TextString(String::from("b")),
+                    CborValue::TextString(String::from("c"))
+                )]),
             ])
         );
     }
@@ -330,20 +529,285 @@ mod tests {
 
         assert_eq!(
             result,
-            CborValue::Map(HashMap::from([
+            CborValue::Map(vec![
                 (
-                    String::from("a"),
+                    CborValue::TextString(String::from("a")),
                     CborValue::Unsigned(CborUnsignedInt::UInt8(7))
                 ),
                 (
-                    String::from("b"),
+                    CborValue::TextString(String::from("b")),
                     CborValue::Array(vec![
                         CborValue::Unsigned(CborUnsignedInt::UInt8(8)),
                         CborValue::Unsigned(CborUnsignedInt::UInt8(9)),
                     ])
                 ),
-            ]))
-        )
+            ])
+        );
+        assert_eq!(
+            result.get("a"),
+            Some(&CborValue::Unsigned(CborUnsignedInt::UInt8(7)))
+        );
+        assert_eq!(result.get("c"), None);
+    }
+
+    #[test]
+    fn decodes_map_with_non_string_keys() {
+        let cbor_hex = "a201020304";
+        let cbor = hex_decode(cbor_hex);
+
+        let result = parse_cbor(cbor.as_slice()).unwrap();
+
+        assert_eq!(
+            result,
+            CborValue::Map(vec![
+                (
+                    CborValue::Unsigned(CborUnsignedInt::UInt8(1)),
+                    CborValue::Unsigned(CborUnsignedInt::UInt8(2))
+                ),
+                (
+                    CborValue::Unsigned(CborUnsignedInt::UInt8(3)),
+                    CborValue::Unsigned(CborUnsignedInt::UInt8(4))
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn decodes_small_unsigned_ints() {
+        let cbor_hex = "850001020304";
+        let cbor = hex_decode(cbor_hex);
+
+        let result = parse_cbor(cbor.as_slice()).unwrap();
+
+        assert_eq!(
+            result,
+            CborValue::Array(
+                (0..=4)
+                    .map(|n| CborValue::Unsigned(CborUnsignedInt::UInt8(n)))
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    fn decodes_negative_ints() {
+        // examples from https://www.rfc-editor.org/rfc/rfc8949.html#appendix-A
+        let cases = [
+            ("20", CborNegativeInt::Int16(-1)),
+            ("29", CborNegativeInt::Int16(-10)),
+            ("3863", CborNegativeInt::Int16(-100)),
+            ("3903e7", CborNegativeInt::Int32(-1000)),
+            (
+                "3bffffffffffffffff",
+                CborNegativeInt::Int128(-18446744073709551616),
+            ),
+            // the largest argument of each size
+            ("38ff", CborNegativeInt::Int16(-256)),
+            ("39ffff", CborNegativeInt::Int32(-65536)),
+            ("3affffffff", CborNegativeInt::Int64(-4294967296)),
+        ];
+
+        for (cbor_hex, expected) in cases {
+            let cbor = hex_decode(cbor_hex);
+
+            let result = parse_cbor(cbor.as_slice()).unwrap();
+
+            assert_eq!(result, CborValue::Signed(expected), "{cbor_hex}");
+        }
+    }
+
+    #[test]
+    fn decodes_text_and_byte_strings() {
+        let cbor_hex = "8244010203046449455446";
+        let cbor = hex_decode(cbor_hex);
+
+        let result = parse_cbor(cbor.as_slice()).unwrap();
+
+        assert_eq!(
+            result,
+            CborValue::Array(vec![
+                CborValue::ByteString(vec![1, 2, 3, 4]),
+                CborValue::TextString(String::from("IETF")),
+            ])
+        );
+    }
+
+    #[test]
+    fn fails_to_decode_invalid_utf8_text_string() {
+        let cbor_hex = "62c328";
+        let cbor = hex_decode(cbor_hex);
+
+        assert!(parse_cbor(cbor.as_slice()).is_err());
+    }
+
+    #[test]
+    fn decodes_tags() {
+        let cbor_hex = "c074323031332d30332d32315432303a30343a30305a";
+        let cbor = hex_decode(cbor_hex);
+
+        let result = parse_cbor(cbor.as_slice()).unwrap();
+
+        assert_eq!(
+            result,
+            CborValue::Tag(
+                0,
+                Box::new(CborValue::TextString(String::from("2013-03-21T20:04:00Z")))
+            )
+        );
+    }
+
+    #[test]
+    fn removes_self_describe_tag() {
+        let cbor_hex = "d9d9f783070809";
+        let cbor = hex_decode(cbor_hex);
+
+        let result = parse_cbor(cbor.as_slice()).unwrap();
+
+        assert!(matches!(result, CborValue::Tag(55799, _)));
+        assert_eq!(
+            result.without_self_describe_tag(),
+            CborValue::Array(vec![
+                CborValue::Unsigned(CborUnsignedInt::UInt8(7)),
+                CborValue::Unsigned(CborUnsignedInt::UInt8(8)),
+                CborValue::Unsigned(CborUnsignedInt::UInt8(9)),
+            ])
+        );
+    }
+
+    #[test]
+    fn decodes_simple_values() {
+        let cbor_hex = "86f4f5f6f7f0f8ff";
+        let cbor = hex_decode(cbor_hex);
+
+        let result = parse_cbor(cbor.as_slice()).unwrap();
+
+        assert_eq!(
+            result,
+            CborValue::Array(vec![
+                CborValue::Simple(CborSimpleValue::False),
+                CborValue::Simple(CborSimpleValue::True),
+                CborValue::Simple(CborSimpleValue::Null),
+                CborValue::Simple(CborSimpleValue::Undefined),
+                CborValue::Simple(CborSimpleValue::Unassigned(16)),
+                CborValue::Simple(CborSimpleValue::Unassigned(255)),
+            ])
+        );
+    }
+
+    #[test]
+    fn decodes_floats() {
+        let cases = [
+            ("f90000", 0.0),
+            ("f93c00", 1.0),
+            ("f97bff", 65504.0),
+            ("f90001", 5.960464477539063e-8),
+            ("f9c400", -4.0),
+            ("f97c00", f64::INFINITY),
+            ("fa47c35000", 100000.0),
+            ("fb3ff199999999999a", 1.1),
+            ("fbc010666666666666", -4.1),
+        ];
+
+        for (cbor_hex, expected) in cases {
+            let cbor = hex_decode(cbor_hex);
+
+            let result = parse_cbor(cbor.as_slice()).unwrap();
+
+            assert_eq!(result, CborValue::Float(expected), "{cbor_hex}");
+        }
+
+        let cbor = hex_decode("f97e00");
+        assert!(matches!(
+            parse_cbor(cbor.as_slice()).unwrap(),
+            CborValue::Float(value) if value.is_nan()
+        ));
+    }
+
+    #[test]
+    fn fails_to_decode_cbor_nested_too_deeply() {
+        let limits = CborLimits {
+            max_depth: 2,
+            ..CborLimits::default()
+        };
+
+        assert!(parse_cbor_with_limits(&hex_decode("818100"), &limits).is_ok());
+        assert!(matches!(
+            parse_cbor_with_limits(&hex_decode("81818100"), &limits),
+            Err(CborError::MaxDepthExceeded { max_depth: 2 })
+        ));
+
+        let deeply_nested = [vec![0x81; DEFAULT_MAX_CBOR_DEPTH + 1], vec![0x00]].concat();
+        assert!(matches!(
+            parse_cbor(&deeply_nested),
+            Err(CborError::MaxDepthExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn fails_to_decode_cbor_with_too_many_items() {
+        let limits = CborLimits {
+            max_items: 4,
+            ..CborLimits::default()
+        };
+
+        assert!(parse_cbor_with_limits(&hex_decode("83070809"), &limits).is_ok());
+        assert!(parse_cbor_with_limits(&hex_decode("a1616107"), &limits).is_ok());
+        assert!(matches!(
+            parse_cbor_with_limits(&hex_decode("8407080910"), &limits),
+            Err(CborError::MaxItemsExceeded { max_items: 4 })
+        ));
+        assert!(matches!(
+            parse_cbor_with_limits(&hex_decode("a2616107616208"), &limits),
+            Err(CborError::MaxItemsExceeded { max_items: 4 })
+        ));
+    }
+
+    #[test]
+    fn fails_to_decode_cbor_that_is_too_large() {
+        let limits = CborLimits {
+            max_size: 3,
+            ..CborLimits::default()
+        };
+
+        assert!(matches!(
+            parse_cbor_with_limits(&hex_decode("83070809"), &limits),
+            Err(CborError::MaxSizeExceeded {
+                max_size: 3,
+                size: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn fails_to_decode_lengths_beyond_the_input() {
+        let cases = [
+            // array of 2^64 - 1 elements
+            "9bffffffffffffffff00",
+            // map of 2^32 - 1 entries
+            "baffffffff0000",
+            // byte string of 2^64 - 1 bytes
+            "5bffffffffffffffff00",
+            // array of 3 elements with 2 elements
+            "830708",
+        ];
+
+        for cbor_hex in cases {
+            assert!(
+                matches!(
+                    parse_cbor(&hex_decode(cbor_hex)),
+                    Err(CborError::UnexpectedEndOfInput)
+                ),
+                "{cbor_hex}"
+            );
+        }
+    }
+
+    #[test]
+    fn fails_to_decode_trailing_input() {
+        assert!(matches!(
+            parse_cbor(&hex_decode("0708")),
+            Err(CborError::MalformedCbor(_))
+        ));
     }
 
     #[test]
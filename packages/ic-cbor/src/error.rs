@@ -37,4 +37,27 @@ pub enum CborError {
 
     #[error("UnexpectedEndOfInput")]
     UnexpectedEndOfInput,
+
+    /// The CBOR was nested deeper than allowed by its limits
+    #[error("CBOR nesting depth exceeds the maximum of {max_depth}")]
+    MaxDepthExceeded {
+        /// The maximum nesting depth
+        max_depth: usize,
+    },
+
+    /// The CBOR contained more data items than allowed by its limits
+    #[error("CBOR contains more than the maximum of {max_items} data items")]
+    MaxItemsExceeded {
+        /// The maximum number of data items
+        max_items: usize,
+    },
+
+    /// The CBOR was larger than allowed by its limits
+    #[error("CBOR is {size} bytes long, which exceeds the maximum of {max_size} bytes")]
+    MaxSizeExceeded {
+        /// The maximum size of the CBOR, in bytes
+        max_size: usize,
+        /// The actual size of the CBOR, in bytes
+        size: usize,
+    },
 }
@@ -9,3 +9,6 @@ pub use error::*;
 
 mod cbor_parser;
 pub use cbor_parser::*;
+
+mod cbor_encoder;
+pub use cbor_encoder::*;
@@ -0,0 +1,277 @@
+use crate::error::{CertificateVerificationError, CertificateVerificationResult};
+use cached::{Cached, SizedCache};
+use std::{fmt, sync::Arc};
+
+/// Storage for the newest certificate time that was seen for each canister, used by a
+/// [CertificateFreshnessTracker].
+///
+/// [InMemoryCertificateTimeStore] is the default implementation, other implementations can
+/// persist the certificate times, or share them between processes.
+pub trait CertificateTimeStore: Send + Sync {
+    /// Returns the newest certificate time that was recorded for the canister, if any.
+    fn newest_certificate_time(&self, canister_id: &[u8]) -> Option<u128>;
+
+    /// Records a certificate time for the canister, keeping it only if it is newer than the newest
+    /// certificate time that was already recorded for the canister.
+    ///
+    /// Implementations must compare and update the certificate time atomically.
+    fn record_certificate_time(&self, canister_id: &[u8], certificate_time: u128);
+}
+
+/// A [CertificateTimeStore] that holds the certificate times of up to a fixed number of canisters
+/// in memory, evicting the canister that was least recently used when it is full.
+///
+/// Once a canister is evicted, older certificates are accepted for it again until a newer one is
+/// recorded, so the store should be large enough to hold every canister that is verified
+/// regularly.
+pub struct InMemoryCertificateTimeStore {
+    certificate_times: parking_lot::Mutex<SizedCache<Vec<u8>, u128>>,
+}
+
+impl fmt::Debug for InMemoryCertificateTimeStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InMemoryCertificateTimeStore")
+            .field("size", &self.certificate_times.lock().cache_size())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for InMemoryCertificateTimeStore {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_SIZE)
+    }
+}
+
+impl InMemoryCertificateTimeStore {
+    /// The number of canisters whose certificate times are held by default.
+    pub const DEFAULT_MAX_SIZE: usize = 10_000;
+
+    /// Creates an empty store that holds the certificate times of up to `max_size` canisters.
+    ///
+    /// # Panics
+    /// Panics if the maximum size is zero.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            certificate_times: parking_lot::Mutex::new(SizedCache::with_size(max_size)),
+        }
+    }
+}
+
+impl CertificateTimeStore for InMemoryCertificateTimeStore {
+    fn newest_certificate_time(&self, canister_id: &[u8]) -> Option<u128> {
+        let mut certificate_times = self.certificate_times.lock();
+
+        certificate_times.cache_get(canister_id).copied()
+    }
+
+    fn record_certificate_time(&self, canister_id: &[u8], certificate_time: u128) {
+        let mut certificate_times = self.certificate_times.lock();
+
+        match certificate_times.cache_get_mut(canister_id) {
+            Some(newest_certificate_time) => {
+                *newest_certificate_time = (*newest_certificate_time).max(certificate_time);
+            }
+            None => {
+                certificate_times.cache_set(canister_id.to_vec(), certificate_time);
+            }
+        }
+    }
+}
+
+/// Tracks the newest certificate time that was seen for each canister, and rejects certificates
+/// that are older than it, so that a replica cannot roll back the state of a canister by replaying
+/// an older certificate that is still within the allowed offset of the current time.
+///
+/// Certificates may be older than the newest certificate time by up to a tolerance, to allow for
+/// responses from different replicas whose states were certified at slightly different times.
+/// The tolerance defaults to zero.
+pub struct CertificateFreshnessTracker {
+    store: Arc<dyn CertificateTimeStore>,
+    tolerance_ns: u128,
+}
+
+impl fmt::Debug for CertificateFreshnessTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertificateFreshnessTracker")
+            .field("tolerance_ns", &self.tolerance_ns)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CertificateFreshnessTracker {
+    /// Creates a tracker that records certificate times in the provided [CertificateTimeStore].
+    pub fn new(store: Arc<dyn CertificateTimeStore>) -> Self {
+        Self {
+            store,
+            tolerance_ns: 0,
+        }
+    }
+
+    /// Creates a tracker that records certificate times in an [InMemoryCertificateTimeStore] of
+    /// the [default size](InMemoryCertificateTimeStore::DEFAULT_MAX_SIZE).
+    pub fn in_memory() -> Self {
+        Self::new(Arc::new(InMemoryCertificateTimeStore::default()))
+    }
+
+    /// Sets the tolerance, in nanoseconds, by which a certificate may be older than the newest
+    /// certificate time that was seen for the canister.
+    pub fn with_tolerance_ns(mut self, tolerance_ns: u128) -> Self {
+        self.tolerance_ns = tolerance_ns;
+
+        self
+    }
+
+    /// Returns the tolerance, in nanoseconds, by which a certificate may be older than the newest
+    /// certificate time that was seen for the canister.
+    pub fn tolerance_ns(&self) -> u128 {
+        self.tolerance_ns
+    }
+
+    /// Checks that the certificate time is not older than the newest certificate time that was
+    /// seen for the canister, less the tolerance.
+    ///
+    /// The certificate time is not recorded, it must be recorded with
+    /// [record_certificate_time](Self::record_certificate_time) once the whole response has been
+    /// verified.
+    pub fn check_certificate_freshness(
+        &self,
+        canister_id: &[u8],
+        certificate_time: u128,
+    ) -> CertificateVerificationResult {
+        let Some(newest_certificate_time) = self.store.newest_certificate_time(canister_id) else {
+            return Ok(());
+        };
+
+        if certificate_time.saturating_add(self.tolerance_ns) < newest_certificate_time {
+            return Err(
+                CertificateVerificationError::CertificateOlderThanNewestSeen {
+                    certificate_time,
+                    newest_certificate_time,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Records the certificate time of a response, so that older certificates for the canister
+    /// are rejected by [check_certificate_freshness](Self::check_certificate_freshness).
+    ///
+    /// This must only be called once the certificate, and the response that it certifies, have
+    /// been verified, otherwise a forged certificate with a time in the future would cause genuine
+    /// certificates to be rejected.
+    pub fn record_certificate_time(&self, canister_id: &[u8], certificate_time: u128) {
+        self.store
+            .record_certificate_time(canister_id, certificate_time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANISTER_ID: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 1, 1, 1];
+    const OTHER_CANISTER_ID: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 2, 1, 1];
+    const THIRD_CANISTER_ID: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 3, 1, 1];
+
+    #[test]
+    fn in_memory_store_keeps_newest_certificate_time() {
+        let store = InMemoryCertificateTimeStore::default();
+
+        assert_eq!(store.newest_certificate_time(CANISTER_ID), None);
+        store.record_certificate_time(CANISTER_ID, 200);
+        store.record_certificate_time(CANISTER_ID, 100);
+        assert_eq!(store.newest_certificate_time(CANISTER_ID), Some(200));
+        store.record_certificate_time(CANISTER_ID, 300);
+        assert_eq!(store.newest_certificate_time(CANISTER_ID), Some(300));
+        assert_eq!(store.newest_certificate_time(OTHER_CANISTER_ID), None);
+    }
+
+    #[test]
+    fn in_memory_store_evicts_least_recently_used_canister() {
+        let store = InMemoryCertificateTimeStore::new(2);
+
+        store.record_certificate_time(CANISTER_ID, 100);
+        store.record_certificate_time(OTHER_CANISTER_ID, 200);
+        store.newest_certificate_time(CANISTER_ID);
+        store.record_certificate_time(THIRD_CANISTER_ID, 300);
+
+        assert_eq!(store.newest_certificate_time(CANISTER_ID), Some(100));
+        assert_eq!(store.newest_certificate_time(OTHER_CANISTER_ID), None);
+        assert_eq!(store.newest_certificate_time(THIRD_CANISTER_ID), Some(300));
+    }
+
+    #[test]
+    fn accepts_newer_and_equal_certificate_times() {
+        let tracker = CertificateFreshnessTracker::in_memory();
+
+        tracker.record_certificate_time(CANISTER_ID, 100);
+        tracker
+            .check_certificate_freshness(CANISTER_ID, 100)
+            .unwrap();
+        tracker
+            .check_certificate_freshness(CANISTER_ID, 200)
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_older_certificate_time() {
+        let tracker = CertificateFreshnessTracker::in_memory();
+
+        tracker.record_certificate_time(CANISTER_ID, 200);
+        let result = tracker.check_certificate_freshness(CANISTER_ID, 199);
+
+        assert!(matches!(
+            result,
+            Err(
+                CertificateVerificationError::CertificateOlderThanNewestSeen {
+                    certificate_time: 199,
+                    newest_certificate_time: 200,
+                }
+            )
+        ));
+    }
+
+    #[test]
+    fn checking_does_not_record_certificate_time() {
+        let tracker = CertificateFreshnessTracker::in_memory();
+
+        tracker
+            .check_certificate_freshness(CANISTER_ID, 200)
+            .unwrap();
+        tracker
+            .check_certificate_freshness(CANISTER_ID, 100)
+            .unwrap();
+    }
+
+    #[test]
+    fn tracks_canisters_separately() {
+        let tracker = CertificateFreshnessTracker::in_memory();
+
+        tracker.record_certificate_time(CANISTER_ID, 200);
+        tracker
+            .check_certificate_freshness(OTHER_CANISTER_ID, 100)
+            .unwrap();
+    }
+
+    #[test]
+    fn accepts_older_certificate_time_within_tolerance() {
+        let tracker = CertificateFreshnessTracker::in_memory().with_tolerance_ns(50);
+
+        tracker.record_certificate_time(CANISTER_ID, 200);
+        tracker
+            .check_certificate_freshness(CANISTER_ID, 150)
+            .unwrap();
+        let result = tracker.check_certificate_freshness(CANISTER_ID, 149);
+
+        assert!(matches!(
+            result,
+            Err(
+                CertificateVerificationError::CertificateOlderThanNewestSeen {
+                    certificate_time: 149,
+                    newest_certificate_time: 200,
+                }
+            )
+        ));
+    }
+}
@@ -0,0 +1,762 @@
+use crate::error::{CertificateVerificationError, CertificateVerificationResult};
+use candid::Principal;
+use ic_cbor::{parse_cbor, parse_cbor_principals_array, CborValue};
+use ic_certification::{
+    hash_tree::Hash, Certificate, HashTree, HashTreeNode, LookupResult, SubtreeLookupResult,
+};
+
+/// The status of a request, as certified at `request_status/<request_id>/status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestStatus {
+    /// The request has been received, but not processed yet.
+    Received,
+    /// The request is being processed.
+    Processing,
+    /// The request has been processed and has a reply.
+    Replied,
+    /// The request has been processed and was rejected.
+    Rejected,
+    /// The request has been processed, but its reply or rejection is no longer available.
+    Done,
+}
+
+/// A [Certificate] whose signature, and the signature of its delegation if present, has been
+/// verified with [VerifyCertificate](crate::VerifyCertificate). It can only be obtained by
+/// verifying a certificate, so its lookups can be trusted.
+#[derive(Debug, Clone, Copy)]
+pub struct VerifiedCertificate<'a> {
+    certificate: &'a Certificate,
+}
+
+impl<'a> VerifiedCertificate<'a> {
+    pub(crate) fn new(certificate: &'a Certificate) -> Self {
+        Self { certificate }
+    }
+
+    /// Returns the certificate that was verified.
+    pub fn certificate(&self) -> &'a Certificate {
+        self.certificate
+    }
+}
+
+/// A [Certificate] that has not been verified. Anyone can create a certificate with arbitrary
+/// values, so its lookups must only be used to decide how to verify it, for example to pick a root
+/// key that is valid at the time of the certificate, or to report diagnostics.
+#[derive(Debug, Clone, Copy)]
+pub struct UnverifiedCertificate<'a> {
+    certificate: &'a Certificate,
+}
+
+impl<'a> UnverifiedCertificate<'a> {
+    /// Wraps a certificate that has not been verified.
+    pub fn new(certificate: &'a Certificate) -> Self {
+        Self { certificate }
+    }
+
+    /// Returns the certificate.
+    pub fn certificate(&self) -> &'a Certificate {
+        self.certificate
+    }
+}
+
+/// Typed lookups of the standard paths of a [Certificate]'s tree, as described in the
+/// [Interface Specification](https://internetcomputer.org/docs/current/references/ic-interface-spec#state-tree).
+///
+/// The lookups are implemented for a [VerifiedCertificate], which is only returned once the
+/// certificate has been verified with [VerifyCertificate](crate::VerifyCertificate), and for an
+/// [UnverifiedCertificate], whose values must not be trusted. Each lookup distinguishes between a
+/// path that is proven to be absent from the tree and a path that was pruned from the tree, so it
+/// is unknown whether it exists.
+pub trait LookupCertificate: private::CertificateTree {
+    /// Looks up the time of the certificate, in nanoseconds since the UNIX epoch, at `time`.
+    fn lookup_time(&self) -> CertificateVerificationResult<u128> {
+        let path: [&[u8]; 1] = [b"time"];
+        let mut value = lookup_leaf(self.tree(), &path)?;
+
+        leb128::read::unsigned(&mut value)
+            .map(u128::from)
+            .map_err(|e| malformed_value(&path, e.to_string()))
+    }
+
+    /// Looks up the certified data of a canister at `canister/<canister_id>/certified_data`.
+    fn lookup_certified_data(&self, canister_id: &[u8]) -> CertificateVerificationResult<&[u8]> {
+        let path: [&[u8]; 3] = [b"canister", canister_id, b"certified_data"];
+
+        lookup_leaf(self.tree(), &path)
+    }
+
+    /// Looks up the SHA-256 hash of the module of a canister at
+    /// `canister/<canister_id>/module_hash`.
+    fn lookup_module_hash(&self, canister_id: &[u8]) -> CertificateVerificationResult<Hash> {
+        let path: [&[u8]; 3] = [b"canister", canister_id, b"module_hash"];
+        let value = lookup_leaf(self.tree(), &path)?;
+
+        value.try_into().map_err(|_| {
+            malformed_value(
+                &path,
+                format!("expected a 32 byte hash, found {} bytes", value.len()),
+            )
+        })
+    }
+
+    /// Looks up the controllers of a canister at `canister/<canister_id>/controllers`.
+    fn lookup_controllers(
+        &self,
+        canister_id: &[u8],
+    ) -> CertificateVerificationResult<Vec<Principal>> {
+        let path: [&[u8]; 3] = [b"canister", canister_id, b"controllers"];
+        let value = lookup_leaf(self.tree(), &path)?;
+
+        let parsed_cbor = parse_cbor(value)
+            .map_err(|e| malformed_value(&path, e.to_string()))?
+            .without_self_describe_tag();
+        let CborValue::Array(controllers) = parsed_cbor else {
+            return Err(malformed_value(&path, "expected an array of principals"));
+        };
+
+        controllers
+            .iter()
+            .map(|controller| match controller {
+                CborValue::ByteString(controller) => Ok(Principal::from_slice(controller)),
+                _ => Err(malformed_value(&path, "expected an array of principals")),
+            })
+            .collect()
+    }
+
+    /// Looks up a metadata section of a canister at `canister/<canister_id>/metadata/<name>`.
+    fn lookup_metadata(
+        &self,
+        canister_id: &[u8],
+        name: &str,
+    ) -> CertificateVerificationResult<&[u8]> {
+        let path: [&[u8]; 4] = [b"canister", canister_id, b"metadata", name.as_bytes()];
+
+        lookup_leaf(self.tree(), &path)
+    }
+
+    /// Looks up the status of a request at `request_status/<request_id>/status`.
+    fn lookup_request_status(
+        &self,
+        request_id: &[u8],
+    ) -> CertificateVerificationResult<RequestStatus> {
+        let path: [&[u8]; 3] = [b"request_status", request_id, b"status"];
+
+        match lookup_leaf(self.tree(), &path)? {
+            b"received" => Ok(RequestStatus::Received),
+            b"processing" => Ok(RequestStatus::Processing),
+            b"replied" => Ok(RequestStatus::Replied),
+            b"rejected" => Ok(RequestStatus::Rejected),
+            b"done" => Ok(RequestStatus::Done),
+            status => Err(malformed_value(
+                &path,
+                format!(
+                    "unknown request status {:?}",
+                    String::from_utf8_lossy(status)
+                ),
+            )),
+        }
+    }
+
+    /// Looks up the reply to a request at `request_status/<request_id>/reply`.
+    fn lookup_request_reply(&self, request_id: &[u8]) -> CertificateVerificationResult<&[u8]> {
+        let path: [&[u8]; 3] = [b"request_status", request_id, b"reply"];
+
+        lookup_leaf(self.tree(), &path)
+    }
+
+    /// Looks up the reject code of a request at `request_status/<request_id>/reject_code`.
+    fn lookup_request_reject_code(&self, request_id: &[u8]) -> CertificateVerificationResult<u64> {
+        let path: [&[u8]; 3] = [b"request_status", request_id, b"reject_code"];
+        let mut value = lookup_leaf(self.tree(), &path)?;
+
+        leb128::read::unsigned(&mut value).map_err(|e| malformed_value(&path, e.to_string()))
+    }
+
+    /// Looks up the reject message of a request at `request_status/<request_id>/reject_message`.
+    fn lookup_request_reject_message(
+        &self,
+        request_id: &[u8],
+    ) -> CertificateVerificationResult<String> {
+        let path: [&[u8]; 3] = [b"request_status", request_id, b"reject_message"];
+        let value = lookup_leaf(self.tree(), &path)?;
+
+        String::from_utf8(value.to_vec()).map_err(|e| malformed_value(&path, e.to_string()))
+    }
+
+    /// Looks up the DER encoded public key of a subnet at `subnet/<subnet_id>/public_key`.
+    fn lookup_subnet_public_key(&self, subnet_id: &[u8]) -> CertificateVerificationResult<&[u8]> {
+        let path: [&[u8]; 3] = [b"subnet", subnet_id, b"public_key"];
+
+        lookup_leaf(self.tree(), &path)
+    }
+
+    /// Looks up all of the canister ID ranges of a subnet. Subnets with many ranges split them into
+    /// shards at `canister_ranges/<subnet_id>/<shard>`, whose ranges are concatenated in order,
+    /// otherwise the ranges are found at `subnet/<subnet_id>/canister_ranges`.
+    fn lookup_subnet_canister_ranges(
+        &self,
+        subnet_id: &[u8],
+    ) -> CertificateVerificationResult<Vec<(Principal, Principal)>> {
+        let Some(shards) = lookup_canister_range_shards(self.tree(), subnet_id) else {
+            let path: [&[u8]; 3] = [b"subnet", subnet_id, b"canister_ranges"];
+            let value = lookup_leaf(self.tree(), &path)?;
+
+            return parse_canister_ranges(&path, value);
+        };
+
+        let mut canister_ranges = vec![];
+        collect_canister_range_shards(shards.as_ref(), subnet_id, &mut canister_ranges)?;
+
+        Ok(canister_ranges)
+    }
+
+    /// Looks up the canister ID ranges of a subnet that may contain the canister ID.
+    ///
+    /// If the subnet's ranges are split into shards at `canister_ranges/<subnet_id>/<shard>`, where
+    /// each shard is labeled with the lowest canister ID of its ranges, these are the ranges of the
+    /// shard with the greatest label that is not greater than the canister ID, or no ranges if
+    /// there is no such shard. Otherwise, these are all of the subnet's ranges at
+    /// `subnet/<subnet_id>/canister_ranges`.
+    fn lookup_subnet_canister_ranges_for_canister(
+        &self,
+        subnet_id: &[u8],
+        canister_id: &[u8],
+    ) -> CertificateVerificationResult<Vec<(Principal, Principal)>> {
+        let Some(shards) = lookup_canister_range_shards(self.tree(), subnet_id) else {
+            return self.lookup_subnet_canister_ranges(subnet_id);
+        };
+
+        match lookup_lower_bound(shards.as_ref(), canister_id) {
+            LowerBoundLookupResult::Found(shard, node) => {
+                parse_canister_range_shard(&[b"canister_ranges", subnet_id, shard], node)
+            }
+            LowerBoundLookupResult::Absent => Ok(vec![]),
+            LowerBoundLookupResult::Unknown => {
+                Err(CertificateVerificationError::CertificatePathUnknown {
+                    path: path_to_vec(&[b"canister_ranges", subnet_id]),
+                })
+            }
+        }
+    }
+
+    /// Looks up the DER encoded public key of a node of a subnet at
+    /// `subnet/<subnet_id>/node/<node_id>/public_key`.
+    fn lookup_subnet_node_public_key(
+        &self,
+        subnet_id: &[u8],
+        node_id: &[u8],
+    ) -> CertificateVerificationResult<&[u8]> {
+        let path: [&[u8]; 5] = [b"subnet", subnet_id, b"node", node_id, b"public_key"];
+
+        lookup_leaf(self.tree(), &path)
+    }
+}
+
+impl LookupCertificate for VerifiedCertificate<'_> {}
+
+impl LookupCertificate for UnverifiedCertificate<'_> {}
+
+mod private {
+    use super::*;
+
+    pub trait CertificateTree {
+        fn tree(&self) -> &HashTree;
+    }
+
+    impl CertificateTree for VerifiedCertificate<'_> {
+        fn tree(&self) -> &HashTree {
+            &self.certificate.tree
+        }
+    }
+
+    impl CertificateTree for UnverifiedCertificate<'_> {
+        fn tree(&self) -> &HashTree {
+            &self.certificate.tree
+        }
+    }
+}
+
+fn lookup_leaf<'a>(tree: &'a HashTree, path: &[&[u8]]) -> CertificateVerificationResult<&'a [u8]> {
+    match tree.lookup_path(path) {
+        LookupResult::Found(value) => Ok(value),
+        LookupResult::Absent => Err(CertificateVerificationError::CertificatePathAbsent {
+            path: path_to_vec(path),
+        }),
+        LookupResult::Unknown => Err(CertificateVerificationError::CertificatePathUnknown {
+            path: path_to_vec(path),
+        }),
+        LookupResult::Error => Err(CertificateVerificationError::CertificatePathNotALeaf {
+            path: path_to_vec(path),
+        }),
+    }
+}
+
+/// Looks up the shards of a subnet's canister ID ranges at `canister_ranges/<subnet_id>`, if the
+/// subnet's ranges are sharded.
+fn lookup_canister_range_shards(tree: &HashTree, subnet_id: &[u8]) -> Option<HashTree> {
+    let path: [&[u8]; 2] = [b"canister_ranges", subnet_id];
+
+    match tree.lookup_subtree(&path) {
+        SubtreeLookupResult::Found(shards) => Some(shards),
+        _ => None,
+    }
+}
+
+/// The result of looking up the labeled node of a fork with the greatest label that is not
+/// greater than a provided label.
+enum LowerBoundLookupResult<'a> {
+    Found(&'a [u8], &'a HashTreeNode),
+    Absent,
+    Unknown,
+}
+
+/// Walks a fork of labeled nodes, whose labels are sorted, from its greatest label down to the
+/// first label that is not greater than the provided label. A pruned node may hide such a label,
+/// so the result is unknown if a pruned node lies between the label that was found and the next
+/// revealed label that is greater than the provided label. Pruned nodes beyond that greater label
+/// can only hide greater labels, so they are skipped.
+fn lookup_lower_bound<'a>(node: &'a HashTreeNode, label: &[u8]) -> LowerBoundLookupResult<'a> {
+    let mut may_hide_lower_bound = false;
+
+    match find_lower_bound(node, label, &mut may_hide_lower_bound) {
+        _ if may_hide_lower_bound => LowerBoundLookupResult::Unknown,
+        Some((node_label, node)) => LowerBoundLookupResult::Found(node_label, node),
+        None => LowerBoundLookupResult::Absent,
+    }
+}
+
+fn find_lower_bound<'a>(
+    node: &'a HashTreeNode,
+    label: &[u8],
+    may_hide_lower_bound: &mut bool,
+) -> Option<(&'a [u8], &'a HashTreeNode)> {
+    match node {
+        HashTreeNode::Labeled(node_label, node) if node_label.as_bytes() <= label => {
+            Some((node_label.as_bytes(), node))
+        }
+        HashTreeNode::Labeled(_, _) => {
+            // the pruned nodes that were passed are beyond this label
+            *may_hide_lower_bound = false;
+            None
+        }
+        HashTreeNode::Fork(nodes) => find_lower_bound(&nodes.1, label, may_hide_lower_bound)
+            .or_else(|| find_lower_bound(&nodes.0, label, may_hide_lower_bound)),
+        HashTreeNode::Pruned(_) => {
+            *may_hide_lower_bound = true;
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Collects the canister ID ranges of all of the shards of a fork, in order.
+fn collect_canister_range_shards(
+    node: &HashTreeNode,
+    subnet_id: &[u8],
+    canister_ranges: &mut Vec<(Principal, Principal)>,
+) -> CertificateVerificationResult {
+    match node {
+        HashTreeNode::Labeled(shard, node) => {
+            canister_ranges.extend(parse_canister_range_shard(
+                &[b"canister_ranges", subnet_id, shard.as_bytes()],
+                node,
+            )?);
+        }
+        HashTreeNode::Fork(nodes) => {
+            collect_canister_range_shards(&nodes.0, subnet_id, canister_ranges)?;
+            collect_canister_range_shards(&nodes.1, subnet_id, canister_ranges)?;
+        }
+        HashTreeNode::Pruned(_) => {
+            return Err(CertificateVerificationError::CertificatePathUnknown {
+                path: path_to_vec(&[b"canister_ranges", subnet_id]),
+            });
+        }
+        HashTreeNode::Empty() | HashTreeNode::Leaf(_) => {}
+    }
+
+    Ok(())
+}
+
+fn parse_canister_range_shard(
+    path: &[&[u8]],
+    node: &HashTreeNode,
+) -> CertificateVerificationResult<Vec<(Principal, Principal)>> {
+    match node {
+        HashTreeNode::Leaf(value) => parse_canister_ranges(path, value),
+        HashTreeNode::Pruned(_) => Err(CertificateVerificationError::CertificatePathUnknown {
+            path: path_to_vec(path),
+        }),
+        _ => Err(CertificateVerificationError::CertificatePathNotALeaf {
+            path: path_to_vec(path),
+        }),
+    }
+}
+
+fn parse_canister_ranges(
+    path: &[&[u8]],
+    value: &[u8],
+) -> CertificateVerificationResult<Vec<(Principal, Principal)>> {
+    parse_cbor_principals_array(value).map_err(|e| malformed_value(path, e.to_string()))
+}
+
+fn malformed_value(path: &[&[u8]], reason: impl Into<String>) -> CertificateVerificationError {
+    CertificateVerificationError::MalformedCertificateValue {
+        path: path_to_vec(path),
+        reason: reason.into(),
+    }
+}
+
+fn path_to_vec(path: &[&[u8]]) -> Vec<Vec<u8>> {
+    path.iter().map(|label| label.to_vec()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_certification::{fork, label, leaf, pruned, HashTree};
+    use ic_response_verification_test_utils::hex_decode;
+
+    const CANISTER_ID: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 1, 1, 1];
+    const SUBNET_ID: &[u8] = &[2, 2];
+    const NODE_ID: &[u8] = &[3, 3];
+    const REQUEST_ID: &[u8] = &[4, 4];
+    const CONTROLLERS_CBOR: &str = "d9d9f7824301020343040506";
+    const CANISTER_RANGES_CBOR: &str = "d9d9f781824a000000000000000001014a00000000000000090101";
+    const OTHER_CANISTER_RANGES_CBOR: &str =
+        "d9d9f781824a000000000000001001014a00000000000000190101";
+    const MODULE_HASH: &str = "784c0f825a938aa7f471587cdf7c7796f828f9362495e2b9c8490f2232359bdb";
+
+    fn create_certificate(tree: HashTree) -> Certificate {
+        Certificate {
+            tree,
+            signature: vec![],
+            delegation: None,
+        }
+    }
+
+    fn create_canister_tree() -> HashTree {
+        let controllers = hex_decode(CONTROLLERS_CBOR);
+
+        fork(
+            label(
+                "canister",
+                label(
+                    CANISTER_ID.to_vec(),
+                    fork(
+                        fork(
+                            label("certified_data", leaf([1, 2, 3])),
+                            label("controllers", leaf(controllers)),
+                        ),
+                        fork(
+                            label(
+                                "metadata",
+                                fork(
+                                    label("candid:service", leaf("service : {}")),
+                                    pruned([0; 32]),
+                                ),
+                            ),
+                            label("module_hash", leaf(hex_decode(MODULE_HASH))),
+                        ),
+                    ),
+                ),
+            ),
+            label(
+                "time",
+                leaf([0xb8, 0xb1, 0x8d, 0xee, 0xb0, 0xda, 0x8c, 0x95, 0x17]),
+            ),
+        )
+    }
+
+    #[test]
+    fn lookup_time() {
+        let certificate = create_certificate(create_canister_tree());
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert_eq!(
+            certificate.lookup_time().unwrap(),
+            1_669_202_493_944_584_376
+        );
+    }
+
+    #[test]
+    fn lookup_canister_paths() {
+        let certificate = create_certificate(create_canister_tree());
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert_eq!(
+            certificate.lookup_certified_data(CANISTER_ID).unwrap(),
+            &[1, 2, 3]
+        );
+        assert_eq!(
+            certificate
+                .lookup_module_hash(CANISTER_ID)
+                .unwrap()
+                .to_vec(),
+            hex_decode(MODULE_HASH)
+        );
+        assert_eq!(
+            certificate.lookup_controllers(CANISTER_ID).unwrap(),
+            vec![
+                Principal::from_slice(&[1, 2, 3]),
+                Principal::from_slice(&[4, 5, 6])
+            ]
+        );
+        assert_eq!(
+            certificate
+                .lookup_metadata(CANISTER_ID, "candid:service")
+                .unwrap(),
+            b"service : {}"
+        );
+    }
+
+    #[test]
+    fn lookup_absent_unknown_and_non_leaf_paths() {
+        let certificate = create_certificate(create_canister_tree());
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert!(matches!(
+            certificate.lookup_certified_data(&[9, 9]),
+            Err(CertificateVerificationError::CertificatePathAbsent { path })
+                if path == vec![b"canister".to_vec(), vec![9, 9], b"certified_data".to_vec()]
+        ));
+        assert!(matches!(
+            certificate.lookup_metadata(CANISTER_ID, "custom"),
+            Err(CertificateVerificationError::CertificatePathUnknown { .. })
+        ));
+        assert!(matches!(
+            certificate.lookup_subnet_public_key(SUBNET_ID),
+            Err(CertificateVerificationError::CertificatePathAbsent { .. })
+        ));
+
+        let certificate = create_certificate(label(
+            "canister",
+            label(
+                CANISTER_ID.to_vec(),
+                label("certified_data", label("nested", leaf([]))),
+            ),
+        ));
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert!(matches!(
+            certificate.lookup_certified_data(CANISTER_ID),
+            Err(CertificateVerificationError::CertificatePathNotALeaf { .. })
+        ));
+    }
+
+    #[test]
+    fn lookup_malformed_values() {
+        let certificate = create_certificate(fork(
+            label(
+                "canister",
+                label(
+                    CANISTER_ID.to_vec(),
+                    fork(
+                        label("controllers", leaf([1, 2, 3])),
+                        label("module_hash", leaf([1, 2, 3])),
+                    ),
+                ),
+            ),
+            label(
+                "request_status",
+                label(REQUEST_ID.to_vec(), label("status", leaf("unknown"))),
+            ),
+        ));
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert!(matches!(
+            certificate.lookup_controllers(CANISTER_ID),
+            Err(CertificateVerificationError::MalformedCertificateValue { .. })
+        ));
+        assert!(matches!(
+            certificate.lookup_module_hash(CANISTER_ID),
+            Err(CertificateVerificationError::MalformedCertificateValue { .. })
+        ));
+        assert!(matches!(
+            certificate.lookup_request_status(REQUEST_ID),
+            Err(CertificateVerificationError::MalformedCertificateValue { .. })
+        ));
+    }
+
+    #[test]
+    fn lookup_request_status_paths() {
+        let certificate = create_certificate(label(
+            "request_status",
+            label(
+                REQUEST_ID.to_vec(),
+                fork(
+                    fork(
+                        label("reject_code", leaf([4])),
+                        label("reject_message", leaf("Canister trapped")),
+                    ),
+                    fork(
+                        label("reply", leaf([68, 73, 68, 76])),
+                        label("status", leaf("rejected")),
+                    ),
+                ),
+            ),
+        ));
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert_eq!(
+            certificate.lookup_request_status(REQUEST_ID).unwrap(),
+            RequestStatus::Rejected
+        );
+        assert_eq!(
+            certificate.lookup_request_reply(REQUEST_ID).unwrap(),
+            &[68, 73, 68, 76]
+        );
+        assert_eq!(
+            certificate.lookup_request_reject_code(REQUEST_ID).unwrap(),
+            4
+        );
+        assert_eq!(
+            certificate
+                .lookup_request_reject_message(REQUEST_ID)
+                .unwrap(),
+            "Canister trapped"
+        );
+    }
+
+    #[test]
+    fn lookup_subnet_paths() {
+        let canister_ranges = hex_decode(CANISTER_RANGES_CBOR);
+        let certificate = create_certificate(label(
+            "subnet",
+            label(
+                SUBNET_ID.to_vec(),
+                fork(
+                    fork(
+                        label("canister_ranges", leaf(canister_ranges)),
+                        label(
+                            "node",
+                            label(NODE_ID.to_vec(), label("public_key", leaf([5, 5]))),
+                        ),
+                    ),
+                    label("public_key", leaf([6, 6])),
+                ),
+            ),
+        ));
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert_eq!(
+            certificate
+                .lookup_subnet_canister_ranges(SUBNET_ID)
+                .unwrap(),
+            vec![(
+                Principal::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 1, 1]),
+                Principal::from_slice(&[0, 0, 0, 0, 0, 0, 0, 9, 1, 1])
+            )]
+        );
+        assert_eq!(
+            certificate
+                .lookup_subnet_node_public_key(SUBNET_ID, NODE_ID)
+                .unwrap(),
+            &[5, 5]
+        );
+        assert_eq!(
+            certificate.lookup_subnet_public_key(SUBNET_ID).unwrap(),
+            &[6, 6]
+        );
+    }
+
+    #[test]
+    fn lookup_sharded_subnet_canister_ranges() {
+        let shard_id = |id: u8| vec![0, 0, 0, 0, 0, 0, 0, id, 1, 1];
+        let certificate = create_certificate(label(
+            "canister_ranges",
+            label(
+                SUBNET_ID.to_vec(),
+                fork(
+                    label(shard_id(0x00), leaf(hex_decode(CANISTER_RANGES_CBOR))),
+                    label(shard_id(0x10), leaf(hex_decode(OTHER_CANISTER_RANGES_CBOR))),
+                ),
+            ),
+        ));
+        let certificate = UnverifiedCertificate::new(&certificate);
+        let first_shard = (
+            Principal::from_slice(&shard_id(0x00)),
+            Principal::from_slice(&shard_id(0x09)),
+        );
+        let second_shard = (
+            Principal::from_slice(&shard_id(0x10)),
+            Principal::from_slice(&shard_id(0x19)),
+        );
+
+        assert_eq!(
+            certificate
+                .lookup_subnet_canister_ranges(SUBNET_ID)
+                .unwrap(),
+            vec![first_shard, second_shard]
+        );
+        assert_eq!(
+            certificate
+                .lookup_subnet_canister_ranges_for_canister(SUBNET_ID, &shard_id(0x05))
+                .unwrap(),
+            vec![first_shard]
+        );
+        assert_eq!(
+            certificate
+                .lookup_subnet_canister_ranges_for_canister(SUBNET_ID, &shard_id(0x20))
+                .unwrap(),
+            vec![second_shard]
+        );
+    }
+
+    #[test]
+    fn lookup_sharded_subnet_canister_ranges_with_pruned_shard() {
+        let shard_id = |id: u8| vec![0, 0, 0, 0, 0, 0, 0, id, 1, 1];
+        let certificate = create_certificate(label(
+            "canister_ranges",
+            label(
+                SUBNET_ID.to_vec(),
+                fork(
+                    label(shard_id(0x00), leaf(hex_decode(CANISTER_RANGES_CBOR))),
+                    pruned([0; 32]),
+                ),
+            ),
+        ));
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert!(matches!(
+            certificate.lookup_subnet_canister_ranges(SUBNET_ID),
+            Err(CertificateVerificationError::CertificatePathUnknown { .. })
+        ));
+        assert!(matches!(
+            certificate.lookup_subnet_canister_ranges_for_canister(SUBNET_ID, &shard_id(0x05)),
+            Err(CertificateVerificationError::CertificatePathUnknown { .. })
+        ));
+    }
+
+    #[test]
+    fn lookup_sharded_subnet_canister_ranges_with_pruned_shard_beyond_the_canister() {
+        let shard_id = |id: u8| vec![0, 0, 0, 0, 0, 0, 0, id, 1, 1];
+        let certificate = create_certificate(label(
+            "canister_ranges",
+            label(
+                SUBNET_ID.to_vec(),
+                fork(
+                    fork(
+                        label(shard_id(0x00), leaf(hex_decode(CANISTER_RANGES_CBOR))),
+                        label(shard_id(0x10), leaf(hex_decode(OTHER_CANISTER_RANGES_CBOR))),
+                    ),
+                    pruned([0; 32]),
+                ),
+            ),
+        ));
+        let certificate = UnverifiedCertificate::new(&certificate);
+
+        assert_eq!(
+            certificate
+                .lookup_subnet_canister_ranges_for_canister(SUBNET_ID, &shard_id(0x05))
+                .unwrap(),
+            vec![(
+                Principal::from_slice(&shard_id(0x00)),
+                Principal::from_slice(&shard_id(0x09)),
+            )]
+        );
+        assert!(matches!(
+            certificate.lookup_subnet_canister_ranges_for_canister(SUBNET_ID, &shard_id(0x20)),
+            Err(CertificateVerificationError::CertificatePathUnknown { .. })
+        ));
+    }
+}
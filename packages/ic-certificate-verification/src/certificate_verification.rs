@@ -1,9 +1,10 @@
 use crate::{
     error::{CertificateVerificationError, CertificateVerificationResult},
-    signature_verification::verify_signature,
+    signature_verification::{verify_signature, SignatureCache, SizedSignatureCache},
+    LookupCertificate, TrustedRootKey, TrustedRootKeys, UnverifiedCertificate, VerifiedCertificate,
 };
 use candid::Principal;
-use ic_cbor::{parse_cbor_principals_array, CertificateToCbor};
+use ic_cbor::CertificateToCbor;
 use ic_certification::{Certificate, Delegation, LookupResult};
 
 const IC_STATE_ROOT_DOMAIN_SEPARATOR: &[u8; 14] = b"\x0Dic-state-root";
@@ -40,84 +41,221 @@ pub fn principal_is_within_ranges(
         .any(|r| principal >= &r.0 && principal <= &r.1)
 }
 
-pub trait VerifyCertificate<T> {
+/// Checks if the principal is within one of the ranges, which must be sorted and
+/// non-overlapping, as they are in a certificate's tree.
+pub fn principal_is_within_sorted_ranges(
+    principal: &Principal,
+    ranges: &[(Principal, Principal)],
+) -> bool {
+    let upper_bound = ranges.partition_point(|(low, _)| low <= principal);
+
+    upper_bound > 0 && principal <= &ranges[upper_bound - 1].1
+}
+
+/// Looks up the canister ID ranges of a subnet that may contain the canister ID, as described in
+/// [LookupCertificate::lookup_subnet_canister_ranges_for_canister].
+fn lookup_canister_ranges(
+    certificate: &VerifiedCertificate,
+    subnet_id: &[u8],
+    canister_id: &[u8],
+) -> CertificateVerificationResult<Vec<(Principal, Principal)>> {
+    certificate
+        .lookup_subnet_canister_ranges_for_canister(subnet_id, canister_id)
+        .map_err(|e| match e {
+            CertificateVerificationError::CertificatePathAbsent { path }
+            | CertificateVerificationError::CertificatePathUnknown { path }
+            | CertificateVerificationError::CertificatePathNotALeaf { path } => {
+                CertificateVerificationError::SubnetCanisterIdRangesNotFound { path }
+            }
+            e => e,
+        })
+}
+
+/// Verifies the signature of the certificate's root hash against the DER encoded public key of
+/// the subnet that signed it, without looking at the certificate's delegation.
+fn verify_certificate_signature(
+    certificate: &Certificate,
+    der_key: Vec<u8>,
+    signature_cache: &dyn SignatureCache,
+) -> CertificateVerificationResult {
+    let sig = certificate.signature.as_slice();
+
+    let root_hash = certificate.tree.digest();
+    let mut msg = vec![];
+    msg.extend_from_slice(IC_STATE_ROOT_DOMAIN_SEPARATOR);
+    msg.extend_from_slice(&root_hash);
+
+    let pk = extract_der(der_key)?;
+
+    verify_signature(&pk, sig, &msg, signature_cache)
+}
+
+/// Verifies a certificate, or a delegation, against a root key and returns what was verified. A
+/// [Certificate] is returned as a [VerifiedCertificate], whose lookups can be trusted, and a
+/// [Delegation] returns the DER encoded public key of the subnet that it delegates to.
+pub trait VerifyCertificate<'a, T> {
+    /// Verifies the certificate, caching verified signatures in the
+    /// [global signature cache](SizedSignatureCache::global).
     fn verify(
-        &self,
+        &'a self,
         canister_id: &[u8],
         root_public_key: &[u8],
-    ) -> CertificateVerificationResult<T>;
-}
+    ) -> CertificateVerificationResult<T> {
+        self.verify_with_signature_cache(
+            canister_id,
+            root_public_key,
+            SizedSignatureCache::global().as_ref(),
+        )
+    }
 
-impl VerifyCertificate<()> for Certificate {
-    fn verify(
-        &self,
+    /// Verifies the certificate, caching verified signatures in the provided [SignatureCache].
+    fn verify_with_signature_cache(
+        &'a self,
         canister_id: &[u8],
         root_public_key: &[u8],
-    ) -> CertificateVerificationResult<()> {
-        let sig = self.signature.as_slice();
+        signature_cache: &dyn SignatureCache,
+    ) -> CertificateVerificationResult<T>;
 
-        let root_hash = self.tree.digest();
-        let mut msg = vec![];
-        msg.extend_from_slice(IC_STATE_ROOT_DOMAIN_SEPARATOR);
-        msg.extend_from_slice(&root_hash);
+    /// Verifies the certificate against each of the [TrustedRootKeys] that is valid at the time of
+    /// the certificate signed by the root key, that is the delegation's certificate if there is a
+    /// delegation, caching verified signatures in the provided [SignatureCache], and returns the key
+    /// that verified it. If no key verifies the certificate, the error of the last key that was
+    /// tried is returned.
+    fn verify_with_trusted_root_keys<'k>(
+        &'a self,
+        canister_id: &[u8],
+        trusted_root_keys: &'k TrustedRootKeys,
+        signature_cache: &dyn SignatureCache,
+    ) -> CertificateVerificationResult<(T, &'k TrustedRootKey)>;
+}
 
-        let der_key = match &self.delegation {
-            Some(delegation) => delegation.verify(canister_id, root_public_key)?,
-            _ => root_public_key.into(),
-        };
-        let pk = extract_der(der_key)?;
+impl<'a> VerifyCertificate<'a, VerifiedCertificate<'a>> for Certificate {
+    fn verify_with_signature_cache(
+        &'a self,
+        canister_id: &[u8],
+        root_public_key: &[u8],
+        signature_cache: &dyn SignatureCache,
+    ) -> CertificateVerificationResult<VerifiedCertificate<'a>> {
+        verify_certificate_with(self, root_public_key, signature_cache, |delegation| {
+            delegation.verify_with_signature_cache(canister_id, root_public_key, signature_cache)
+        })
+    }
 
-        verify_signature(&pk, sig, &msg)
+    fn verify_with_trusted_root_keys<'k>(
+        &'a self,
+        canister_id: &[u8],
+        trusted_root_keys: &'k TrustedRootKeys,
+        signature_cache: &dyn SignatureCache,
+    ) -> CertificateVerificationResult<(VerifiedCertificate<'a>, &'k TrustedRootKey)> {
+        trusted_root_keys.verify_with(root_key_certificate_time(self)?, |root_public_key| {
+            self.verify_with_signature_cache(canister_id, root_public_key, signature_cache)
+        })
     }
 }
 
-impl VerifyCertificate<Vec<u8>> for Delegation {
-    fn verify(
+impl VerifyCertificate<'_, Vec<u8>> for Delegation {
+    fn verify_with_signature_cache(
         &self,
         canister_id: &[u8],
         root_public_key: &[u8],
+        signature_cache: &dyn SignatureCache,
     ) -> CertificateVerificationResult<Vec<u8>> {
-        let cert: Certificate = Certificate::from_cbor(&self.certificate)?;
-        if cert.delegation.is_some() {
-            return Err(CertificateVerificationError::CertificateHasTooManyDelegations);
-        }
-        cert.verify(canister_id, root_public_key)?;
+        verify_delegation_with(self, root_public_key, signature_cache, |verified_cert| {
+            let canister_ranges =
+                lookup_canister_ranges(verified_cert, self.subnet_id.as_ref(), canister_id)?;
+
+            let canister_id = Principal::from_slice(canister_id);
+            if !principal_is_within_sorted_ranges(&canister_id, &canister_ranges[..]) {
+                // the certificate is not authorized to answer calls for this canister
+                return Err(CertificateVerificationError::PrincipalOutOfRange {
+                    canister_id,
+                    canister_ranges,
+                });
+            }
+
+            Ok(())
+        })
+    }
 
-        let canister_range_path = [
-            "subnet".as_bytes(),
-            self.subnet_id.as_ref(),
-            "canister_ranges".as_bytes(),
-        ];
-        let LookupResult::Found(canister_range) = cert.tree.lookup_path(&canister_range_path) else {
-            return Err(CertificateVerificationError::SubnetCanisterIdRangesNotFound {
-                path: canister_range_path.iter().map(|p| p.to_vec()).collect(),
-            });
-        };
-
-        let canister_id = Principal::from_slice(canister_id);
-        let canister_ranges: Vec<(Principal, Principal)> =
-            parse_cbor_principals_array(canister_range)?;
-        if !principal_is_within_ranges(&canister_id, &canister_ranges[..]) {
-            // the certificate is not authorized to answer calls for this canister
-            return Err(CertificateVerificationError::PrincipalOutOfRange {
-                canister_id,
-                canister_ranges,
-            });
-        }
+    fn verify_with_trusted_root_keys<'k>(
+        &self,
+        canister_id: &[u8],
+        trusted_root_keys: &'k TrustedRootKeys,
+        signature_cache: &dyn SignatureCache,
+    ) -> CertificateVerificationResult<(Vec<u8>, &'k TrustedRootKey)> {
+        trusted_root_keys.verify_with(delegation_certificate_time(self)?, |root_public_key| {
+            self.verify_with_signature_cache(canister_id, root_public_key, signature_cache)
+        })
+    }
+}
 
-        let public_key_path = [
-            "subnet".as_bytes(),
-            self.subnet_id.as_ref(),
-            "public_key".as_bytes(),
-        ];
-        let LookupResult::Found(subnet_public_key) = cert.tree.lookup_path(&public_key_path) else {
-            return Err(CertificateVerificationError::SubnetPublicKeyNotFound {
-                path: public_key_path.iter().map(|p| p.to_vec()).collect(),
-            });
-        };
+/// Verifies the certificate's signature against the DER encoded public key of the subnet that its
+/// delegation delegates to, as returned by `verify_delegation`, or against the root key if the
+/// certificate has no delegation.
+pub(crate) fn verify_certificate_with<'a>(
+    certificate: &'a Certificate,
+    root_public_key: &[u8],
+    signature_cache: &dyn SignatureCache,
+    verify_delegation: impl FnOnce(&Delegation) -> CertificateVerificationResult<Vec<u8>>,
+) -> CertificateVerificationResult<VerifiedCertificate<'a>> {
+    let der_key = match &certificate.delegation {
+        Some(delegation) => verify_delegation(delegation)?,
+        _ => root_public_key.into(),
+    };
+
+    verify_certificate_signature(certificate, der_key, signature_cache)?;
+
+    Ok(VerifiedCertificate::new(certificate))
+}
 
-        Ok(subnet_public_key.into())
+/// Verifies the delegation's certificate against the root key, checks that the delegation is
+/// authorized with `authorize_delegation`, and returns the DER encoded public key of the subnet
+/// that it delegates to.
+pub(crate) fn verify_delegation_with(
+    delegation: &Delegation,
+    root_public_key: &[u8],
+    signature_cache: &dyn SignatureCache,
+    authorize_delegation: impl FnOnce(&VerifiedCertificate) -> CertificateVerificationResult,
+) -> CertificateVerificationResult<Vec<u8>> {
+    let cert: Certificate = Certificate::from_cbor(&delegation.certificate)?;
+    if cert.delegation.is_some() {
+        return Err(CertificateVerificationError::CertificateHasTooManyDelegations);
     }
+    verify_certificate_signature(&cert, root_public_key.to_vec(), signature_cache)?;
+    let verified_cert = VerifiedCertificate::new(&cert);
+
+    authorize_delegation(&verified_cert)?;
+
+    let public_key_path = [
+        "subnet".as_bytes(),
+        delegation.subnet_id.as_ref(),
+        "public_key".as_bytes(),
+    ];
+    let LookupResult::Found(subnet_public_key) = cert.tree.lookup_path(&public_key_path) else {
+        return Err(CertificateVerificationError::SubnetPublicKeyNotFound {
+            path: public_key_path.iter().map(|p| p.to_vec()).collect(),
+        });
+    };
+
+    Ok(subnet_public_key.into())
+}
+
+/// Returns the time of the certificate that is signed by the root key, so that the root key's
+/// validity is checked against it. If there is a delegation, the root key signs the delegation's
+/// certificate rather than this one.
+pub(crate) fn root_key_certificate_time(
+    certificate: &Certificate,
+) -> CertificateVerificationResult<u128> {
+    match &certificate.delegation {
+        Some(delegation) => delegation_certificate_time(delegation),
+        None => UnverifiedCertificate::new(certificate).lookup_time(),
+    }
+}
+
+fn delegation_certificate_time(delegation: &Delegation) -> CertificateVerificationResult<u128> {
+    let cert: Certificate = Certificate::from_cbor(&delegation.certificate)?;
+
+    UnverifiedCertificate::new(&cert).lookup_time()
 }
 
 pub fn validate_certificate_time(
@@ -161,8 +299,9 @@ pub fn validate_certificate_time(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{NoSignatureCache, SignatureCacheStatistics};
     use ic_cbor::CertificateToCbor;
-    use ic_certification::Certificate;
+    use ic_certification::{empty, fork, label, leaf, pruned, Certificate, HashTree};
     use ic_certification_testing::{CertificateBuilder, CertificateData};
     use ic_response_verification_test_utils::{
         create_canister_id, get_current_timestamp, get_timestamp, AssetTree,
@@ -174,9 +313,218 @@ mod tests {
 
     static CANISTER_ID: &str = "r7inp-6aaaa-aaaaa-aaabq-cai";
     const MAX_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000; // 5 min
+    const SUBNET_ID: &[u8] = &[1, 2, 3];
+
+    fn canister_id_from_u64(id: u64) -> Vec<u8> {
+        [id.to_be_bytes().as_slice(), &[0x01, 0x01]].concat()
+    }
+
+    fn principal_from_u64(id: u64) -> Principal {
+        Principal::from_slice(&canister_id_from_u64(id))
+    }
+
+    fn encode_canister_ranges(ranges: &[(u64, u64)]) -> Vec<u8> {
+        let mut cbor = vec![0xd9, 0xd9, 0xf7, 0x80 + ranges.len() as u8];
+        for (low, high) in ranges {
+            cbor.extend([0x82, 0x4a]);
+            cbor.extend(canister_id_from_u64(*low));
+            cbor.extend([0x4a]);
+            cbor.extend(canister_id_from_u64(*high));
+        }
+
+        cbor
+    }
+
+    fn create_sharded_tree(shards: &[(u64, &[(u64, u64)])]) -> HashTree {
+        let shards = shards
+            .iter()
+            .map(|(shard, ranges)| {
+                label(
+                    canister_id_from_u64(*shard),
+                    leaf(encode_canister_ranges(ranges)),
+                )
+            })
+            .reduce(fork)
+            .unwrap();
+
+        label("canister_ranges", label(SUBNET_ID.to_vec(), shards))
+    }
+
+    fn create_certificate_with_tree(tree: HashTree) -> Certificate {
+        Certificate {
+            tree,
+            signature: vec![],
+            delegation: None,
+        }
+    }
 
     #[test]
-    fn verify_certificate() {
+    fn principal_is_within_sorted_ranges_checks_range_bounds() {
+        let ranges = [
+            (principal_from_u64(0), principal_from_u64(9)),
+            (principal_from_u64(20), principal_from_u64(29)),
+        ];
+
+        for id in [0, 5, 9, 20, 29] {
+            assert!(principal_is_within_sorted_ranges(
+                &principal_from_u64(id),
+                &ranges
+            ));
+        }
+        for id in [10, 19, 30] {
+            assert!(!principal_is_within_sorted_ranges(
+                &principal_from_u64(id),
+                &ranges
+            ));
+        }
+        assert!(!principal_is_within_sorted_ranges(
+            &principal_from_u64(0),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn lookup_canister_ranges_from_shards() {
+        let certificate = create_certificate_with_tree(create_sharded_tree(&[
+            (10, &[(10, 19)]),
+            (30, &[(30, 39), (50, 59)]),
+        ]));
+
+        let canister_ranges = lookup_canister_ranges(
+            &VerifiedCertificate::new(&certificate),
+            SUBNET_ID,
+            &canister_id_from_u64(15),
+        )
+        .unwrap();
+        assert_eq!(
+            canister_ranges,
+            vec![(principal_from_u64(10), principal_from_u64(19))]
+        );
+
+        let canister_ranges = lookup_canister_ranges(
+            &VerifiedCertificate::new(&certificate),
+            SUBNET_ID,
+            &canister_id_from_u64(55),
+        )
+        .unwrap();
+        assert_eq!(
+            canister_ranges,
+            vec![
+                (principal_from_u64(30), principal_from_u64(39)),
+                (principal_from_u64(50), principal_from_u64(59)),
+            ]
+        );
+
+        let canister_ranges = lookup_canister_ranges(
+            &VerifiedCertificate::new(&certificate),
+            SUBNET_ID,
+            &canister_id_from_u64(5),
+        )
+        .unwrap();
+        assert!(canister_ranges.is_empty());
+    }
+
+    #[test]
+    fn lookup_canister_ranges_from_many_shards() {
+        let shards: Vec<(u64, Vec<(u64, u64)>)> = (0..8)
+            .map(|shard| (shard * 100, vec![(shard * 100, shard * 100 + 49)]))
+            .collect();
+        let shards: Vec<(u64, &[(u64, u64)])> = shards
+            .iter()
+            .map(|(shard, ranges)| (*shard, ranges.as_slice()))
+            .collect();
+        let certificate = create_certificate_with_tree(create_sharded_tree(&shards));
+
+        for shard in 0..8 {
+            let canister_ranges = lookup_canister_ranges(
+                &VerifiedCertificate::new(&certificate),
+                SUBNET_ID,
+                &canister_id_from_u64(shard * 100 + 75),
+            )
+            .unwrap();
+
+            assert_eq!(
+                canister_ranges,
+                vec![(
+                    principal_from_u64(shard * 100),
+                    principal_from_u64(shard * 100 + 49)
+                )]
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_canister_ranges_from_pruned_shards_should_fail() {
+        let certificate = create_certificate_with_tree(label(
+            "canister_ranges",
+            label(
+                SUBNET_ID.to_vec(),
+                fork(
+                    label(
+                        canister_id_from_u64(10),
+                        leaf(encode_canister_ranges(&[(10, 19)])),
+                    ),
+                    pruned([0; 32]),
+                ),
+            ),
+        ));
+
+        let result = lookup_canister_ranges(
+            &VerifiedCertificate::new(&certificate),
+            SUBNET_ID,
+            &canister_id_from_u64(15),
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::SubnetCanisterIdRangesNotFound { path })
+                if path == vec![b"canister_ranges".to_vec(), SUBNET_ID.to_vec()]
+        ));
+    }
+
+    #[test]
+    fn lookup_canister_ranges_from_subnet() {
+        let certificate = create_certificate_with_tree(label(
+            "subnet",
+            label(
+                SUBNET_ID.to_vec(),
+                label("canister_ranges", leaf(encode_canister_ranges(&[(0, 9)]))),
+            ),
+        ));
+
+        let canister_ranges = lookup_canister_ranges(
+            &VerifiedCertificate::new(&certificate),
+            SUBNET_ID,
+            &canister_id_from_u64(5),
+        )
+        .unwrap();
+
+        assert_eq!(
+            canister_ranges,
+            vec![(principal_from_u64(0), principal_from_u64(9))]
+        );
+    }
+
+    #[test]
+    fn lookup_missing_canister_ranges_should_fail() {
+        let certificate =
+            create_certificate_with_tree(label("subnet", label(SUBNET_ID.to_vec(), empty())));
+
+        let result = lookup_canister_ranges(
+            &VerifiedCertificate::new(&certificate),
+            SUBNET_ID,
+            &canister_id_from_u64(5),
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::SubnetCanisterIdRangesNotFound { path })
+                if path == vec![b"subnet".to_vec(), SUBNET_ID.to_vec(), b"canister_ranges".to_vec()]
+        ));
+    }
+
+    #[test]
+    fn verify_certificate_with_sharded_canister_ranges() {
         let canister_id = create_canister_id(CANISTER_ID);
         let CertificateData {
             cbor_encoded_certificate,
@@ -187,6 +535,8 @@ mod tests {
             &AssetTree::new().get_certified_data(),
         )
         .unwrap()
+        .with_delegation(123, vec![(0, 1), (2, 4), (10, 19), (20, 29)])
+        .with_sharded_canister_ranges(2)
         .build()
         .unwrap();
 
@@ -195,6 +545,260 @@ mod tests {
         certificate.verify(canister_id.as_ref(), &root_key).unwrap();
     }
 
+    #[test]
+    fn verify_certificate_with_sharded_canister_ranges_out_of_range_should_fail() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .with_delegation(123, vec![(0, 1), (4, 9), (10, 19), (20, 29)])
+        .with_sharded_canister_ranges(2)
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+
+        let result = certificate.verify(canister_id.as_ref(), &root_key);
+
+        assert!(matches!(
+            result.err(),
+            Some(CertificateVerificationError::PrincipalOutOfRange { canister_ranges, .. })
+                if canister_ranges == vec![
+                    (principal_from_u64(0), principal_from_u64(1)),
+                    (principal_from_u64(4), principal_from_u64(9)),
+                ]
+        ))
+    }
+
+    #[test]
+    fn verify_certificate() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+
+        let verified_certificate = certificate.verify(canister_id.as_ref(), &root_key).unwrap();
+
+        assert_eq!(
+            verified_certificate
+                .lookup_certified_data(canister_id.as_ref())
+                .unwrap(),
+            AssetTree::new().get_certified_data()
+        );
+    }
+
+    #[test]
+    fn verify_certificate_with_signature_cache() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+        let signature_cache = SizedSignatureCache::new(10);
+
+        certificate
+            .verify_with_signature_cache(canister_id.as_ref(), &root_key, &signature_cache)
+            .unwrap();
+        certificate
+            .verify_with_signature_cache(canister_id.as_ref(), &root_key, &signature_cache)
+            .unwrap();
+
+        assert_eq!(
+            signature_cache.cache_statistics(),
+            SignatureCacheStatistics::new(1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn verify_certificate_with_trusted_root_keys() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        let CertificateData {
+            cbor_encoded_certificate: _,
+            certificate: _,
+            root_key: other_root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+        let trusted_root_keys: TrustedRootKeys = [
+            TrustedRootKey::new("staging", other_root_key),
+            TrustedRootKey::new("mainnet", root_key),
+        ]
+        .into_iter()
+        .collect();
+
+        let (_, key) = certificate
+            .verify_with_trusted_root_keys(
+                canister_id.as_ref(),
+                &trusted_root_keys,
+                &NoSignatureCache,
+            )
+            .unwrap();
+
+        assert_eq!(key.label(), "mainnet");
+        assert_eq!(key.verified_certificates(), 1);
+        assert_eq!(
+            trusted_root_keys
+                .get("staging")
+                .unwrap()
+                .verified_certificates(),
+            0
+        );
+    }
+
+    #[test]
+    fn verify_certificate_with_expired_trusted_root_key_should_fail() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_timestamp = get_current_timestamp();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .with_time(current_timestamp)
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+        let trusted_root_keys: TrustedRootKeys = [TrustedRootKey::new("mainnet", root_key)
+            .with_validity(None, Some(current_timestamp - 1))]
+        .into_iter()
+        .collect();
+
+        let result = certificate.verify_with_trusted_root_keys(
+            canister_id.as_ref(),
+            &trusted_root_keys,
+            &NoSignatureCache,
+        );
+
+        assert!(matches!(
+            result.err(),
+            Some(CertificateVerificationError::NoTrustedRootKeyValidAtTime { certificate_time })
+                if certificate_time == current_timestamp
+        ))
+    }
+
+    #[test]
+    fn verify_certificate_with_trusted_root_key_valid_at_delegation_time() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_timestamp = get_current_timestamp();
+        let delegation_timestamp = current_timestamp - 1_000;
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .with_delegation(123, vec![(0, 9)])
+        .with_time(current_timestamp)
+        .with_delegation_time(delegation_timestamp)
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+        let trusted_root_keys: TrustedRootKeys = [TrustedRootKey::new("mainnet", root_key)
+            .with_validity(None, Some(current_timestamp - 1))]
+        .into_iter()
+        .collect();
+
+        let (_, key) = certificate
+            .verify_with_trusted_root_keys(
+                canister_id.as_ref(),
+                &trusted_root_keys,
+                &NoSignatureCache,
+            )
+            .unwrap();
+
+        assert_eq!(key.label(), "mainnet");
+    }
+
+    #[test]
+    fn verify_certificate_with_trusted_root_key_invalid_at_delegation_time_should_fail() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_timestamp = get_current_timestamp();
+        let delegation_timestamp = current_timestamp - 1_000;
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(
+            &canister_id.to_string(),
+            &AssetTree::new().get_certified_data(),
+        )
+        .unwrap()
+        .with_delegation(123, vec![(0, 9)])
+        .with_time(current_timestamp)
+        .with_delegation_time(delegation_timestamp)
+        .build()
+        .unwrap();
+
+        let certificate = Certificate::from_cbor(&cbor_encoded_certificate).unwrap();
+        let trusted_root_keys: TrustedRootKeys = [TrustedRootKey::new("mainnet", root_key)
+            .with_validity(Some(delegation_timestamp + 1), None)]
+        .into_iter()
+        .collect();
+
+        let result = certificate.verify_with_trusted_root_keys(
+            canister_id.as_ref(),
+            &trusted_root_keys,
+            &NoSignatureCache,
+        );
+
+        assert!(matches!(
+            result.err(),
+            Some(CertificateVerificationError::NoTrustedRootKeyValidAtTime { certificate_time })
+                if certificate_time == delegation_timestamp
+        ))
+    }
+
     #[test]
     fn verify_certificate_with_nested_delegation_should_fail() {
         let canister_id = create_canister_id(CANISTER_ID);
@@ -0,0 +1,449 @@
+use crate::{
+    certificate_verification::{validate_certificate_time, VerifyCertificate},
+    error::{CertificateVerificationError, CertificateVerificationResult},
+    signature_verification::{SignatureCache, SizedSignatureCache},
+    LookupCertificate, TrustedRootKey, TrustedRootKeys, VerifiedCertificate,
+};
+use ic_cbor::{CertificateToCbor, HashTreeToCbor};
+use ic_certification::{Certificate, HashTree, LookupResult};
+
+/// A suitable maximum offset between the time of a certificate and the current time for
+/// [verify_certified_data], five minutes.
+pub const MAX_CERTIFIED_DATA_TIME_OFFSET_NS: u128 = 300_000_000_000;
+
+/// A value that was verified by [verify_certified_data].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedValue {
+    /// The value at the path in the witness, or `None` if the witness proves that there is no
+    /// value at the path.
+    pub value: Option<Vec<u8>>,
+    /// The time of the certificate, in nanoseconds since the UNIX epoch.
+    pub certificate_time: u128,
+}
+
+/// Verifies a value that a canister certified with its certified data, for example the result of
+/// a certified query call, and returns the value at the provided path of the witness.
+///
+/// The canister is expected to return the CBOR encoded certificate that it obtained from
+/// `ic0.data_certificate`, and a CBOR encoded witness, a [HashTree] whose root hash is the
+/// canister's certified data. Verification checks that:
+///
+/// - the time of the certificate is within `max_certificate_time_offset_ns` of the current time,
+///   for example [MAX_CERTIFIED_DATA_TIME_OFFSET_NS],
+/// - the certificate, and its delegation if present, is signed by the provided root key,
+/// - the root hash of the witness matches the certified data of the canister in the certificate,
+/// - the witness either contains a value at the path, or proves that there is no value at it.
+///
+/// Verified signatures are cached in the [global signature cache](SizedSignatureCache::global).
+pub fn verify_certified_data(
+    certificate: &[u8],
+    witness: &[u8],
+    canister_id: &[u8],
+    root_key: &[u8],
+    current_time_ns: u128,
+    max_certificate_time_offset_ns: u128,
+    path: &[&[u8]],
+) -> CertificateVerificationResult<VerifiedValue> {
+    verify_certified_data_with_signature_cache(
+        certificate,
+        witness,
+        canister_id,
+        root_key,
+        current_time_ns,
+        max_certificate_time_offset_ns,
+        path,
+        SizedSignatureCache::global().as_ref(),
+    )
+}
+
+/// Verifies a value that a canister certified with its certified data, as described in
+/// [verify_certified_data], caching verified signatures in the provided [SignatureCache].
+#[allow(clippy::too_many_arguments)]
+pub fn verify_certified_data_with_signature_cache(
+    certificate: &[u8],
+    witness: &[u8],
+    canister_id: &[u8],
+    root_key: &[u8],
+    current_time_ns: u128,
+    max_certificate_time_offset_ns: u128,
+    path: &[&[u8]],
+    signature_cache: &dyn SignatureCache,
+) -> CertificateVerificationResult<VerifiedValue> {
+    let certificate = Certificate::from_cbor(certificate)?;
+    validate_certificate_time(
+        &certificate,
+        &current_time_ns,
+        &max_certificate_time_offset_ns,
+    )?;
+    let certificate =
+        certificate.verify_with_signature_cache(canister_id, root_key, signature_cache)?;
+
+    verify_witness(&certificate, witness, canister_id, path)
+}
+
+/// Verifies a value that a canister certified with its certified data, as described in
+/// [verify_certified_data], against each of the [TrustedRootKeys] that is valid at the time of the
+/// certificate, caching verified signatures in the provided [SignatureCache]. Returns the key that
+/// verified the certificate along with the value.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_certified_data_with_trusted_root_keys<'a>(
+    certificate: &[u8],
+    witness: &[u8],
+    canister_id: &[u8],
+    trusted_root_keys: &'a TrustedRootKeys,
+    current_time_ns: u128,
+    max_certificate_time_offset_ns: u128,
+    path: &[&[u8]],
+    signature_cache: &dyn SignatureCache,
+) -> CertificateVerificationResult<(VerifiedValue, &'a TrustedRootKey)> {
+    let certificate = Certificate::from_cbor(certificate)?;
+    validate_certificate_time(
+        &certificate,
+        &current_time_ns,
+        &max_certificate_time_offset_ns,
+    )?;
+    let (certificate, root_key) = certificate.verify_with_trusted_root_keys(
+        canister_id,
+        trusted_root_keys,
+        signature_cache,
+    )?;
+
+    Ok((
+        verify_witness(&certificate, witness, canister_id, path)?,
+        root_key,
+    ))
+}
+
+/// Checks that the root hash of the witness matches the certified data of the canister in the
+/// verified certificate, and looks up the value at the path of the witness.
+fn verify_witness(
+    certificate: &VerifiedCertificate,
+    witness: &[u8],
+    canister_id: &[u8],
+    path: &[&[u8]],
+) -> CertificateVerificationResult<VerifiedValue> {
+    let witness = HashTree::from_cbor(witness)?;
+    let certified_data = certificate.lookup_certified_data(canister_id)?;
+    let witness_digest = witness.digest();
+    if certified_data != witness_digest {
+        return Err(CertificateVerificationError::WitnessDigestMismatch {
+            certified_data: certified_data.to_vec(),
+            witness_digest,
+        });
+    }
+
+    let value = match witness.lookup_path(path) {
+        LookupResult::Found(value) => Some(value.to_vec()),
+        LookupResult::Absent => None,
+        LookupResult::Unknown => {
+            return Err(CertificateVerificationError::WitnessPathUnknown {
+                path: path.iter().map(|label| label.to_vec()).collect(),
+            })
+        }
+        LookupResult::Error => {
+            return Err(CertificateVerificationError::WitnessPathNotALeaf {
+                path: path.iter().map(|label| label.to_vec()).collect(),
+            })
+        }
+    };
+
+    Ok(VerifiedValue {
+        value,
+        certificate_time: certificate.lookup_time()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoSignatureCache, SignatureCacheStatistics};
+    use ic_certification::{AsHashTree, RbTree};
+    use ic_certification_testing::{CertificateBuilder, CertificateData};
+    use ic_response_verification_test_utils::{create_canister_id, get_current_timestamp};
+
+    static CANISTER_ID: &str = "r7inp-6aaaa-aaaaa-aaabq-cai";
+    static OTHER_CANISTER_ID: &str = "rdmx6-jaaaa-aaaaa-aaadq-cai";
+
+    fn create_tree() -> RbTree<&'static str, Vec<u8>> {
+        let mut tree = RbTree::new();
+        tree.insert("count", vec![0, 0, 0, 42]);
+        tree.insert("name", b"counter".to_vec());
+
+        tree
+    }
+
+    fn create_certificate(certified_data: &[u8], current_time: u128) -> CertificateData {
+        let canister_id = create_canister_id(CANISTER_ID);
+
+        CertificateBuilder::new(&canister_id.to_string(), certified_data)
+            .unwrap()
+            .with_time(current_time)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn verify_certified_value() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+
+        let result = verify_certified_data(
+            &cbor_encoded_certificate,
+            &tree.witness(b"count").to_cbor(),
+            canister_id.as_ref(),
+            &root_key,
+            current_time,
+            MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+            &[b"count"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            VerifiedValue {
+                value: Some(vec![0, 0, 0, 42]),
+                certificate_time: current_time,
+            }
+        );
+    }
+
+    #[test]
+    fn verify_certified_absent_value() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+
+        let result = verify_certified_data(
+            &cbor_encoded_certificate,
+            &tree.witness(b"missing").to_cbor(),
+            canister_id.as_ref(),
+            &root_key,
+            current_time,
+            MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+            &[b"missing"],
+        )
+        .unwrap();
+
+        assert_eq!(result.value, None);
+    }
+
+    #[test]
+    fn verify_certified_value_pruned_from_witness_should_fail() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+
+        let result = verify_certified_data(
+            &cbor_encoded_certificate,
+            &tree.witness(b"count").to_cbor(),
+            canister_id.as_ref(),
+            &root_key,
+            current_time,
+            MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+            &[b"name"],
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::WitnessPathUnknown { path })
+                if path == vec![b"name".to_vec()]
+        ));
+    }
+
+    #[test]
+    fn verify_certified_value_with_mismatched_witness_should_fail() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&[0; 32], current_time);
+
+        let result = verify_certified_data(
+            &cbor_encoded_certificate,
+            &tree.witness(b"count").to_cbor(),
+            canister_id.as_ref(),
+            &root_key,
+            current_time,
+            MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+            &[b"count"],
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::WitnessDigestMismatch { certified_data, .. })
+                if certified_data == vec![0; 32]
+        ));
+    }
+
+    #[test]
+    fn verify_certified_value_for_other_canister_should_fail() {
+        let other_canister_id = create_canister_id(OTHER_CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+
+        let result = verify_certified_data(
+            &cbor_encoded_certificate,
+            &tree.witness(b"count").to_cbor(),
+            other_canister_id.as_ref(),
+            &root_key,
+            current_time,
+            MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+            &[b"count"],
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::CertificatePathAbsent { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_expired_certified_value_should_fail() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+
+        let result = verify_certified_data(
+            &cbor_encoded_certificate,
+            &tree.witness(b"count").to_cbor(),
+            canister_id.as_ref(),
+            &root_key,
+            current_time + MAX_CERTIFIED_DATA_TIME_OFFSET_NS + 1,
+            MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+            &[b"count"],
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::TimeTooFarInThePast { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_certified_value_with_custom_time_offset_should_fail() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+
+        let result = verify_certified_data(
+            &cbor_encoded_certificate,
+            &tree.witness(b"count").to_cbor(),
+            canister_id.as_ref(),
+            &root_key,
+            current_time + 1_000,
+            999,
+            &[b"count"],
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::TimeTooFarInThePast { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_certified_value_with_signature_cache() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+        let signature_cache = SizedSignatureCache::new(10);
+
+        for _ in 0..2 {
+            let result = verify_certified_data_with_signature_cache(
+                &cbor_encoded_certificate,
+                &tree.witness(b"count").to_cbor(),
+                canister_id.as_ref(),
+                &root_key,
+                current_time,
+                MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+                &[b"count"],
+                &signature_cache,
+            )
+            .unwrap();
+
+            assert_eq!(result.value, Some(vec![0, 0, 0, 42]));
+        }
+
+        assert_eq!(
+            signature_cache.cache_statistics(),
+            SignatureCacheStatistics::new(1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn verify_certified_value_with_trusted_root_keys() {
+        let canister_id = create_canister_id(CANISTER_ID);
+        let current_time = get_current_timestamp();
+        let tree = create_tree();
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+        let CertificateData {
+            cbor_encoded_certificate: _,
+            certificate: _,
+            root_key: other_root_key,
+        } = create_certificate(&tree.root_hash(), current_time);
+        let trusted_root_keys: TrustedRootKeys = [
+            TrustedRootKey::new("staging", other_root_key),
+            TrustedRootKey::new("mainnet", root_key),
+        ]
+        .into_iter()
+        .collect();
+
+        let (result, key) = verify_certified_data_with_trusted_root_keys(
+            &cbor_encoded_certificate,
+            &tree.witness(b"count").to_cbor(),
+            canister_id.as_ref(),
+            &trusted_root_keys,
+            current_time,
+            MAX_CERTIFIED_DATA_TIME_OFFSET_NS,
+            &[b"count"],
+            &NoSignatureCache,
+        )
+        .unwrap();
+
+        assert_eq!(result.value, Some(vec![0, 0, 0, 42]));
+        assert_eq!(key.label(), "mainnet");
+    }
+}
@@ -91,4 +91,109 @@ pub enum CertificateVerificationError {
     /// The certificate contained more than one delegation.
     #[error("The certificate contained more than one delegation")]
     CertificateHasTooManyDelegations,
+
+    /// The root hash of the witness does not match the certified data of the canister
+    #[error("The root hash of the witness {witness_digest:?} does not match the certified data of the canister: {certified_data:?}")]
+    WitnessDigestMismatch {
+        /// The certified data of the canister in the certificate
+        certified_data: Vec<u8>,
+        /// The root hash of the witness
+        witness_digest: [u8; 32],
+    },
+
+    /// The path was pruned from the witness, so it is unknown whether it exists
+    #[error("Path is unknown, it was pruned from the witness: {path:?}")]
+    WitnessPathUnknown {
+        /// The path that was looked up in the witness
+        path: Vec<Vec<u8>>,
+    },
+
+    /// The path leads to a subtree of the witness, rather than a leaf
+    #[error("Path does not lead to a leaf in the witness: {path:?}")]
+    WitnessPathNotALeaf {
+        /// The path that was looked up in the witness
+        path: Vec<Vec<u8>>,
+    },
+
+    /// The certificate is older than the newest certificate that was already seen for the canister
+    #[error("Certificate time {certificate_time:?} is older than the newest certificate time already seen for the canister: {newest_certificate_time:?}")]
+    CertificateOlderThanNewestSeen {
+        /// The time of the certificate
+        certificate_time: u128,
+        /// The newest certificate time that was already seen for the canister
+        newest_certificate_time: u128,
+    },
+
+    /// None of the trusted root keys is valid at the time of the certificate
+    #[error("No trusted root key is valid at the certificate time: {certificate_time:?}")]
+    NoTrustedRootKeyValidAtTime {
+        /// The time of the certificate
+        certificate_time: u128,
+    },
+
+    /// The path is proven to be absent from the certificate's tree
+    #[error("Path is absent from the certificate's tree: {path:?}")]
+    CertificatePathAbsent {
+        /// The path that was looked up in the certificate
+        path: Vec<Vec<u8>>,
+    },
+
+    /// The path was pruned from the certificate's tree, so it is unknown whether it exists
+    #[error("Path is unknown, it was pruned from the certificate's tree: {path:?}")]
+    CertificatePathUnknown {
+        /// The path that was looked up in the certificate
+        path: Vec<Vec<u8>>,
+    },
+
+    /// The path leads to a subtree of the certificate's tree, rather than a leaf
+    #[error("Path does not lead to a leaf in the certificate's tree: {path:?}")]
+    CertificatePathNotALeaf {
+        /// The path that was looked up in the certificate
+        path: Vec<Vec<u8>>,
+    },
+
+    /// The value found at a path in the certificate's tree could not be decoded
+    #[error("Value at path {path:?} in the certificate's tree is malformed: {reason}")]
+    MalformedCertificateValue {
+        /// The path that was looked up in the certificate
+        path: Vec<Vec<u8>>,
+        /// The reason that the value could not be decoded
+        reason: String,
+    },
+
+    /// The certificate's delegation is for a different subnet than the certificate was requested for
+    #[error("The certificate's delegation is for subnet {actual:?}, but the certificate was requested for subnet {expected:?}")]
+    DelegationSubnetIdMismatch {
+        /// The ID of the subnet that the certificate was requested for
+        expected: Vec<u8>,
+        /// The ID of the subnet in the certificate's delegation
+        actual: Vec<u8>,
+    },
+
+    /// The certificate is missing the nodes of the subnet
+    #[error("Subnet nodes not found in certificate at path: {path:?}")]
+    SubnetNodesNotFound {
+        /// The path that was used to look up the nodes in the certificate
+        path: Vec<Vec<u8>>,
+    },
+
+    /// The query response was not signed by any node
+    #[error("The query response has no node signatures")]
+    MissingNodeSignatures,
+
+    /// The query response was signed by a node that is not a node of the subnet
+    #[error("Node {node_id:?} is not a node of subnet {subnet_id:?}")]
+    NodeNotInSubnet {
+        /// The ID of the node that signed the query response
+        node_id: Vec<u8>,
+        /// The ID of the subnet
+        subnet_id: Vec<u8>,
+    },
+
+    /// Failed to verify a node's signature of the query response
+    #[error("Signature verification failed for node {node_id:?}")]
+    NodeSignatureVerificationFailed {
+        /// The ID of the node that signed the query response
+        node_id: Vec<u8>,
+    },
 }
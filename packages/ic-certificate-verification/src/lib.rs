@@ -1,7 +1,26 @@
 mod signature_verification;
+pub use signature_verification::{
+    NoSignatureCache, SignatureCache, SignatureCacheEntry, SignatureCacheStatistics,
+    SizedSignatureCache,
+};
 
 mod error;
 pub use error::*;
 
 mod certificate_verification;
 pub use certificate_verification::*;
+
+mod certificate_lookup;
+pub use certificate_lookup::*;
+
+mod trusted_root_keys;
+pub use trusted_root_keys::*;
+
+mod certificate_freshness;
+pub use certificate_freshness::*;
+
+mod certified_data_verification;
+pub use certified_data_verification::*;
+
+mod node_signature_verification;
+pub use node_signature_verification::*;
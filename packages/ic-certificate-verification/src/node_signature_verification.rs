@@ -0,0 +1,793 @@
+use crate::{
+    certificate_verification::{
+        root_key_certificate_time, verify_certificate_with, verify_delegation_with,
+    },
+    error::{CertificateVerificationError, CertificateVerificationResult},
+    signature_verification::{SignatureCache, SizedSignatureCache},
+    LookupCertificate, TrustedRootKey, TrustedRootKeys, VerifiedCertificate,
+};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use ic_cbor::CertificateToCbor;
+use ic_certification::{Certificate, SubtreeLookupResult};
+use ic_representation_independent_hash::{representation_independent_hash, Sha256Digest, Value};
+use std::collections::BTreeMap;
+
+const IC_RESPONSE_DOMAIN_SEPARATOR: &[u8; 12] = b"\x0Bic-response";
+const ED25519_DER_PREFIX: &[u8; 12] = b"\x30\x2a\x30\x05\x06\x03\x2b\x65\x70\x03\x21\x00";
+const ED25519_KEY_LENGTH: usize = 32;
+
+/// The content of a query response that is signed by the replica nodes, as described in the
+/// [Interface Specification](https://internetcomputer.org/docs/current/references/ic-interface-spec#http-query).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryResponse {
+    /// The query was processed and has a reply.
+    Replied {
+        /// The Candid encoded reply of the query.
+        arg: Vec<u8>,
+    },
+    /// The query was processed and was rejected.
+    Rejected {
+        /// The reject code of the query.
+        reject_code: u64,
+        /// The reject message of the query.
+        reject_message: String,
+        /// The error code of the query, if the replica provided one.
+        error_code: Option<String>,
+    },
+}
+
+/// A signature of a replica node over a [QueryResponse].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeSignature {
+    /// The time at which the node signed the response, in nanoseconds since the UNIX epoch.
+    pub timestamp: u64,
+    /// The ed25519 signature of the node.
+    pub signature: Vec<u8>,
+    /// The ID of the node that signed the response.
+    pub identity: Vec<u8>,
+}
+
+/// The ed25519 public keys of the nodes of a subnet, as certified at
+/// `subnet/<subnet_id>/node/<node_id>/public_key` in a certificate returned by a `read_state`
+/// request for the subnet.
+///
+/// The keys are used to verify the [NodeSignature]s of query responses, so that responses from
+/// nodes outside of the subnet are rejected.
+#[derive(Debug, Clone)]
+pub struct SubnetNodeKeys {
+    subnet_id: Vec<u8>,
+    certificate_time: u128,
+    node_keys: BTreeMap<Vec<u8>, VerifyingKey>,
+}
+
+impl SubnetNodeKeys {
+    /// Verifies the CBOR encoded certificate of the subnet with the provided ID against the root
+    /// key, and reads the public keys of the subnet's nodes from it. Verified signatures are cached
+    /// in the [global signature cache](SizedSignatureCache::global).
+    ///
+    /// If the certificate has a delegation, the delegation must be for the same subnet. The time
+    /// of the certificate is not validated, use [SubnetNodeKeys::certificate_time] to decide how
+    /// long the keys should be trusted.
+    pub fn from_certificate(
+        certificate: &[u8],
+        subnet_id: &[u8],
+        root_key: &[u8],
+    ) -> CertificateVerificationResult<Self> {
+        Self::from_certificate_with_signature_cache(
+            certificate,
+            subnet_id,
+            root_key,
+            SizedSignatureCache::global().as_ref(),
+        )
+    }
+
+    /// Reads the public keys of the subnet's nodes, as described in
+    /// [SubnetNodeKeys::from_certificate], caching verified signatures in the provided
+    /// [SignatureCache].
+    pub fn from_certificate_with_signature_cache(
+        certificate: &[u8],
+        subnet_id: &[u8],
+        root_key: &[u8],
+        signature_cache: &dyn SignatureCache,
+    ) -> CertificateVerificationResult<Self> {
+        let certificate = Certificate::from_cbor(certificate)?;
+        let certificate =
+            verify_subnet_certificate(&certificate, subnet_id, root_key, signature_cache)?;
+
+        lookup_subnet_node_keys(&certificate, subnet_id)
+    }
+
+    /// Reads the public keys of the subnet's nodes, as described in
+    /// [SubnetNodeKeys::from_certificate], verifying the certificate against each of the
+    /// [TrustedRootKeys] that is valid at the time of the certificate signed by the root key and
+    /// caching verified signatures in the provided [SignatureCache]. Returns the key that verified
+    /// the certificate along with the node keys.
+    pub fn from_certificate_with_trusted_root_keys<'a>(
+        certificate: &[u8],
+        subnet_id: &[u8],
+        trusted_root_keys: &'a TrustedRootKeys,
+        signature_cache: &dyn SignatureCache,
+    ) -> CertificateVerificationResult<(Self, &'a TrustedRootKey)> {
+        let certificate = Certificate::from_cbor(certificate)?;
+        let (certificate, root_key) = trusted_root_keys.verify_with(
+            root_key_certificate_time(&certificate)?,
+            |root_key| {
+                verify_subnet_certificate(&certificate, subnet_id, root_key, signature_cache)
+            },
+        )?;
+
+        Ok((lookup_subnet_node_keys(&certificate, subnet_id)?, root_key))
+    }
+
+    /// Returns the ID of the subnet.
+    pub fn subnet_id(&self) -> &[u8] {
+        &self.subnet_id
+    }
+
+    /// Returns the time of the certificate that the keys were read from, in nanoseconds since the
+    /// UNIX epoch.
+    pub fn certificate_time(&self) -> u128 {
+        self.certificate_time
+    }
+
+    /// Returns the IDs of the subnet's nodes, in ascending order.
+    pub fn node_ids(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.node_keys.keys().map(Vec::as_slice)
+    }
+
+    /// Returns the raw ed25519 public key of the node with the provided ID, if it is a node of
+    /// the subnet.
+    pub fn node_public_key(&self, node_id: &[u8]) -> Option<&[u8; ED25519_KEY_LENGTH]> {
+        self.node_keys.get(node_id).map(VerifyingKey::as_bytes)
+    }
+
+    /// Verifies that the query response for the request with the provided request ID is signed by
+    /// nodes of the subnet.
+    ///
+    /// There must be at least one signature, and every signature must be made by a node of the
+    /// subnet over the response, the request ID and the timestamp of the signature.
+    pub fn verify_query_response(
+        &self,
+        request_id: &Sha256Digest,
+        response: &QueryResponse,
+        signatures: &[NodeSignature],
+    ) -> CertificateVerificationResult {
+        if signatures.is_empty() {
+            return Err(CertificateVerificationError::MissingNodeSignatures);
+        }
+
+        for node_signature in signatures {
+            let Some(node_key) = self.node_keys.get(&node_signature.identity) else {
+                return Err(CertificateVerificationError::NodeNotInSubnet {
+                    node_id: node_signature.identity.clone(),
+                    subnet_id: self.subnet_id.clone(),
+                });
+            };
+
+            let signature_verification_failed =
+                || CertificateVerificationError::NodeSignatureVerificationFailed {
+                    node_id: node_signature.identity.clone(),
+                };
+            let signature = Signature::from_slice(&node_signature.signature)
+                .map_err(|_| signature_verification_failed())?;
+
+            let mut msg = vec![];
+            msg.extend_from_slice(IC_RESPONSE_DOMAIN_SEPARATOR);
+            msg.extend_from_slice(&query_response_hash(
+                response,
+                request_id,
+                node_signature.timestamp,
+            ));
+
+            node_key
+                .verify(&msg, &signature)
+                .map_err(|_| signature_verification_failed())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Verifies a certificate returned by a `read_state` request for a subnet. Unlike canister
+/// certificates, the delegation is checked against the subnet ID, rather than canister ranges.
+fn verify_subnet_certificate<'a>(
+    certificate: &'a Certificate,
+    subnet_id: &[u8],
+    root_key: &[u8],
+    signature_cache: &dyn SignatureCache,
+) -> CertificateVerificationResult<VerifiedCertificate<'a>> {
+    verify_certificate_with(certificate, root_key, signature_cache, |delegation| {
+        if delegation.subnet_id != subnet_id {
+            return Err(CertificateVerificationError::DelegationSubnetIdMismatch {
+                expected: subnet_id.to_vec(),
+                actual: delegation.subnet_id.clone(),
+            });
+        }
+
+        verify_delegation_with(delegation, root_key, signature_cache, |_| Ok(()))
+    })
+}
+
+fn lookup_subnet_node_keys(
+    certificate: &VerifiedCertificate,
+    subnet_id: &[u8],
+) -> CertificateVerificationResult<SubnetNodeKeys> {
+    let nodes_path: [&[u8]; 3] = [b"subnet", subnet_id, b"node"];
+    let SubtreeLookupResult::Found(nodes) = certificate
+        .certificate()
+        .tree
+        .lookup_subtree(&nodes_path) else {
+        return Err(CertificateVerificationError::SubnetNodesNotFound {
+            path: nodes_path.iter().map(|p| p.to_vec()).collect(),
+        });
+    };
+
+    let mut node_keys = BTreeMap::new();
+    for path in nodes.list_paths() {
+        let [node_id, public_key_label] = path.as_slice() else {
+            continue;
+        };
+        if public_key_label.as_bytes() != b"public_key" {
+            continue;
+        }
+
+        let node_id = node_id.as_bytes();
+        let der_key = certificate.lookup_subnet_node_public_key(subnet_id, node_id)?;
+        let node_key = extract_ed25519_der(der_key).map_err(|reason| {
+            CertificateVerificationError::MalformedCertificateValue {
+                path: [b"subnet", subnet_id, b"node", node_id, b"public_key"]
+                    .iter()
+                    .map(|p| p.to_vec())
+                    .collect(),
+                reason,
+            }
+        })?;
+
+        node_keys.insert(node_id.to_vec(), node_key);
+    }
+
+    Ok(SubnetNodeKeys {
+        subnet_id: subnet_id.to_vec(),
+        certificate_time: certificate.lookup_time()?,
+        node_keys,
+    })
+}
+
+fn extract_ed25519_der(der_key: &[u8]) -> Result<VerifyingKey, String> {
+    let Some(key) = der_key.strip_prefix(ED25519_DER_PREFIX.as_slice()) else {
+        return Err("expected a DER encoded ed25519 public key".to_string());
+    };
+    let key: &[u8; ED25519_KEY_LENGTH] = key.try_into().map_err(|_| {
+        format!(
+            "ed25519 public key must be {ED25519_KEY_LENGTH} bytes long, but is {} bytes long",
+            key.len()
+        )
+    })?;
+
+    VerifyingKey::from_bytes(key).map_err(|e| e.to_string())
+}
+
+/// Calculates the representation independent hash of the signed content of a query response,
+/// which is the response's fields together with the request ID and the signature's timestamp.
+fn query_response_hash(
+    response: &QueryResponse,
+    request_id: &Sha256Digest,
+    timestamp: u64,
+) -> Sha256Digest {
+    let mut map = vec![
+        ("request_id".into(), Value::Bytes(request_id.to_vec())),
+        ("timestamp".into(), Value::Number(timestamp)),
+    ];
+
+    match response {
+        QueryResponse::Replied { arg } => {
+            map.push(("status".into(), Value::String("replied".into())));
+            map.push((
+                "reply".into(),
+                Value::Map(vec![("arg".into(), Value::Bytes(arg.clone()))]),
+            ));
+        }
+        QueryResponse::Rejected {
+            reject_code,
+            reject_message,
+            error_code,
+        } => {
+            map.push(("status".into(), Value::String("rejected".into())));
+            map.push(("reject_code".into(), Value::Number(*reject_code)));
+            map.push((
+                "reject_message".into(),
+                Value::String(reject_message.clone()),
+            ));
+            if let Some(error_code) = error_code {
+                map.push(("error_code".into(), Value::String(error_code.clone())));
+            }
+        }
+    }
+
+    representation_independent_hash(&map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NoSignatureCache, SignatureCacheStatistics};
+    use ed25519_dalek::{Signer, SigningKey};
+    use ic_certification::{fork, label, leaf, HashTree};
+    use ic_certification_testing::{create_subnet_id, CertificateBuilder, CertificateData};
+    use ic_representation_independent_hash::{request_id, RequestContent};
+    use ic_response_verification_test_utils::hex_decode;
+
+    const SUBNET_ID: &[u8] = &[1, 1];
+    const NODE_ID: &[u8] = &[2, 2];
+    const OTHER_NODE_ID: &[u8] = &[3, 3];
+    const REQUEST_ID: &Sha256Digest = &[4; 32];
+    const TIMESTAMP: u64 = 1_700_000_000_000_000_000;
+
+    fn create_signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn der_encode(signing_key: &SigningKey) -> Vec<u8> {
+        [
+            ED25519_DER_PREFIX.as_slice(),
+            signing_key.verifying_key().as_bytes(),
+        ]
+        .concat()
+    }
+
+    fn create_certificate(nodes: HashTree) -> Certificate {
+        Certificate {
+            tree: fork(
+                label("subnet", label(SUBNET_ID.to_vec(), label("node", nodes))),
+                label(
+                    "time",
+                    leaf([0xb8, 0xb1, 0x8d, 0xee, 0xb0, 0xda, 0x8c, 0x95, 0x17]),
+                ),
+            ),
+            signature: vec![],
+            delegation: None,
+        }
+    }
+
+    fn create_subnet_node_keys() -> SubnetNodeKeys {
+        let certificate = create_certificate(fork(
+            label(
+                NODE_ID.to_vec(),
+                label("public_key", leaf(der_encode(&create_signing_key(1)))),
+            ),
+            label(
+                OTHER_NODE_ID.to_vec(),
+                label("public_key", leaf(der_encode(&create_signing_key(2)))),
+            ),
+        ));
+
+        lookup_subnet_node_keys(&VerifiedCertificate::new(&certificate), SUBNET_ID).unwrap()
+    }
+
+    fn sign(
+        signing_key: &SigningKey,
+        node_id: &[u8],
+        response: &QueryResponse,
+        request_id: &Sha256Digest,
+    ) -> NodeSignature {
+        let mut msg = vec![];
+        msg.extend_from_slice(IC_RESPONSE_DOMAIN_SEPARATOR);
+        msg.extend_from_slice(&query_response_hash(response, request_id, TIMESTAMP));
+
+        NodeSignature {
+            timestamp: TIMESTAMP,
+            signature: signing_key.sign(&msg).to_bytes().to_vec(),
+            identity: node_id.to_vec(),
+        }
+    }
+
+    fn create_node_public_keys() -> Vec<(Vec<u8>, Vec<u8>)> {
+        vec![
+            (NODE_ID.to_vec(), der_encode(&create_signing_key(1))),
+            (OTHER_NODE_ID.to_vec(), der_encode(&create_signing_key(2))),
+        ]
+    }
+
+    fn create_reply() -> QueryResponse {
+        QueryResponse::Replied {
+            arg: b"DIDL\x00\x01\x71\x05hello".to_vec(),
+        }
+    }
+
+    #[test]
+    fn lookup_node_keys() {
+        let subnet_node_keys = create_subnet_node_keys();
+
+        assert_eq!(subnet_node_keys.subnet_id(), SUBNET_ID);
+        assert_eq!(
+            subnet_node_keys.certificate_time(),
+            1_669_202_493_944_584_376
+        );
+        assert_eq!(
+            subnet_node_keys.node_ids().collect::<Vec<_>>(),
+            vec![NODE_ID, OTHER_NODE_ID]
+        );
+        assert_eq!(
+            subnet_node_keys.node_public_key(NODE_ID).unwrap(),
+            create_signing_key(1).verifying_key().as_bytes()
+        );
+        assert!(subnet_node_keys.node_public_key(&[9, 9]).is_none());
+    }
+
+    #[test]
+    fn lookup_node_keys_with_missing_nodes_should_fail() {
+        let certificate = Certificate {
+            tree: label(
+                "subnet",
+                label(SUBNET_ID.to_vec(), label("public_key", leaf([1, 2, 3]))),
+            ),
+            signature: vec![],
+            delegation: None,
+        };
+
+        let result = lookup_subnet_node_keys(&VerifiedCertificate::new(&certificate), SUBNET_ID);
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::SubnetNodesNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn lookup_node_keys_with_malformed_key_should_fail() {
+        let certificate = create_certificate(label(
+            NODE_ID.to_vec(),
+            label("public_key", leaf([0x30, 0x2a, 0x30, 0x05])),
+        ));
+
+        let result = lookup_subnet_node_keys(&VerifiedCertificate::new(&certificate), SUBNET_ID);
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::MalformedCertificateValue { .. })
+        ));
+    }
+
+    #[test]
+    fn subnet_node_keys_from_certificate() {
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::from_subnet_node_keys(1, create_node_public_keys())
+            .with_time(TIMESTAMP.into())
+            .build()
+            .unwrap();
+        let response = create_reply();
+        let signatures = [sign(&create_signing_key(1), NODE_ID, &response, REQUEST_ID)];
+
+        let subnet_node_keys = SubnetNodeKeys::from_certificate(
+            &cbor_encoded_certificate,
+            &create_subnet_id(1),
+            &root_key,
+        )
+        .unwrap();
+
+        assert_eq!(subnet_node_keys.subnet_id(), create_subnet_id(1));
+        assert_eq!(subnet_node_keys.certificate_time(), TIMESTAMP.into());
+        assert_eq!(
+            subnet_node_keys.node_ids().collect::<Vec<_>>(),
+            vec![NODE_ID, OTHER_NODE_ID]
+        );
+        subnet_node_keys
+            .verify_query_response(REQUEST_ID, &response, &signatures)
+            .unwrap();
+    }
+
+    #[test]
+    fn subnet_node_keys_from_delegated_certificate() {
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::from_subnet_node_keys(1, create_node_public_keys())
+            .with_delegation(1, vec![(0, 9)])
+            .build()
+            .unwrap();
+
+        let subnet_node_keys = SubnetNodeKeys::from_certificate(
+            &cbor_encoded_certificate,
+            &create_subnet_id(1),
+            &root_key,
+        )
+        .unwrap();
+
+        assert_eq!(
+            subnet_node_keys.node_public_key(NODE_ID).unwrap(),
+            create_signing_key(1).verifying_key().as_bytes()
+        );
+    }
+
+    #[test]
+    fn subnet_node_keys_from_certificate_delegated_to_other_subnet_should_fail() {
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::from_subnet_node_keys(1, create_node_public_keys())
+            .with_delegation(2, vec![(0, 9)])
+            .build()
+            .unwrap();
+
+        let result = SubnetNodeKeys::from_certificate(
+            &cbor_encoded_certificate,
+            &create_subnet_id(1),
+            &root_key,
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::DelegationSubnetIdMismatch { expected, actual })
+                if expected == create_subnet_id(1) && actual == create_subnet_id(2)
+        ));
+    }
+
+    #[test]
+    fn subnet_node_keys_from_certificate_with_nested_delegation_should_fail() {
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::from_subnet_node_keys(1, create_node_public_keys())
+            .with_delegation(1, vec![(0, 9)])
+            .with_nested_delegation(2, vec![(0, 9)])
+            .build()
+            .unwrap();
+
+        let result = SubnetNodeKeys::from_certificate(
+            &cbor_encoded_certificate,
+            &create_subnet_id(1),
+            &root_key,
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::CertificateHasTooManyDelegations)
+        ));
+    }
+
+    #[test]
+    fn subnet_node_keys_from_certificate_with_invalid_signature_should_fail() {
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::from_subnet_node_keys(1, create_node_public_keys())
+            .with_invalid_signature()
+            .build()
+            .unwrap();
+
+        let result = SubnetNodeKeys::from_certificate(
+            &cbor_encoded_certificate,
+            &create_subnet_id(1),
+            &root_key,
+        );
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::SignatureVerificationFailed)
+        ));
+    }
+
+    #[test]
+    fn subnet_node_keys_from_certificate_with_signature_cache() {
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::from_subnet_node_keys(1, create_node_public_keys())
+            .with_delegation(1, vec![(0, 9)])
+            .build()
+            .unwrap();
+        let signature_cache = SizedSignatureCache::new(10);
+
+        for _ in 0..2 {
+            SubnetNodeKeys::from_certificate_with_signature_cache(
+                &cbor_encoded_certificate,
+                &create_subnet_id(1),
+                &root_key,
+                &signature_cache,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            signature_cache.cache_statistics(),
+            SignatureCacheStatistics::new(2, 2, 2)
+        );
+    }
+
+    #[test]
+    fn subnet_node_keys_from_certificate_with_trusted_root_keys() {
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::from_subnet_node_keys(1, create_node_public_keys())
+            .with_delegation(1, vec![(0, 9)])
+            .with_time(TIMESTAMP.into())
+            .build()
+            .unwrap();
+        let trusted_root_keys: TrustedRootKeys = [
+            TrustedRootKey::new("expired", root_key.clone())
+                .with_validity(None, Some(u128::from(TIMESTAMP) - 1)),
+            TrustedRootKey::new("mainnet", root_key),
+        ]
+        .into_iter()
+        .collect();
+
+        let (subnet_node_keys, key) = SubnetNodeKeys::from_certificate_with_trusted_root_keys(
+            &cbor_encoded_certificate,
+            &create_subnet_id(1),
+            &trusted_root_keys,
+            &NoSignatureCache,
+        )
+        .unwrap();
+
+        assert_eq!(subnet_node_keys.subnet_id(), create_subnet_id(1));
+        assert_eq!(key.label(), "mainnet");
+    }
+
+    #[test]
+    fn replied_query_response_hash_known_answer() {
+        let result = query_response_hash(&create_reply(), REQUEST_ID, TIMESTAMP);
+
+        assert_eq!(
+            result.to_vec(),
+            hex_decode("4b27fee331d2e5c2723c403a6f699cdc20583954c9f214bdcb6f4ae3f70975c2")
+        );
+    }
+
+    #[test]
+    fn rejected_query_response_hash_known_answer() {
+        let response = QueryResponse::Rejected {
+            reject_code: 3,
+            reject_message: "Canister has no query method".to_string(),
+            error_code: Some("IC0302".to_string()),
+        };
+
+        let result = query_response_hash(&response, REQUEST_ID, TIMESTAMP);
+
+        assert_eq!(
+            result.to_vec(),
+            hex_decode("75568fa7408433af80fd745c5246c269f964335e9f2a80d55a0c0f95db66468c")
+        );
+    }
+
+    #[test]
+    fn rejected_query_response_hash() {
+        let response = QueryResponse::Rejected {
+            reject_code: 3,
+            reject_message: "Canister has no query method".to_string(),
+            error_code: Some("IC0302".to_string()),
+        };
+        let expected_hash = representation_independent_hash(&[
+            ("request_id".into(), Value::Bytes(REQUEST_ID.to_vec())),
+            ("timestamp".into(), Value::Number(TIMESTAMP)),
+            ("status".into(), Value::String("rejected".into())),
+            ("reject_code".into(), Value::Number(3)),
+            (
+                "reject_message".into(),
+                Value::String("Canister has no query method".into()),
+            ),
+            ("error_code".into(), Value::String("IC0302".into())),
+        ]);
+
+        let result = query_response_hash(&response, REQUEST_ID, TIMESTAMP);
+
+        assert_eq!(result, expected_hash);
+    }
+
+    #[test]
+    fn verify_query_response() {
+        let subnet_node_keys = create_subnet_node_keys();
+        let response = create_reply();
+        let signatures = [
+            sign(&create_signing_key(1), NODE_ID, &response, REQUEST_ID),
+            sign(&create_signing_key(2), OTHER_NODE_ID, &response, REQUEST_ID),
+        ];
+
+        subnet_node_keys
+            .verify_query_response(REQUEST_ID, &response, &signatures)
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_query_response_without_signatures_should_fail() {
+        let subnet_node_keys = create_subnet_node_keys();
+
+        let result = subnet_node_keys.verify_query_response(REQUEST_ID, &create_reply(), &[]);
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::MissingNodeSignatures)
+        ));
+    }
+
+    #[test]
+    fn verify_query_response_from_node_outside_of_subnet_should_fail() {
+        let subnet_node_keys = create_subnet_node_keys();
+        let response = create_reply();
+        let signatures = [sign(&create_signing_key(3), &[9, 9], &response, REQUEST_ID)];
+
+        let result = subnet_node_keys.verify_query_response(REQUEST_ID, &response, &signatures);
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::NodeNotInSubnet { node_id, subnet_id })
+                if node_id == [9, 9] && subnet_id == SUBNET_ID
+        ));
+    }
+
+    #[test]
+    fn verify_query_response_signed_with_wrong_key_should_fail() {
+        let subnet_node_keys = create_subnet_node_keys();
+        let response = create_reply();
+        let signatures = [sign(&create_signing_key(2), NODE_ID, &response, REQUEST_ID)];
+
+        let result = subnet_node_keys.verify_query_response(REQUEST_ID, &response, &signatures);
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::NodeSignatureVerificationFailed { node_id })
+                if node_id == NODE_ID
+        ));
+    }
+
+    #[test]
+    fn verify_query_response_for_other_request_should_fail() {
+        let create_query = |arg: &[u8]| RequestContent::Query {
+            canister_id: vec![0, 0, 0, 0, 0, 0, 0, 1, 1, 1],
+            method_name: "greet".into(),
+            arg: arg.to_vec(),
+            sender: vec![0x04],
+            ingress_expiry: TIMESTAMP,
+            nonce: None,
+        };
+        let query_request_id = request_id(&create_query(b"DIDL\x00\x00"));
+        let other_query_request_id = request_id(&create_query(b"DIDL\x00\x01\x7e\x01"));
+        let subnet_node_keys = create_subnet_node_keys();
+        let response = create_reply();
+        let signatures = [sign(
+            &create_signing_key(1),
+            NODE_ID,
+            &response,
+            &other_query_request_id,
+        )];
+
+        let result =
+            subnet_node_keys.verify_query_response(&query_request_id, &response, &signatures);
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::NodeSignatureVerificationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_tampered_query_response_should_fail() {
+        let subnet_node_keys = create_subnet_node_keys();
+        let signatures = [sign(
+            &create_signing_key(1),
+            NODE_ID,
+            &create_reply(),
+            REQUEST_ID,
+        )];
+        let tampered_response = QueryResponse::Replied {
+            arg: b"DIDL\x00\x01\x71\x05world".to_vec(),
+        };
+
+        let result =
+            subnet_node_keys.verify_query_response(REQUEST_ID, &tampered_response, &signatures);
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::NodeSignatureVerificationFailed { .. })
+        ));
+    }
+}
@@ -1,8 +1,8 @@
-use self::signature_cache::{SignatureCache, SignatureCacheEntry};
 use crate::CertificateVerificationError;
 use miracl_core_bls12381::bls12381::bls::{core_verify, BLS_OK};
 
 mod signature_cache;
+pub use signature_cache::*;
 
 #[cfg(test)]
 mod reproducible_rng;
@@ -10,14 +10,15 @@ mod reproducible_rng;
 #[cfg(test)]
 mod tests;
 
-pub fn verify_signature(
+pub(crate) fn verify_signature(
     pk: &[u8],
     sig: &[u8],
     msg: &[u8],
+    signature_cache: &dyn SignatureCache,
 ) -> Result<(), CertificateVerificationError> {
     let entry = SignatureCacheEntry::new(pk, sig, msg);
 
-    if SignatureCache::global().contains(&entry) {
+    if signature_cache.contains(&entry) {
         return Ok(());
     }
 
@@ -27,6 +28,6 @@ pub fn verify_signature(
         return Err(CertificateVerificationError::SignatureVerificationFailed);
     }
 
-    SignatureCache::global().insert(&entry);
+    signature_cache.insert(&entry);
     Ok(())
 }
@@ -1,24 +1,30 @@
 //! Cache for BLS signatures
 
 use cached::{Cached, SizedCache};
+use std::{fmt, sync::Arc};
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-#[cfg(test)]
+/// Statistics about a [SignatureCache], for example to be reported as metrics.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub struct SignatureCacheStatistics {
+    /// The number of entries in the cache
     pub size: usize,
+    /// The number of lookups that found an entry in the cache
     pub hits: u64,
+    /// The number of lookups that did not find an entry in the cache
     pub misses: u64,
 }
 
-#[cfg(test)]
 impl SignatureCacheStatistics {
-    pub(super) fn new(size: usize, hits: u64, misses: u64) -> Self {
+    /// Creates new statistics from the size of the cache and its number of hits and misses
+    pub fn new(size: usize, hits: u64, misses: u64) -> Self {
         Self { size, hits, misses }
     }
 }
 
+/// An entry in a [SignatureCache], a hash of the public key, signature and message of a BLS
+/// signature that was verified.
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
-pub(crate) struct SignatureCacheEntry {
+pub struct SignatureCacheEntry {
     hash: [u8; 32],
 }
 
@@ -26,7 +32,7 @@ impl SignatureCacheEntry {
     /// Hash the verification inputs to a short string
     ///
     /// This reduces the amount of memory the cache consumes
-    pub(crate) fn new(pk: &[u8], sig: &[u8], msg: &[u8]) -> Self {
+    pub fn new(pk: &[u8], sig: &[u8], msg: &[u8]) -> Self {
         use sha2::{Digest, Sha256};
         let mut sha256 = Sha256::new();
         sha256.update(pk);
@@ -37,16 +43,49 @@ impl SignatureCacheEntry {
     }
 }
 
-/// A cache for BLS signature verification
-pub(crate) struct SignatureCache {
+/// A cache of BLS signatures that have already been verified, so that verifying the same
+/// certificate again does not repeat the expensive signature verification.
+///
+/// [SizedSignatureCache] is the default implementation and [NoSignatureCache] disables caching.
+pub trait SignatureCache: Send + Sync {
+    /// Check if a cache entry already exists
+    ///
+    /// Returns true if found, false otherwise
+    fn contains(&self, entry: &SignatureCacheEntry) -> bool;
+
+    /// Insert a entry into the signature cache
+    ///
+    /// # Warning
+    /// A signature should only be added to the cache if it has previously
+    /// been verified to be valid.
+    fn insert(&self, entry: &SignatureCacheEntry);
+
+    /// Return statistics about the cache
+    ///
+    /// Returns the size of the cache, the number of cache hits, and
+    /// the number of cache misses
+    fn cache_statistics(&self) -> SignatureCacheStatistics;
+}
+
+/// A [SignatureCache] that holds up to a fixed number of entries, evicting the least recently used
+/// entry when it is full.
+pub struct SizedSignatureCache {
     cache: parking_lot::Mutex<SizedCache<SignatureCacheEntry, ()>>,
 }
 
 lazy_static::lazy_static! {
-    static ref GLOBAL_SIGNATURE_CACHE: SignatureCache = SignatureCache::new(SignatureCache::SIZE_OF_GLOBAL_CACHE);
+    static ref GLOBAL_SIGNATURE_CACHE: Arc<SizedSignatureCache> = Arc::new(SizedSignatureCache::new(SizedSignatureCache::SIZE_OF_GLOBAL_CACHE));
 }
 
-impl SignatureCache {
+impl fmt::Debug for SizedSignatureCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SizedSignatureCache")
+            .field("statistics", &self.cache_statistics())
+            .finish_non_exhaustive()
+    }
+}
+
+impl SizedSignatureCache {
     /// Specify the size of the global signature cache
     ///
     /// cached::SizedCache uses approximately 65 bytes of memory
@@ -68,42 +107,35 @@ impl SignatureCache {
     pub const SIZE_OF_GLOBAL_CACHE: usize = 100000;
 
     /// Create a new signature cache with the specified maximum size
-    pub(super) fn new(max_size: usize) -> Self {
+    ///
+    /// # Panics
+    /// Panics if the maximum size is zero, [NoSignatureCache] should be used to disable caching.
+    pub fn new(max_size: usize) -> Self {
         let cache = parking_lot::Mutex::<SizedCache<SignatureCacheEntry, ()>>::new(
             SizedCache::with_size(max_size),
         );
         Self { cache }
     }
 
-    /// Return a reference to the global signature cache
-    pub(crate) fn global() -> &'static Self {
-        &GLOBAL_SIGNATURE_CACHE
+    /// Return the global signature cache, which holds [SIZE_OF_GLOBAL_CACHE](Self::SIZE_OF_GLOBAL_CACHE)
+    /// entries and is used when no other cache is provided
+    pub fn global() -> Arc<Self> {
+        Arc::clone(&GLOBAL_SIGNATURE_CACHE)
     }
+}
 
-    /// Check if a cache entry already exists
-    ///
-    /// Returns true if found, false otherwise
-    pub(crate) fn contains(&self, entry: &SignatureCacheEntry) -> bool {
+impl SignatureCache for SizedSignatureCache {
+    fn contains(&self, entry: &SignatureCacheEntry) -> bool {
         let mut cache = self.cache.lock();
         cache.cache_get(entry).is_some()
     }
 
-    /// Insert a entry into the signature cache
-    ///
-    /// # Warning
-    /// A signature should only be added to the cache if it has previously
-    /// been verified to be valid.
-    pub(crate) fn insert(&self, entry: &SignatureCacheEntry) {
+    fn insert(&self, entry: &SignatureCacheEntry) {
         let mut cache = self.cache.lock();
         cache.cache_set(*entry, ());
     }
 
-    /// Return statistics about the cache
-    ///
-    /// Returns the size of the cache, the number of cache hits, and
-    /// the number of cache misses
-    #[cfg(test)]
-    pub(crate) fn cache_statistics(&self) -> SignatureCacheStatistics {
+    fn cache_statistics(&self) -> SignatureCacheStatistics {
         let cache = self.cache.lock();
 
         let cache_size = cache.cache_size();
@@ -113,3 +145,21 @@ impl SignatureCache {
         SignatureCacheStatistics::new(cache_size, hits, misses)
     }
 }
+
+/// A [SignatureCache] that never holds any entries, so that every signature is verified.
+///
+/// This is intended for memory constrained environments, such as service workers.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NoSignatureCache;
+
+impl SignatureCache for NoSignatureCache {
+    fn contains(&self, _entry: &SignatureCacheEntry) -> bool {
+        false
+    }
+
+    fn insert(&self, _entry: &SignatureCacheEntry) {}
+
+    fn cache_statistics(&self) -> SignatureCacheStatistics {
+        SignatureCacheStatistics::default()
+    }
+}
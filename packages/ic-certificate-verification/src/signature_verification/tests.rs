@@ -1,7 +1,9 @@
 use super::signature_cache::SignatureCacheEntry;
 use crate::signature_verification::{
     reproducible_rng::reproducible_rng,
-    signature_cache::{SignatureCache, SignatureCacheStatistics},
+    signature_cache::{
+        NoSignatureCache, SignatureCache, SignatureCacheStatistics, SizedSignatureCache,
+    },
 };
 use rand::RngCore;
 
@@ -20,7 +22,7 @@ fn random_signature_cache_entry<R: RngCore>(rng: &mut R) -> SignatureCacheEntry
 #[test]
 fn should_have_signature_cache_behave_like_a_lru_cache() {
     let cache_size = 1000;
-    let cache = SignatureCache::new(cache_size);
+    let cache = SizedSignatureCache::new(cache_size);
 
     let rng = &mut reproducible_rng();
 
@@ -86,7 +88,7 @@ fn should_have_signature_cache_behave_like_a_lru_cache() {
 #[test]
 fn should_have_signature_cache_update_lru_status_after_cache_hit() {
     let cache_size = 3;
-    let cache = SignatureCache::new(cache_size);
+    let cache = SizedSignatureCache::new(cache_size);
 
     let rng = &mut reproducible_rng();
 
@@ -119,3 +121,19 @@ fn should_have_signature_cache_update_lru_status_after_cache_hit() {
     assert!(cache.contains(&entry3));
     assert!(!cache.contains(&entry4));
 }
+
+#[test]
+fn should_have_no_signature_cache_never_contain_entries() {
+    let cache = NoSignatureCache;
+
+    let rng = &mut reproducible_rng();
+    let entry = random_signature_cache_entry(rng);
+
+    cache.insert(&entry);
+
+    assert!(!cache.contains(&entry));
+    assert_eq!(
+        cache.cache_statistics(),
+        SignatureCacheStatistics::new(0, 0, 0)
+    );
+}
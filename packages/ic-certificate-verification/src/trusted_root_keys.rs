@@ -0,0 +1,251 @@
+use crate::error::{CertificateVerificationError, CertificateVerificationResult};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A DER encoded root key that certificates are verified against, with a label to identify it
+/// and an optional window of certificate times in which it is valid.
+#[derive(Debug)]
+pub struct TrustedRootKey {
+    label: String,
+    der_key: Vec<u8>,
+    not_before_ns: Option<u128>,
+    not_after_ns: Option<u128>,
+    verified_certificates: AtomicU64,
+}
+
+impl TrustedRootKey {
+    /// Creates a root key that is valid for certificates of any time.
+    pub fn new(label: impl Into<String>, der_key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            label: label.into(),
+            der_key: der_key.into(),
+            not_before_ns: None,
+            not_after_ns: None,
+            verified_certificates: AtomicU64::new(0),
+        }
+    }
+
+    /// Restricts the root key to certificates whose time, in nanoseconds since the UNIX epoch, is
+    /// within the provided bounds. Both bounds are inclusive and a missing bound is unrestricted.
+    pub fn with_validity(
+        mut self,
+        not_before_ns: Option<u128>,
+        not_after_ns: Option<u128>,
+    ) -> Self {
+        self.not_before_ns = not_before_ns;
+        self.not_after_ns = not_after_ns;
+
+        self
+    }
+
+    /// Returns the label of the root key.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the DER encoded root key.
+    pub fn der_key(&self) -> &[u8] {
+        &self.der_key
+    }
+
+    /// Returns true if the root key is valid for a certificate with the provided time.
+    pub fn is_valid_at(&self, certificate_time_ns: u128) -> bool {
+        let is_too_early = matches!(
+            self.not_before_ns,
+            Some(not_before_ns) if certificate_time_ns < not_before_ns
+        );
+        let is_too_late = matches!(
+            self.not_after_ns,
+            Some(not_after_ns) if certificate_time_ns > not_after_ns
+        );
+
+        !is_too_early && !is_too_late
+    }
+
+    /// Returns the number of certificates that have been verified by the root key, for example to
+    /// find out when a rotated key is no longer in use.
+    pub fn verified_certificates(&self) -> u64 {
+        self.verified_certificates.load(Ordering::Relaxed)
+    }
+}
+
+/// A store of the root keys that certificates are trusted to be signed by, for example the root
+/// keys of several networks, or the current and next root key of a network during a key rotation.
+///
+/// A certificate is verified against each key that is valid at the time of the certificate, in
+/// the order the keys were added, and each key records the number of certificates it verified.
+#[derive(Debug, Default)]
+pub struct TrustedRootKeys {
+    keys: Vec<TrustedRootKey>,
+}
+
+impl TrustedRootKeys {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a root key to the store.
+    pub fn add(&mut self, key: TrustedRootKey) -> &mut Self {
+        self.keys.push(key);
+
+        self
+    }
+
+    /// Returns the root keys of the store, in the order they were added.
+    pub fn keys(&self) -> &[TrustedRootKey] {
+        &self.keys
+    }
+
+    /// Returns the first root key with the provided label.
+    pub fn get(&self, label: &str) -> Option<&TrustedRootKey> {
+        self.keys.iter().find(|key| key.label == label)
+    }
+
+    /// Returns the root keys that are valid for a certificate with the provided time.
+    pub fn keys_valid_at(
+        &self,
+        certificate_time_ns: u128,
+    ) -> impl Iterator<Item = &TrustedRootKey> + '_ {
+        self.keys
+            .iter()
+            .filter(move |key| key.is_valid_at(certificate_time_ns))
+    }
+
+    /// Runs `verify` with each root key that is valid for a certificate with the provided time
+    /// until one succeeds, and records the verification against that key. If no key succeeds, the
+    /// error of the last key that was tried is returned.
+    pub(crate) fn verify_with<T>(
+        &self,
+        certificate_time_ns: u128,
+        verify: impl Fn(&[u8]) -> CertificateVerificationResult<T>,
+    ) -> CertificateVerificationResult<(T, &TrustedRootKey)> {
+        let mut result = Err(CertificateVerificationError::NoTrustedRootKeyValidAtTime {
+            certificate_time: certificate_time_ns,
+        });
+
+        for key in self.keys_valid_at(certificate_time_ns) {
+            result = verify(&key.der_key).map(|value| (value, key));
+
+            if let Ok((_, key)) = &result {
+                key.verified_certificates.fetch_add(1, Ordering::Relaxed);
+                break;
+            }
+        }
+
+        result
+    }
+}
+
+impl FromIterator<TrustedRootKey> for TrustedRootKeys {
+    fn from_iter<I: IntoIterator<Item = TrustedRootKey>>(keys: I) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_trusted_root_keys() -> TrustedRootKeys {
+        [
+            TrustedRootKey::new("current", vec![1]).with_validity(None, Some(200)),
+            TrustedRootKey::new("next", vec![2]).with_validity(Some(100), None),
+            TrustedRootKey::new("local", vec![3]),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn labels<'a>(keys: impl Iterator<Item = &'a TrustedRootKey>) -> Vec<&'a str> {
+        keys.map(TrustedRootKey::label).collect()
+    }
+
+    #[test]
+    fn keys_valid_at_certificate_time() {
+        let trusted_root_keys = create_trusted_root_keys();
+
+        assert_eq!(
+            labels(trusted_root_keys.keys_valid_at(99)),
+            vec!["current", "local"]
+        );
+        assert_eq!(
+            labels(trusted_root_keys.keys_valid_at(100)),
+            vec!["current", "next", "local"]
+        );
+        assert_eq!(
+            labels(trusted_root_keys.keys_valid_at(200)),
+            vec!["current", "next", "local"]
+        );
+        assert_eq!(
+            labels(trusted_root_keys.keys_valid_at(201)),
+            vec!["next", "local"]
+        );
+    }
+
+    #[test]
+    fn get_key_by_label() {
+        let trusted_root_keys = create_trusted_root_keys();
+
+        assert_eq!(trusted_root_keys.get("next").unwrap().der_key(), &[2]);
+        assert!(trusted_root_keys.get("unknown").is_none());
+    }
+
+    #[test]
+    fn verify_with_records_the_key_that_verified() {
+        let trusted_root_keys = create_trusted_root_keys();
+
+        let (_, key) = trusted_root_keys
+            .verify_with(150, |der_key| match der_key {
+                [2] => Ok(()),
+                _ => Err(CertificateVerificationError::SignatureVerificationFailed),
+            })
+            .unwrap();
+
+        assert_eq!(key.label(), "next");
+
+        let verified_certificates = |label| {
+            trusted_root_keys
+                .get(label)
+                .unwrap()
+                .verified_certificates()
+        };
+        assert_eq!(verified_certificates("current"), 0);
+        assert_eq!(verified_certificates("next"), 1);
+        assert_eq!(verified_certificates("local"), 0);
+    }
+
+    #[test]
+    fn verify_with_returns_the_error_of_the_last_key() {
+        let trusted_root_keys = create_trusted_root_keys();
+
+        let result = trusted_root_keys.verify_with(50, |der_key| match der_key {
+            [1] => Err::<(), _>(CertificateVerificationError::SignatureVerificationFailed),
+            _ => Err(CertificateVerificationError::CertificateHasTooManyDelegations),
+        });
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::CertificateHasTooManyDelegations)
+        ));
+    }
+
+    #[test]
+    fn verify_with_no_key_valid_at_certificate_time() {
+        let trusted_root_keys: TrustedRootKeys =
+            [TrustedRootKey::new("current", vec![1]).with_validity(Some(100), Some(200))]
+                .into_iter()
+                .collect();
+
+        let result = trusted_root_keys.verify_with(201, |_| Ok(()));
+
+        assert!(matches!(
+            result,
+            Err(CertificateVerificationError::NoTrustedRootKeyValidAtTime {
+                certificate_time: 201
+            })
+        ));
+        assert_eq!(trusted_root_keys.keys()[0].verified_certificates(), 0);
+    }
+}
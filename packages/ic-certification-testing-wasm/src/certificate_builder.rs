@@ -49,6 +49,13 @@ impl CertificateBuilder {
         Ok(self)
     }
 
+    #[wasm_bindgen(js_name = withShardedCanisterRanges)]
+    pub fn with_sharded_canister_ranges(mut self, shard_size: usize) -> Self {
+        self.builder.with_sharded_canister_ranges(shard_size);
+
+        self
+    }
+
     #[wasm_bindgen(js_name = withTime)]
     pub fn with_time_js(mut self, time: u64) -> Self {
         self.builder
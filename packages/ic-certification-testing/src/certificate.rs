@@ -1,5 +1,5 @@
 use crate::{encoding::serialize_to_cbor, error::CertificationTestResult};
-use ic_crypto_tree_hash::{flatmap, Label, LabeledTree};
+use ic_crypto_tree_hash::{flatmap, FlatMap, Label, LabeledTree};
 use ic_types::{CanisterId, SubnetId};
 
 pub(crate) fn create_certificate_tree(
@@ -16,18 +16,71 @@ pub(crate) fn create_certificate_tree(
         Label::from("time") => LabeledTree::Leaf(encoded_time.to_vec())])
 }
 
+pub(crate) fn create_subnet_node_tree(
+    subnet_id: &SubnetId,
+    node_public_keys: &[(Vec<u8>, Vec<u8>)],
+    encoded_time: &[u8],
+) -> LabeledTree<Vec<u8>> {
+    let nodes = node_public_keys
+        .iter()
+        .map(|(node_id, public_key)| {
+            (
+                Label::from(node_id.clone()),
+                LabeledTree::SubTree(flatmap![
+                    Label::from("public_key") => LabeledTree::Leaf(public_key.clone()),
+                ]),
+            )
+        })
+        .collect();
+    let nodes = LabeledTree::SubTree(FlatMap::from_key_values(nodes));
+
+    LabeledTree::SubTree(flatmap![
+        Label::from("subnet") => LabeledTree::SubTree(flatmap![
+            Label::from(subnet_id.get_ref().to_vec()) => LabeledTree::SubTree(flatmap![
+                Label::from("node") => nodes,
+            ])
+        ]),
+        Label::from("time") => LabeledTree::Leaf(encoded_time.to_vec())])
+}
+
 pub(crate) fn create_delegation_tree(
     delegatee_public_key: &[u8],
     encoded_time: &[u8],
     subnet_id: &SubnetId,
     canister_ranges: &[(CanisterId, CanisterId)],
+    canister_ranges_shard_size: Option<usize>,
 ) -> CertificationTestResult<LabeledTree<Vec<u8>>> {
-    let canister_ranges = serialize_to_cbor(&canister_ranges.to_vec());
+    let Some(shard_size) = canister_ranges_shard_size else {
+        let canister_ranges = serialize_to_cbor(&canister_ranges.to_vec());
+
+        return Ok(LabeledTree::SubTree(flatmap![
+            Label::from("subnet") => LabeledTree::SubTree(flatmap![
+                Label::from(subnet_id.get_ref().to_vec()) => LabeledTree::SubTree(flatmap![
+                    Label::from("canister_ranges") => LabeledTree::Leaf(canister_ranges),
+                    Label::from("public_key") => LabeledTree::Leaf(delegatee_public_key.to_vec()),
+                ])
+            ]),
+            Label::from("time") => LabeledTree::Leaf(encoded_time.to_vec())
+        ]));
+    };
+
+    let shards = canister_ranges
+        .chunks(shard_size.max(1))
+        .map(|shard| {
+            (
+                Label::from(shard[0].0.get_ref().to_vec()),
+                LabeledTree::Leaf(serialize_to_cbor(&shard.to_vec())),
+            )
+        })
+        .collect();
+    let shards = LabeledTree::SubTree(FlatMap::from_key_values(shards));
 
     Ok(LabeledTree::SubTree(flatmap![
+        Label::from("canister_ranges") => LabeledTree::SubTree(flatmap![
+            Label::from(subnet_id.get_ref().to_vec()) => shards,
+        ]),
         Label::from("subnet") => LabeledTree::SubTree(flatmap![
             Label::from(subnet_id.get_ref().to_vec()) => LabeledTree::SubTree(flatmap![
-                Label::from("canister_ranges") => LabeledTree::Leaf(canister_ranges),
                 Label::from("public_key") => LabeledTree::Leaf(delegatee_public_key.to_vec()),
             ])
         ]),
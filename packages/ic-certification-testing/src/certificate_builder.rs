@@ -1,5 +1,5 @@
 use crate::{
-    certificate::{create_certificate_tree, create_delegation_tree},
+    certificate::{create_certificate_tree, create_delegation_tree, create_subnet_node_tree},
     encoding::{leb_encode_timestamp, serialize_to_cbor},
     error::{CertificationTestError, CertificationTestResult},
     signature::{generate_keypair, get_tree_signature, KeyPair},
@@ -43,14 +43,23 @@ pub struct CanisterData {
     certified_data: Vec<u8>,
 }
 
+#[derive(Debug, Clone)]
+struct SubnetNodeData {
+    subnet_id: SubnetId,
+    node_public_keys: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CertificateBuilder {
     time: Option<u128>,
+    delegation_time: Option<u128>,
     canister: Option<CanisterData>,
+    subnet_nodes: Option<SubnetNodeData>,
     subnet: Option<SubnetData>,
     nested_subnet: Option<SubnetData>,
     signature: Option<Blob>,
     custom_tree: Option<LabeledTree<Vec<u8>>>,
+    canister_ranges_shard_size: Option<usize>,
 }
 
 impl CertificateBuilder {
@@ -63,25 +72,54 @@ impl CertificateBuilder {
 
         Ok(CertificateBuilder {
             time: None,
+            delegation_time: None,
             canister: Some(CanisterData {
                 canister_id,
                 certified_data: certified_data.to_vec(),
             }),
+            subnet_nodes: None,
             subnet: None,
             nested_subnet: None,
             signature: None,
             custom_tree: None,
+            canister_ranges_shard_size: None,
         })
     }
 
     pub fn from_custom_tree(custom_tree: LabeledTree<Vec<u8>>) -> Self {
         CertificateBuilder {
             time: None,
+            delegation_time: None,
             canister: None,
+            subnet_nodes: None,
             subnet: None,
             nested_subnet: None,
             signature: None,
             custom_tree: Some(custom_tree),
+            canister_ranges_shard_size: None,
+        }
+    }
+
+    /// Creates a certificate like the one returned by a `read_state` request for a subnet, with
+    /// the public key of each node of the subnet at `subnet/<subnet_id>/node/<node_id>/public_key`.
+    /// The subnet ID is created as in [create_subnet_id].
+    pub fn from_subnet_node_keys(
+        subnet_id: u64,
+        node_public_keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Self {
+        CertificateBuilder {
+            time: None,
+            delegation_time: None,
+            canister: None,
+            subnet_nodes: Some(SubnetNodeData {
+                subnet_id: SubnetId::from(PrincipalId::new_subnet_test_id(subnet_id)),
+                node_public_keys,
+            }),
+            subnet: None,
+            nested_subnet: None,
+            signature: None,
+            custom_tree: None,
+            canister_ranges_shard_size: None,
         }
     }
 
@@ -105,12 +143,29 @@ impl CertificateBuilder {
         self
     }
 
+    /// Stores the canister ID ranges of delegations in the sharded
+    /// `canister_ranges/<subnet_id>/<shard>` layout, with at most `shard_size` ranges per shard,
+    /// instead of in a single `subnet/<subnet_id>/canister_ranges` leaf.
+    pub fn with_sharded_canister_ranges(&mut self, shard_size: usize) -> &mut Self {
+        self.canister_ranges_shard_size = Some(shard_size);
+
+        self
+    }
+
     pub fn with_time(&mut self, time: u128) -> &mut Self {
         self.time = Some(time);
 
         self
     }
 
+    /// Sets the time of the delegation's certificate, which is otherwise the same as the time of
+    /// the certificate.
+    pub fn with_delegation_time(&mut self, delegation_time: u128) -> &mut Self {
+        self.delegation_time = Some(delegation_time);
+
+        self
+    }
+
     pub fn with_invalid_signature(&mut self) -> &mut Self {
         let signature =
             CombinedThresholdSig(b"invalid sig -----padding to get to 48 bytes-----".to_vec());
@@ -123,23 +178,37 @@ impl CertificateBuilder {
         let time = self.time.unwrap_or(DEFAULT_CERTIFICATE_TIME);
         let encoded_time = leb_encode_timestamp(time)?;
 
-        let tree = (match (&self.custom_tree, &self.canister) {
-            (Some(custom_tree), None) => Ok(custom_tree.clone()),
-            (None, Some(canister)) => Ok(create_certificate_tree(
+        let tree = (match (&self.custom_tree, &self.canister, &self.subnet_nodes) {
+            (Some(custom_tree), None, None) => Ok(custom_tree.clone()),
+            (None, Some(canister), None) => Ok(create_certificate_tree(
                 &canister.canister_id,
                 &canister.certified_data,
                 &encoded_time,
             )),
-            (Some(_), Some(_)) => {
+            (None, None, Some(subnet_nodes)) => Ok(create_subnet_node_tree(
+                &subnet_nodes.subnet_id,
+                &subnet_nodes.node_public_keys,
+                &encoded_time,
+            )),
+            (Some(_), Some(_), _) => {
                 Err(CertificationTestError::BothCanisterParamsAndCustomTreeProvided)
             }
-            (None, None) => Err(CertificationTestError::CanisterParamsOrCustomTreeRequired),
+            (Some(_), None, Some(_)) | (None, Some(_), Some(_)) => {
+                Err(CertificationTestError::BothSubnetNodeKeysAndCanisterParamsOrCustomTreeProvided)
+            }
+            (None, None, None) => Err(CertificationTestError::CanisterParamsOrCustomTreeRequired),
         })?;
 
         let (keypair, tree, signature) = build_certificate(&tree)?;
         let signature = self.signature.as_ref().unwrap_or(&signature);
 
-        let nested_delegation_data = self.build_nested_delegation(&keypair, &encoded_time)?;
+        let encoded_delegation_time = match self.delegation_time {
+            Some(delegation_time) => leb_encode_timestamp(delegation_time)?,
+            None => encoded_time.clone(),
+        };
+
+        let nested_delegation_data =
+            self.build_nested_delegation(&keypair, &encoded_delegation_time)?;
         if let Some((delegation, keypair)) = nested_delegation_data {
             let certificate = Certificate {
                 tree,
@@ -155,7 +224,7 @@ impl CertificateBuilder {
             });
         }
 
-        let delegation_data = self.build_delegation(&keypair, &encoded_time)?;
+        let delegation_data = self.build_delegation(&keypair, &encoded_delegation_time)?;
         if let Some((delegation, keypair)) = delegation_data {
             let certificate = Certificate {
                 tree,
@@ -192,8 +261,13 @@ impl CertificateBuilder {
         encoded_time: &[u8],
     ) -> CertificationTestResult<Option<(CertificateDelegation, KeyPair)>> {
         if let Some(subnet_data) = &self.subnet {
-            let delegation_data =
-                create_delegation_data(delegatee_keypair, encoded_time, subnet_data, None)?;
+            let delegation_data = create_delegation_data(
+                delegatee_keypair,
+                encoded_time,
+                subnet_data,
+                self.canister_ranges_shard_size,
+                None,
+            )?;
 
             return Ok(Some(delegation_data));
         }
@@ -212,6 +286,7 @@ impl CertificateBuilder {
                     delegatee_keypair,
                     encoded_time,
                     nested_subnet_data,
+                    self.canister_ranges_shard_size,
                     None,
                 )?;
 
@@ -219,6 +294,7 @@ impl CertificateBuilder {
                     &nested_keypair,
                     encoded_time,
                     subnet_data,
+                    self.canister_ranges_shard_size,
                     Some(nested_delegation),
                 )?;
 
@@ -233,6 +309,7 @@ fn create_delegation_data(
     delegatee_keypair: &KeyPair,
     encoded_time: &[u8],
     subnet_data: &SubnetData,
+    canister_ranges_shard_size: Option<usize>,
     nested_delegation: Option<CertificateDelegation>,
 ) -> CertificationTestResult<(CertificateDelegation, KeyPair)> {
     let tree = create_delegation_tree(
@@ -240,6 +317,7 @@ fn create_delegation_data(
         encoded_time,
         &subnet_data.subnet_id,
         &subnet_data.canister_id_ranges,
+        canister_ranges_shard_size,
     )?;
     let (keypair, tree, signature) = build_certificate(&tree)?;
     let certificate = Certificate {
@@ -257,6 +335,14 @@ fn create_delegation_data(
     ))
 }
 
+/// Returns the ID of the test subnet with the provided number, as used by
+/// [CertificateBuilder::with_delegation] and [CertificateBuilder::from_subnet_node_keys].
+pub fn create_subnet_id(subnet_id: u64) -> Vec<u8> {
+    SubnetId::from(PrincipalId::new_subnet_test_id(subnet_id))
+        .get()
+        .to_vec()
+}
+
 fn create_subnet_data(subnet_id: u64, canister_id_ranges: Vec<(u64, u64)>) -> SubnetData {
     let canister_id_ranges = canister_id_ranges
         .into_iter()
@@ -33,6 +33,9 @@ pub enum CertificationTestError {
     #[error("only one of canister params or a custom tree may be provided")]
     BothCanisterParamsAndCustomTreeProvided,
 
+    #[error("subnet node keys may not be provided together with canister params or a custom tree")]
+    BothSubnetNodeKeysAndCanisterParamsOrCustomTreeProvided,
+
     #[error("failed to merge witnesses")]
     WitnessMergingFailed,
 }
@@ -0,0 +1,246 @@
+//! Human readable exports of a [HashTree], for debugging and visualizing trees.
+//!
+//! Forks are flattened in every format, so the nodes below a fork are shown as siblings in the
+//! order of their labels. Labels and leaves are shown as UTF-8 when printable and as hex
+//! otherwise, and the digests of pruned nodes are abbreviated.
+
+use super::{HashTree, HashTreeNode};
+use std::fmt::Write;
+
+/// The number of bytes of a pruned digest, or of a long non-printable leaf, that are shown.
+const ABBREVIATED_LEN: usize = 4;
+
+/// The maximum length, in bytes, of a non-printable leaf that is shown in full.
+const MAX_FULL_HEX_LEN: usize = 32;
+
+impl<Storage: AsRef<[u8]>> HashTree<Storage> {
+    /// Exports the tree as an indented ASCII tree, with one node per line.
+    ///
+    /// ```text
+    /// "a"
+    /// |-- "x"
+    /// |   `-- leaf("hello")
+    /// `-- pruned(1b4feff9...)
+    /// "b"
+    /// `-- leaf("good")
+    /// ```
+    pub fn to_ascii_tree(&self) -> String {
+        let mut out = String::new();
+        for node in fork_leaves(&self.root) {
+            out.push_str(&node_text(node));
+            out.push('\n');
+            write_ascii_children(&mut out, node, "");
+        }
+
+        out
+    }
+
+    /// Exports the tree as a [Graphviz](https://graphviz.org) DOT graph.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph HashTree {\n    root [label=\"\", shape=point];\n");
+        let mut next_id = 0;
+        for node in fork_leaves(&self.root) {
+            write_dot_node(&mut out, node, "root", &mut next_id);
+        }
+        out.push_str("}\n");
+
+        out
+    }
+
+    /// Exports the tree as a pretty printed JSON array of its top level nodes.
+    ///
+    /// Each node is an object with a `type` of `labeled`, `leaf`, `pruned` or `empty`. Labeled
+    /// nodes have a `label` and the `children` below it, leaves have a `value` and pruned nodes
+    /// have an abbreviated `digest`.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        write_json_nodes(&mut out, &fork_leaves(&self.root), "");
+        out.push('\n');
+
+        out
+    }
+}
+
+fn fork_leaves<Storage: AsRef<[u8]>>(node: &HashTreeNode<Storage>) -> Vec<&HashTreeNode<Storage>> {
+    let mut nodes = vec![];
+    node.collect_fork_leaves(&mut nodes);
+
+    nodes
+}
+
+fn children<Storage: AsRef<[u8]>>(node: &HashTreeNode<Storage>) -> Vec<&HashTreeNode<Storage>> {
+    match node {
+        HashTreeNode::Labeled(_, subtree) => fork_leaves(subtree),
+        _ => vec![],
+    }
+}
+
+fn node_text<Storage: AsRef<[u8]>>(node: &HashTreeNode<Storage>) -> String {
+    match node {
+        HashTreeNode::Labeled(label, _) => match printable(label.as_bytes()) {
+            Some(label) => format!("{:?}", label),
+            None => format!("0x{}", hex::encode(label.as_bytes())),
+        },
+        HashTreeNode::Leaf(value) => format!("leaf({})", leaf_text(value.as_ref())),
+        HashTreeNode::Pruned(digest) => format!("pruned({})", abbreviated_hex(digest)),
+        HashTreeNode::Empty() => "empty".to_string(),
+        HashTreeNode::Fork(_) => "fork".to_string(),
+    }
+}
+
+fn printable(bytes: &[u8]) -> Option<&str> {
+    std::str::from_utf8(bytes)
+        .ok()
+        .filter(|s| s.chars().all(|c| !c.is_control()))
+}
+
+fn leaf_text(value: &[u8]) -> String {
+    match printable(value) {
+        Some(value) => format!("{:?}", value),
+        None if value.len() <= MAX_FULL_HEX_LEN => format!("0x{}", hex::encode(value)),
+        None => format!("0x{} ({} bytes)", abbreviated_hex(value), value.len()),
+    }
+}
+
+fn abbreviated_hex(bytes: &[u8]) -> String {
+    format!(
+        "{}...",
+        hex::encode(&bytes[..bytes.len().min(ABBREVIATED_LEN)])
+    )
+}
+
+fn write_ascii_children<Storage: AsRef<[u8]>>(
+    out: &mut String,
+    node: &HashTreeNode<Storage>,
+    prefix: &str,
+) {
+    let children = children(node);
+    for (i, child) in children.iter().enumerate() {
+        let is_last = i == children.len() - 1;
+        let (connector, child_prefix) = if is_last {
+            ("`-- ", "    ")
+        } else {
+            ("|-- ", "|   ")
+        };
+
+        out.push_str(prefix);
+        out.push_str(connector);
+        out.push_str(&node_text(child));
+        out.push('\n');
+        write_ascii_children(out, child, &format!("{}{}", prefix, child_prefix));
+    }
+}
+
+fn write_dot_node<Storage: AsRef<[u8]>>(
+    out: &mut String,
+    node: &HashTreeNode<Storage>,
+    parent_id: &str,
+    next_id: &mut usize,
+) {
+    let id = format!("n{}", next_id);
+    *next_id += 1;
+
+    let attributes = match node {
+        HashTreeNode::Labeled(_, _) => "shape=box",
+        HashTreeNode::Pruned(_) => "shape=box, style=dashed",
+        _ => "shape=ellipse",
+    };
+    writeln!(
+        out,
+        "    {} [label={}, {}];",
+        id,
+        quoted(&node_text(node)),
+        attributes
+    )
+    .unwrap();
+    writeln!(out, "    {} -> {};", parent_id, id).unwrap();
+
+    for child in children(node) {
+        write_dot_node(out, child, &id, next_id);
+    }
+}
+
+fn write_json_nodes<Storage: AsRef<[u8]>>(
+    out: &mut String,
+    nodes: &[&HashTreeNode<Storage>],
+    indent: &str,
+) {
+    if nodes.is_empty() {
+        out.push_str("[]");
+        return;
+    }
+
+    let node_indent = format!("{}    ", indent);
+    out.push_str("[\n");
+    for (i, node) in nodes.iter().enumerate() {
+        out.push_str(&node_indent);
+        write_json_node(out, node, &node_indent);
+        if i < nodes.len() - 1 {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(indent);
+    out.push(']');
+}
+
+fn write_json_node<Storage: AsRef<[u8]>>(
+    out: &mut String,
+    node: &HashTreeNode<Storage>,
+    indent: &str,
+) {
+    let field_indent = format!("{}    ", indent);
+    let (node_type, field) = match node {
+        HashTreeNode::Labeled(label, _) => (
+            "labeled",
+            Some((
+                "label",
+                match printable(label.as_bytes()) {
+                    Some(label) => label.to_string(),
+                    None => format!("0x{}", hex::encode(label.as_bytes())),
+                },
+            )),
+        ),
+        HashTreeNode::Leaf(value) => (
+            "leaf",
+            Some((
+                "value",
+                match printable(value.as_ref()) {
+                    Some(value) => value.to_string(),
+                    None => leaf_text(value.as_ref()),
+                },
+            )),
+        ),
+        HashTreeNode::Pruned(digest) => ("pruned", Some(("digest", abbreviated_hex(digest)))),
+        HashTreeNode::Empty() => ("empty", None),
+        HashTreeNode::Fork(_) => ("fork", None),
+    };
+
+    out.push_str("{\n");
+    write!(out, "{}\"type\": {}", field_indent, quoted(node_type)).unwrap();
+    if let Some((name, value)) = field {
+        write!(out, ",\n{}\"{}\": {}", field_indent, name, quoted(&value)).unwrap();
+    }
+    if let HashTreeNode::Labeled(_, _) = node {
+        write!(out, ",\n{}\"children\": ", field_indent).unwrap();
+        write_json_nodes(out, &children(node), &field_indent);
+    }
+    write!(out, "\n{}}}", indent).unwrap();
+}
+
+/// Quotes and escapes a string, in a form that is valid in both DOT and JSON.
+fn quoted(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+
+    out
+}
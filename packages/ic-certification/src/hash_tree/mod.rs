@@ -4,6 +4,7 @@ use hex::FromHexError;
 use sha2::Digest;
 use std::{
     borrow::{Borrow, Cow},
+    collections::BTreeMap,
     fmt,
 };
 
@@ -168,6 +169,106 @@ pub enum SubtreeLookupResult<Storage: AsRef<[u8]>> {
     Found(HashTree<Storage>),
 }
 
+/// A change between two [HashTree]s, as reported by [HashTree::diff].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TreeChange<Storage: AsRef<[u8]>> {
+    /// The path is only present in the new tree.
+    Added {
+        /// The path that was added.
+        path: Vec<Label<Storage>>,
+    },
+
+    /// The path is only present in the old tree.
+    Removed {
+        /// The path that was removed.
+        path: Vec<Label<Storage>>,
+    },
+
+    /// The path is present in both trees, with a different leaf or with a leaf in one tree and
+    /// a subtree in the other.
+    Modified {
+        /// The path that was modified.
+        path: Vec<Label<Storage>>,
+    },
+
+    /// The subtree at the path differs between the trees, but one of them is pruned where they
+    /// differ, so the paths that changed below it are unknown.
+    Inconclusive {
+        /// The path of the subtree that differs.
+        path: Vec<Label<Storage>>,
+    },
+}
+
+/// An error returned when merging two [HashTree]s that are not partial views of the same tree.
+#[derive(thiserror::Error, Clone, PartialEq, Eq, Debug)]
+pub enum MergeError {
+    /// A pruned node does not have the same hash as the node at the same position in the other
+    /// tree.
+    #[error(
+        r#"Inconsistent hashes: "{}" and "{}""#,
+        hex::encode(left),
+        hex::encode(right)
+    )]
+    InconsistentHashes {
+        /// The hash of the node in the left tree.
+        left: Hash,
+        /// The hash of the node in the right tree.
+        right: Hash,
+    },
+
+    /// Two labeled nodes at the same position have different labels.
+    #[error(
+        r#"Inconsistent labels: "{}" and "{}""#,
+        hex::encode(left),
+        hex::encode(right)
+    )]
+    InconsistentLabels {
+        /// The label of the node in the left tree.
+        left: Vec<u8>,
+        /// The label of the node in the right tree.
+        right: Vec<u8>,
+    },
+
+    /// Two leaves at the same position have different values.
+    #[error(
+        r#"Inconsistent leaves: "{}" and "{}""#,
+        hex::encode(left),
+        hex::encode(right)
+    )]
+    InconsistentLeaves {
+        /// The value of the leaf in the left tree.
+        left: Vec<u8>,
+        /// The value of the leaf in the right tree.
+        right: Vec<u8>,
+    },
+
+    /// Two nodes at the same position are of different types, neither of which is pruned.
+    #[error("Inconsistent tree structure")]
+    InconsistentStructure,
+}
+
+/// An error returned when a [HashTree] is not well formed, as defined by the
+/// [spec](https://internetcomputer.org/docs/current/references/ic-interface-spec/#certificate).
+#[derive(thiserror::Error, Clone, PartialEq, Eq, Debug)]
+pub enum MalformedHashTreeError {
+    /// The labels of a subtree are not in strictly increasing order.
+    #[error(
+        r#"Label "{}" is not greater than the preceding label "{}""#,
+        hex::encode(label),
+        hex::encode(previous)
+    )]
+    LabelsNotStrictlyIncreasing {
+        /// The label that precedes the out of order label.
+        previous: Vec<u8>,
+        /// The out of order label.
+        label: Vec<u8>,
+    },
+
+    /// A fork contains a leaf, leaves are only allowed as the whole of a subtree.
+    #[error("Fork contains a leaf")]
+    LeafInFork,
+}
+
 /// A HashTree representing a full tree.
 #[derive(Clone, PartialEq, Eq)]
 pub struct HashTree<Storage: AsRef<[u8]>> {
@@ -217,6 +318,60 @@ impl<Storage: Clone + AsRef<[u8]>> HashTree<Storage> {
     pub fn list_paths(&self) -> Vec<Vec<Label<Storage>>> {
         self.root.list_paths(&vec![])
     }
+
+    /// Merges two partial views of the same tree, such as two witnesses, into a single tree that
+    /// reveals every node that is revealed by either of them.
+    ///
+    /// A pruned node is replaced by the node at the same position in the other tree, as long as
+    /// its hash matches. An error is returned if the trees are not partial views of the same tree.
+    pub fn merge(&self, other: &Self) -> Result<Self, MergeError> {
+        Ok(HashTree {
+            root: self.root.merge(&other.root)?,
+        })
+    }
+
+    /// Compares this tree with a newer version of it and reports the paths that were added,
+    /// removed or modified, in path order.
+    ///
+    /// Subtrees with the same digest are equal, whether they are pruned or not. Where one of the
+    /// trees is pruned and the digests differ, the change is reported as
+    /// [Inconclusive](TreeChange::Inconclusive).
+    pub fn diff(&self, other: &Self) -> Vec<TreeChange<Storage>> {
+        let mut changes = vec![];
+        self.root.diff(&other.root, &mut vec![], &mut changes);
+
+        changes
+    }
+
+    /// Checks that the tree is well formed, which [lookup_path](HashTree::lookup_path) and
+    /// [lookup_subtree](HashTree::lookup_subtree) rely on to prove the absence of a path.
+    ///
+    /// The labels below every node must be strictly increasing and forks may not contain
+    /// leaves.
+    pub fn validate_well_formed(&self) -> Result<(), MalformedHashTreeError> {
+        self.root.validate_well_formed()
+    }
+
+    /// Prunes the tree down to the provided paths, replacing every subtree that is not needed to
+    /// look up the paths with a pruned node, so the digest of the tree is unchanged.
+    ///
+    /// The subtree at each path that is present in the tree is kept in full. For paths that are
+    /// absent from the tree, the neighboring labels that prove their absence are kept instead.
+    pub fn prune_to<P, L>(&self, paths: &[P]) -> Self
+    where
+        P: AsRef<[L]>,
+        L: AsRef<[u8]>,
+    {
+        let paths: Vec<Vec<&[u8]>> = paths
+            .iter()
+            .map(|path| path.as_ref().iter().map(|label| label.as_ref()).collect())
+            .collect();
+        let paths: Vec<&[&[u8]]> = paths.iter().map(|path| path.as_slice()).collect();
+
+        HashTree {
+            root: self.root.prune_to(&paths),
+        }
+    }
 }
 
 impl<Storage: AsRef<[u8]>> AsRef<HashTreeNode<Storage>> for HashTree<Storage> {
@@ -308,6 +463,107 @@ enum LookupLabelResult<'node, Storage: AsRef<[u8]>> {
     Found(&'node HashTreeNode<Storage>),
 }
 
+/// Private type for how much of a node is kept when pruning a tree.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PruneReveal {
+    /// The node is pruned.
+    None,
+
+    /// The label of the node is kept to prove the absence of a neighboring label.
+    Neighbor,
+
+    /// The node is kept and its subtree is pruned to the remaining paths.
+    Full,
+}
+
+/// Private type for the nodes below the forks of a node, used to compare two trees.
+struct DiffLevel<'a, Storage: AsRef<[u8]>> {
+    /// The nodes below the forks, in order.
+    nodes: Vec<&'a HashTreeNode<Storage>>,
+
+    /// The digest of every subtree of the forks, with the range of nodes below it.
+    subtrees: Vec<(Hash, std::ops::Range<usize>)>,
+}
+
+impl<'a, Storage: AsRef<[u8]>> DiffLevel<'a, Storage> {
+    fn new(node: &'a HashTreeNode<Storage>) -> Self {
+        let mut level = DiffLevel {
+            nodes: vec![],
+            subtrees: vec![],
+        };
+        level.collect(node);
+
+        level
+    }
+
+    fn collect(&mut self, node: &'a HashTreeNode<Storage>) -> Hash {
+        let start = self.nodes.len();
+        let digest = match node {
+            HashTreeNode::Fork(children) => {
+                let left = self.collect(&children.0);
+                let right = self.collect(&children.1);
+
+                fork_hash(&left, &right)
+            }
+            _ => {
+                self.nodes.push(node);
+
+                node.digest()
+            }
+        };
+        self.subtrees.push((digest, start..self.nodes.len()));
+
+        digest
+    }
+
+    /// Matches the pruned nodes of this level against the subtrees of the other level. Returns
+    /// which nodes of this level are pruned nodes that matched, along with which nodes of the
+    /// other level are covered by a matching pruned node.
+    fn match_pruned(&self, other: &Self) -> (Vec<bool>, Vec<bool>) {
+        let mut matched = vec![false; self.nodes.len()];
+        let mut covered = vec![false; other.nodes.len()];
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let HashTreeNode::Pruned(digest) = node else {
+                continue;
+            };
+
+            if let Some((_, range)) = other.subtrees.iter().find(|(d, _)| d == digest) {
+                matched[i] = true;
+                covered[range.clone()].fill(true);
+            }
+        }
+
+        (matched, covered)
+    }
+
+    /// Returns true if a pruned node of this level neither matched a subtree of the other level
+    /// nor is covered by a matching pruned node of the other level.
+    fn has_unresolved_pruned(&self, matched: &[bool], covered: &[bool]) -> bool {
+        self.nodes
+            .iter()
+            .zip(matched.iter().zip(covered))
+            .any(|(node, (matched, covered))| {
+                matches!(node, HashTreeNode::Pruned(_)) && !matched && !covered
+            })
+    }
+
+    fn labeled_nodes(
+        &self,
+        covered: &'a [bool],
+    ) -> impl Iterator<Item = (&'a Label<Storage>, &'a HashTreeNode<Storage>)> + '_ {
+        self.nodes
+            .iter()
+            .zip(covered)
+            .filter_map(|(node, covered)| match node {
+                HashTreeNode::Labeled(label, subtree) if !covered => {
+                    Some((label, subtree.as_ref()))
+                }
+                _ => None,
+            })
+    }
+}
+
 /// A Node in the HashTree.
 #[derive(Clone, PartialEq, Eq)]
 pub enum HashTreeNode<Storage: AsRef<[u8]>> {
@@ -466,6 +722,9 @@ impl<Storage: AsRef<[u8]>> HashTreeNode<Storage> {
                 }
             }
             HashTreeNode::Pruned(_) => LookupLabelResult::Unknown,
+            // An empty node flattens to no labels at all, so the search continues with the
+            // siblings of this node.
+            HashTreeNode::Empty() => LookupLabelResult::Greater,
             // Any other type of node and we need to look for more forks.
             _ => LookupLabelResult::Absent,
         }
@@ -500,6 +759,18 @@ impl<Storage: AsRef<[u8]>> HashTreeNode<Storage> {
             (Some(LLR::Absent | LLR::Greater | LLR::Less), _) => Absent,
         }
     }
+
+    /// Collects the nodes below the forks of this node, in order. A node that is not a fork is
+    /// its own only fork leaf.
+    fn collect_fork_leaves<'a>(&'a self, nodes: &mut Vec<&'a Self>) {
+        match self {
+            HashTreeNode::Fork(children) => {
+                children.0.collect_fork_leaves(nodes);
+                children.1.collect_fork_leaves(nodes);
+            }
+            _ => nodes.push(self),
+        }
+    }
 }
 
 impl<Storage: Clone + AsRef<[u8]>> HashTreeNode<Storage> {
@@ -530,6 +801,266 @@ impl<Storage: Clone + AsRef<[u8]>> HashTreeNode<Storage> {
         }
     }
 
+    fn merge(&self, other: &Self) -> Result<Self, MergeError> {
+        use HashTreeNode::*;
+
+        match (self, other) {
+            (Pruned(l), Pruned(r)) => {
+                if l != r {
+                    return Err(MergeError::InconsistentHashes {
+                        left: *l,
+                        right: *r,
+                    });
+                }
+
+                Ok(Pruned(*l))
+            }
+            (Pruned(l), r) => {
+                let r_hash = r.digest();
+                if *l != r_hash {
+                    return Err(MergeError::InconsistentHashes {
+                        left: *l,
+                        right: r_hash,
+                    });
+                }
+
+                Ok(r.to_owned())
+            }
+            (l, Pruned(r)) => {
+                let l_hash = l.digest();
+                if l_hash != *r {
+                    return Err(MergeError::InconsistentHashes {
+                        left: l_hash,
+                        right: *r,
+                    });
+                }
+
+                Ok(l.to_owned())
+            }
+            (Fork(l), Fork(r)) => Ok(Fork(Box::new((l.0.merge(&r.0)?, l.1.merge(&r.1)?)))),
+            (Labeled(l_label, l), Labeled(r_label, r)) => {
+                if l_label.as_bytes() != r_label.as_bytes() {
+                    return Err(MergeError::InconsistentLabels {
+                        left: l_label.as_bytes().to_vec(),
+                        right: r_label.as_bytes().to_vec(),
+                    });
+                }
+
+                Ok(Labeled(l_label.clone(), Box::new(l.merge(r)?)))
+            }
+            (Leaf(l), Leaf(r)) => {
+                if l.as_ref() != r.as_ref() {
+                    return Err(MergeError::InconsistentLeaves {
+                        left: l.as_ref().to_vec(),
+                        right: r.as_ref().to_vec(),
+                    });
+                }
+
+                Ok(Leaf(l.clone()))
+            }
+            (Empty(), Empty()) => Ok(Empty()),
+            _ => Err(MergeError::InconsistentStructure),
+        }
+    }
+
+    fn prune_to(&self, paths: &[&[&[u8]]]) -> Self {
+        let mut child_paths: BTreeMap<&[u8], Vec<&[&[u8]]>> = BTreeMap::new();
+        for path in paths {
+            let Some((label, rest)) = path.split_first() else {
+                return self.to_owned();
+            };
+
+            child_paths.entry(*label).or_default().push(rest);
+        }
+
+        // the labeled nodes below a node are the leaves of its forks, in order
+        let mut nodes = vec![];
+        self.collect_fork_leaves(&mut nodes);
+
+        let labels: Vec<(usize, &[u8])> = nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, node)| match node {
+                HashTreeNode::Labeled(label, _) => Some((i, label.as_bytes())),
+                _ => None,
+            })
+            .collect();
+
+        let mut reveals = vec![PruneReveal::None; nodes.len()];
+        for label in child_paths.keys() {
+            match labels.binary_search_by(|(_, l)| l.cmp(label)) {
+                Ok(pos) => reveals[labels[pos].0] = PruneReveal::Full,
+                Err(pos) => {
+                    // keep the labels on either side of the absent label, along with
+                    // everything between them, to prove its absence
+                    let first = pos.checked_sub(1).map_or(0, |pos| labels[pos].0);
+                    let last = labels.get(pos).map_or(nodes.len() - 1, |(i, _)| *i);
+
+                    for reveal in &mut reveals[first..=last] {
+                        if *reveal == PruneReveal::None {
+                            *reveal = PruneReveal::Neighbor;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.prune_fork_leaves(&reveals, &child_paths, &mut 0)
+    }
+
+    fn diff(
+        &self,
+        other: &Self,
+        path: &mut Vec<Label<Storage>>,
+        changes: &mut Vec<TreeChange<Storage>>,
+    ) {
+        use HashTreeNode::*;
+
+        if self.digest() == other.digest() {
+            return;
+        }
+
+        match (self, other) {
+            (Pruned(_), _) | (_, Pruned(_)) => {
+                changes.push(TreeChange::Inconclusive { path: path.clone() })
+            }
+            (Leaf(_), _) | (_, Leaf(_)) => {
+                changes.push(TreeChange::Modified { path: path.clone() })
+            }
+            _ => self.diff_labels(other, path, changes),
+        }
+    }
+
+    fn diff_labels(
+        &self,
+        other: &Self,
+        path: &mut Vec<Label<Storage>>,
+        changes: &mut Vec<TreeChange<Storage>>,
+    ) {
+        use std::cmp::Ordering;
+
+        let old = DiffLevel::new(self);
+        let new = DiffLevel::new(other);
+
+        // a pruned node that matches a subtree of the other tree proves that the labels in the
+        // subtree are unchanged, labels that are hidden in any other pruned node may have changed
+        let (old_matched, new_covered) = old.match_pruned(&new);
+        let (new_matched, old_covered) = new.match_pruned(&old);
+        let old_unresolved = old.has_unresolved_pruned(&old_matched, &old_covered);
+        let new_unresolved = new.has_unresolved_pruned(&new_matched, &new_covered);
+        if old_unresolved || new_unresolved {
+            changes.push(TreeChange::Inconclusive { path: path.clone() });
+        }
+
+        let mut old_labeled = old.labeled_nodes(&old_covered).peekable();
+        let mut new_labeled = new.labeled_nodes(&new_covered).peekable();
+        loop {
+            let ordering = match (old_labeled.peek(), new_labeled.peek()) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((old_label, _)), Some((new_label, _))) => {
+                    old_label.as_bytes().cmp(new_label.as_bytes())
+                }
+            };
+
+            match ordering {
+                Ordering::Less => {
+                    let (label, _) = old_labeled.next().unwrap();
+                    path.push(label.clone());
+                    changes.push(if new_unresolved {
+                        TreeChange::Inconclusive { path: path.clone() }
+                    } else {
+                        TreeChange::Removed { path: path.clone() }
+                    });
+                    path.pop();
+                }
+                Ordering::Greater => {
+                    let (label, _) = new_labeled.next().unwrap();
+                    path.push(label.clone());
+                    changes.push(if old_unresolved {
+                        TreeChange::Inconclusive { path: path.clone() }
+                    } else {
+                        TreeChange::Added { path: path.clone() }
+                    });
+                    path.pop();
+                }
+                Ordering::Equal => {
+                    let (label, old_subtree) = old_labeled.next().unwrap();
+                    let (_, new_subtree) = new_labeled.next().unwrap();
+                    path.push(label.clone());
+                    old_subtree.diff(new_subtree, path, changes);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    fn validate_well_formed(&self) -> Result<(), MalformedHashTreeError> {
+        if let HashTreeNode::Leaf(_) = self {
+            return Ok(());
+        }
+
+        let mut nodes = vec![];
+        self.collect_fork_leaves(&mut nodes);
+
+        let mut previous_label: Option<&[u8]> = None;
+        for node in nodes {
+            match node {
+                HashTreeNode::Labeled(label, subtree) => {
+                    let label = label.as_bytes();
+                    if let Some(previous) = previous_label.filter(|previous| *previous >= label) {
+                        return Err(MalformedHashTreeError::LabelsNotStrictlyIncreasing {
+                            previous: previous.to_vec(),
+                            label: label.to_vec(),
+                        });
+                    }
+                    previous_label = Some(label);
+
+                    subtree.validate_well_formed()?;
+                }
+                HashTreeNode::Leaf(_) => return Err(MalformedHashTreeError::LeafInFork),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn prune_fork_leaves(
+        &self,
+        reveals: &[PruneReveal],
+        child_paths: &BTreeMap<&[u8], Vec<&[&[u8]]>>,
+        index: &mut usize,
+    ) -> Self {
+        if let HashTreeNode::Fork(children) = self {
+            let left = children.0.prune_fork_leaves(reveals, child_paths, index);
+            let right = children.1.prune_fork_leaves(reveals, child_paths, index);
+
+            return match (left, right) {
+                (HashTreeNode::Pruned(l), HashTreeNode::Pruned(r)) => {
+                    HashTreeNode::Pruned(fork_hash(&l, &r))
+                }
+                (left, right) => HashTreeNode::Fork(Box::new((left, right))),
+            };
+        }
+
+        let reveal = reveals[*index];
+        *index += 1;
+
+        match (reveal, self) {
+            (PruneReveal::None, _) => HashTreeNode::Pruned(self.digest()),
+            (PruneReveal::Full, HashTreeNode::Labeled(label, node)) => HashTreeNode::Labeled(
+                label.clone(),
+                Box::new(node.prune_to(&child_paths[label.as_bytes()])),
+            ),
+            (PruneReveal::Neighbor, HashTreeNode::Labeled(label, node)) => {
+                HashTreeNode::Labeled(label.clone(), Box::new(HashTreeNode::Pruned(node.digest())))
+            }
+            _ => self.to_owned(),
+        }
+    }
+
     fn list_paths(&self, path: &Vec<Label<Storage>>) -> Vec<Vec<Label<Storage>>> {
         match self {
             HashTreeNode::Empty() => vec![],
@@ -812,5 +1343,7 @@ fn domain_sep(s: &str) -> sha2::Sha256 {
     h
 }
 
+mod export;
+
 #[cfg(test)]
 mod tests;
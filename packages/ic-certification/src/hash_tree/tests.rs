@@ -1,7 +1,8 @@
 #![cfg(test)]
 
 use crate::hash_tree::{
-    empty, fork, label, leaf, pruned, pruned_from_hex, HashTree, LookupResult, SubtreeLookupResult,
+    empty, fork, label, leaf, pruned, pruned_from_hex, HashTree, Label, LookupResult,
+    MalformedHashTreeError, MergeError, SubtreeLookupResult, TreeChange,
 };
 
 fn lookup_path<P: AsRef<[&'static str]>>(tree: &HashTree<Vec<u8>>, path: P) -> LookupResult {
@@ -299,6 +300,23 @@ fn can_lookup_paths_8() {
     assert_eq!(tree.lookup_path([b"label 6"]), LookupResult::Unknown);
 }
 
+#[test]
+fn can_lookup_paths_through_empty_nodes() {
+    let tree: HashTree<Vec<u8>> = fork(
+        fork(empty(), label("label 3", leaf(vec![1, 2, 3, 4, 5, 6]))),
+        fork(empty(), fork(label("label 5", empty()), empty())),
+    );
+
+    assert_eq!(tree.lookup_path([b"label 2"]), LookupResult::Absent);
+    assert_eq!(
+        tree.lookup_path([b"label 3"]),
+        LookupResult::Found(&[1, 2, 3, 4, 5, 6])
+    );
+    assert_eq!(tree.lookup_path([b"label 4"]), LookupResult::Absent);
+    assert_eq!(tree.lookup_path([b"label 5"]), LookupResult::Absent);
+    assert_eq!(tree.lookup_path([b"label 6"]), LookupResult::Absent);
+}
+
 #[test]
 fn can_lookup_subtrees_1() {
     use SubtreeLookupResult::*;
@@ -506,3 +524,465 @@ fn can_lookup_subtrees_8() {
     assert_eq!(lookup_subtree(&tree, ["label 5"]), Found(empty()));
     assert_eq!(lookup_subtree(&tree, ["label 6"]), Unknown);
 }
+
+fn merge_test_tree() -> HashTree<Vec<u8>> {
+    fork(
+        fork(label("a", leaf(*b"hello")), label("b", leaf(*b"good"))),
+        fork(label("c", empty()), label("d", leaf(*b"morning"))),
+    )
+}
+
+#[test]
+fn merge_reveals_nodes_of_both_trees() {
+    let tree = merge_test_tree();
+    let lhs: HashTree<Vec<u8>> = fork(
+        fork(
+            label("a", leaf(*b"hello")),
+            pruned(label::<Vec<u8>, _, _>("b", leaf(*b"good")).digest()),
+        ),
+        pruned(fork::<Vec<u8>>(label("c", empty()), label("d", leaf(*b"morning"))).digest()),
+    );
+    let rhs: HashTree<Vec<u8>> = fork(
+        pruned(fork::<Vec<u8>>(label("a", leaf(*b"hello")), label("b", leaf(*b"good"))).digest()),
+        fork(
+            pruned(label::<Vec<u8>, _, _>("c", empty()).digest()),
+            label("d", leaf(*b"morning")),
+        ),
+    );
+
+    let merged = lhs.merge(&rhs).unwrap();
+
+    assert_eq!(merged.digest(), tree.digest());
+    assert_eq!(rhs.merge(&lhs).unwrap(), merged);
+    assert_eq!(lookup_path(&merged, ["a"]), LookupResult::Found(b"hello"));
+    assert_eq!(lookup_path(&merged, ["b"]), LookupResult::Unknown);
+    assert_eq!(lookup_path(&merged, ["d"]), LookupResult::Found(b"morning"));
+    assert_eq!(merged.merge(&tree).unwrap(), tree);
+}
+
+#[test]
+fn merge_rejects_inconsistent_trees() {
+    let tree = merge_test_tree();
+
+    assert!(matches!(
+        tree.merge(&pruned([0; 32])),
+        Err(MergeError::InconsistentHashes { right, .. }) if right == [0; 32]
+    ));
+    assert!(matches!(
+        pruned::<Vec<u8>, _>([0; 32]).merge(&pruned([1; 32])),
+        Err(MergeError::InconsistentHashes { .. })
+    ));
+    assert_eq!(
+        label::<Vec<u8>, _, _>("a", empty()).merge(&label("b", empty())),
+        Err(MergeError::InconsistentLabels {
+            left: b"a".to_vec(),
+            right: b"b".to_vec(),
+        })
+    );
+    assert_eq!(
+        leaf::<Vec<u8>, _>(*b"hello").merge(&leaf(*b"world")),
+        Err(MergeError::InconsistentLeaves {
+            left: b"hello".to_vec(),
+            right: b"world".to_vec(),
+        })
+    );
+    assert_eq!(
+        tree.merge(&label("a", leaf(*b"hello"))),
+        Err(MergeError::InconsistentStructure)
+    );
+}
+
+fn prune_test_tree() -> HashTree<Vec<u8>> {
+    fork(
+        fork(
+            label(
+                "a",
+                fork(
+                    fork(label("x", leaf(*b"hello")), empty()),
+                    label("y", leaf(*b"world")),
+                ),
+            ),
+            label("b", leaf(*b"good")),
+        ),
+        fork(
+            fork(label("c", empty()), label("d", leaf(*b"morning"))),
+            label("f", leaf(*b"evening")),
+        ),
+    )
+}
+
+#[test]
+fn prune_to_keeps_requested_paths() {
+    let tree = prune_test_tree();
+
+    let pruned_tree = tree.prune_to(&[["a", "x"], ["c", "z"]]);
+
+    assert_eq!(pruned_tree.digest(), tree.digest());
+    assert_eq!(
+        lookup_path(&pruned_tree, ["a", "x"]),
+        LookupResult::Found(b"hello")
+    );
+    assert_eq!(lookup_path(&pruned_tree, ["a", "y"]), LookupResult::Unknown);
+    assert_eq!(lookup_path(&pruned_tree, ["b"]), LookupResult::Unknown);
+    assert_eq!(lookup_path(&pruned_tree, ["c", "z"]), LookupResult::Absent);
+    assert_eq!(lookup_path(&pruned_tree, ["d"]), LookupResult::Unknown);
+    assert_eq!(lookup_path(&pruned_tree, ["f"]), LookupResult::Unknown);
+
+    let pruned_tree = tree.prune_to(&[vec!["a"], vec!["f"]]);
+
+    assert_eq!(pruned_tree.digest(), tree.digest());
+    assert_eq!(
+        lookup_subtree(&pruned_tree, ["a"]),
+        lookup_subtree(&tree, ["a"])
+    );
+    assert_eq!(
+        lookup_path(&pruned_tree, ["f"]),
+        LookupResult::Found(b"evening")
+    );
+    assert_eq!(lookup_path(&pruned_tree, ["b"]), LookupResult::Unknown);
+}
+
+#[test]
+fn prune_to_proves_absence_of_missing_paths() {
+    let tree = prune_test_tree();
+
+    for path in ["0", "aa", "e", "g"] {
+        let pruned_tree = tree.prune_to(&[[path]]);
+
+        assert_eq!(pruned_tree.digest(), tree.digest(), "path: {}", path);
+        assert_eq!(
+            lookup_path(&pruned_tree, [path]),
+            LookupResult::Absent,
+            "path: {}",
+            path
+        );
+    }
+
+    let pruned_tree = tree.prune_to(&[["e"]]);
+
+    assert_eq!(
+        pruned_tree,
+        fork(
+            pruned(
+                fork::<Vec<u8>>(
+                    label(
+                        "a",
+                        fork(
+                            fork(label("x", leaf(*b"hello")), empty()),
+                            label("y", leaf(*b"world")),
+                        ),
+                    ),
+                    label("b", leaf(*b"good")),
+                )
+                .digest()
+            ),
+            fork(
+                fork(
+                    pruned(label::<Vec<u8>, _, _>("c", empty()).digest()),
+                    label("d", pruned(leaf::<Vec<u8>, _>(*b"morning").digest())),
+                ),
+                label("f", pruned(leaf::<Vec<u8>, _>(*b"evening").digest())),
+            ),
+        )
+    );
+}
+
+#[test]
+fn prune_to_without_paths() {
+    let tree = prune_test_tree();
+
+    assert_eq!(tree.prune_to::<[&str; 0], &str>(&[]), pruned(tree.digest()));
+    assert_eq!(tree.prune_to(&[[""; 0]]), tree);
+}
+
+#[test]
+fn well_formed_trees_are_valid() {
+    let trees: [HashTree<Vec<u8>>; 8] = [
+        empty(),
+        leaf(*b"hello"),
+        pruned([0; 32]),
+        label("a", empty()),
+        fork(label("x", leaf(*b"hello")), empty()),
+        fork(empty(), label("a", empty())),
+        prune_test_tree().prune_to(&[vec!["a", "x"], vec!["e"]]),
+        fork(
+            fork(label("a", leaf(*b"hello")), pruned([0; 32])),
+            label("b", fork(label("x", empty()), label("y", pruned([1; 32])))),
+        ),
+    ];
+
+    for tree in trees {
+        assert_eq!(tree.validate_well_formed(), Ok(()), "tree: {:?}", tree);
+    }
+}
+
+#[test]
+fn malformed_trees_are_invalid() {
+    assert_eq!(
+        fork::<Vec<u8>>(label("b", empty()), label("a", empty())).validate_well_formed(),
+        Err(MalformedHashTreeError::LabelsNotStrictlyIncreasing {
+            previous: b"b".to_vec(),
+            label: b"a".to_vec(),
+        })
+    );
+    assert_eq!(
+        fork::<Vec<u8>>(label("a", empty()), label("a", empty())).validate_well_formed(),
+        Err(MalformedHashTreeError::LabelsNotStrictlyIncreasing {
+            previous: b"a".to_vec(),
+            label: b"a".to_vec(),
+        })
+    );
+    assert_eq!(
+        label::<Vec<u8>, _, _>(
+            "a",
+            fork(
+                fork(label("x", empty()), pruned([0; 32])),
+                label("w", empty())
+            )
+        )
+        .validate_well_formed(),
+        Err(MalformedHashTreeError::LabelsNotStrictlyIncreasing {
+            previous: b"x".to_vec(),
+            label: b"w".to_vec(),
+        })
+    );
+    assert_eq!(
+        fork::<Vec<u8>>(label("a", empty()), leaf(*b"hello")).validate_well_formed(),
+        Err(MalformedHashTreeError::LeafInFork)
+    );
+}
+
+fn export_test_tree() -> HashTree<Vec<u8>> {
+    fork(
+        label(
+            "a",
+            fork(
+                label("x", leaf(*b"hello")),
+                pruned_from_hex("1b4feff9bef8131788b0c9dc6dbad6e81e524249c879e9f10f71ce3749f5a638")
+                    .unwrap(),
+            ),
+        ),
+        fork(
+            label([0xff, 0x00], leaf([0xde, 0xad, 0xbe, 0xef])),
+            label("c \"d\"", empty()),
+        ),
+    )
+}
+
+#[test]
+fn export_to_ascii_tree() {
+    assert_eq!(
+        export_test_tree().to_ascii_tree(),
+        r#""a"
+|-- "x"
+|   `-- leaf("hello")
+`-- pruned(1b4feff9...)
+0xff00
+`-- leaf(0xdeadbeef)
+"c \"d\""
+`-- empty
+"#
+    );
+}
+
+#[test]
+fn export_to_dot() {
+    assert_eq!(
+        export_test_tree().to_dot(),
+        r#"digraph HashTree {
+    root [label="", shape=point];
+    n0 [label="\"a\"", shape=box];
+    root -> n0;
+    n1 [label="\"x\"", shape=box];
+    n0 -> n1;
+    n2 [label="leaf(\"hello\")", shape=ellipse];
+    n1 -> n2;
+    n3 [label="pruned(1b4feff9...)", shape=box, style=dashed];
+    n0 -> n3;
+    n4 [label="0xff00", shape=box];
+    root -> n4;
+    n5 [label="leaf(0xdeadbeef)", shape=ellipse];
+    n4 -> n5;
+    n6 [label="\"c \\\"d\\\"\"", shape=box];
+    root -> n6;
+    n7 [label="empty", shape=ellipse];
+    n6 -> n7;
+}
+"#
+    );
+}
+
+#[test]
+fn export_to_json() {
+    assert_eq!(
+        export_test_tree().to_json(),
+        r#"[
+    {
+        "type": "labeled",
+        "label": "a",
+        "children": [
+            {
+                "type": "labeled",
+                "label": "x",
+                "children": [
+                    {
+                        "type": "leaf",
+                        "value": "hello"
+                    }
+                ]
+            },
+            {
+                "type": "pruned",
+                "digest": "1b4feff9..."
+            }
+        ]
+    },
+    {
+        "type": "labeled",
+        "label": "0xff00",
+        "children": [
+            {
+                "type": "leaf",
+                "value": "0xdeadbeef"
+            }
+        ]
+    },
+    {
+        "type": "labeled",
+        "label": "c \"d\"",
+        "children": [
+            {
+                "type": "empty"
+            }
+        ]
+    }
+]
+"#
+    );
+    assert_eq!(
+        empty::<Vec<u8>>().to_json(),
+        "[\n    {\n        \"type\": \"empty\"\n    }\n]\n"
+    );
+}
+
+fn diff_test_tree(b: &str, extra_label: Option<&str>) -> HashTree<Vec<u8>> {
+    let c = match extra_label {
+        Some(extra_label) => fork(label("c", empty()), label(extra_label, leaf(*b"extra"))),
+        None => label("c", empty()),
+    };
+
+    fork(
+        fork(
+            label(
+                "a",
+                fork(label("x", leaf(*b"hello")), label("y", leaf(*b"world"))),
+            ),
+            label("b", leaf(b.as_bytes())),
+        ),
+        c,
+    )
+}
+
+fn labels(path: &[&str]) -> Vec<Label<Vec<u8>>> {
+    path.iter().map(|segment| Label::from(*segment)).collect()
+}
+
+#[test]
+fn diff_reports_changed_paths() {
+    let old_tree = diff_test_tree("good", Some("d"));
+    let new_tree = diff_test_tree("bad", Some("e"));
+
+    assert_eq!(old_tree.diff(&old_tree), vec![]);
+    assert_eq!(
+        old_tree.diff(&new_tree),
+        vec![
+            TreeChange::Modified {
+                path: labels(&["b"])
+            },
+            TreeChange::Removed {
+                path: labels(&["d"])
+            },
+            TreeChange::Added {
+                path: labels(&["e"])
+            },
+        ]
+    );
+    assert_eq!(
+        old_tree.diff(&label("a", leaf(*b"hello"))),
+        vec![
+            TreeChange::Modified {
+                path: labels(&["a"])
+            },
+            TreeChange::Removed {
+                path: labels(&["b"])
+            },
+            TreeChange::Removed {
+                path: labels(&["c"])
+            },
+            TreeChange::Removed {
+                path: labels(&["d"])
+            },
+        ]
+    );
+}
+
+#[test]
+fn diff_treats_pruned_subtrees_with_matching_digests_as_equal() {
+    let tree = diff_test_tree("good", None);
+    let witness = tree.prune_to(&[["a", "x"]]);
+
+    assert_eq!(tree.diff(&witness), vec![]);
+    assert_eq!(witness.diff(&tree), vec![]);
+    assert_eq!(witness.diff(&tree.prune_to(&[["c"]])), vec![]);
+    assert_eq!(tree.diff(&pruned(tree.digest())), vec![]);
+}
+
+#[test]
+fn diff_reports_inconclusive_changes_below_pruned_subtrees() {
+    let old_tree = diff_test_tree("good", None);
+    let new_tree = diff_test_tree("bad", None);
+
+    assert_eq!(
+        old_tree.diff(&pruned(new_tree.digest())),
+        vec![TreeChange::Inconclusive { path: vec![] }]
+    );
+    assert_eq!(
+        old_tree
+            .prune_to(&[["a"]])
+            .diff(&new_tree.prune_to(&[["a"]])),
+        vec![TreeChange::Inconclusive { path: vec![] }]
+    );
+    assert_eq!(
+        old_tree.prune_to(&[["b"]]).diff(&new_tree),
+        vec![TreeChange::Modified {
+            path: labels(&["b"])
+        }]
+    );
+    assert_eq!(
+        old_tree
+            .prune_to(&[["a"]])
+            .diff(&diff_test_tree("bad", Some("d")).prune_to(&[["a"]])),
+        vec![TreeChange::Inconclusive { path: vec![] }]
+    );
+}
+
+#[test]
+fn diff_matches_pruned_subtrees_against_the_other_tree() {
+    let old_tree = diff_test_tree("good", None);
+    let new_tree = diff_test_tree("good", Some("d"));
+
+    // the pruned nodes of the witness match the "b" and "c" subtrees of the new tree, so the
+    // witness proves that "d" was not in the old tree
+    assert_eq!(
+        old_tree.prune_to(&[["a"]]).diff(&new_tree),
+        vec![TreeChange::Added {
+            path: labels(&["d"])
+        }]
+    );
+    assert_eq!(
+        new_tree.diff(&old_tree.prune_to(&[["a"]])),
+        vec![TreeChange::Removed {
+            path: labels(&["d"])
+        }]
+    );
+}
@@ -23,6 +23,8 @@ pub type HashTreeNode = hash_tree::HashTreeNode<Vec<u8>>;
 pub type Label = hash_tree::Label<Vec<u8>>;
 /// A result of looking up for a subtree.
 pub type SubtreeLookupResult = hash_tree::SubtreeLookupResult<Vec<u8>>;
+/// A change between two [`HashTree`]s.
+pub type TreeChange = hash_tree::TreeChange<Vec<u8>>;
 
 /// A `Delegation` as defined in <https://internetcomputer.org/docs/current/references/ic-interface-spec/#certification-delegation>
 pub type Delegation = certificate::Delegation<Vec<u8>>;
@@ -1,4 +1,5 @@
-use crate::{empty, fork, labeled, leaf, pruned, AsHashTree, Hash, HashTree, HashTreeNode, RbTree};
+use crate::{AsHashTree, Hash, HashTree, RbTree};
+use std::collections::BTreeMap;
 
 pub trait NestedTreeKeyRequirements: Clone + AsRef<[u8]> + 'static {}
 pub trait NestedTreeValueRequirements: AsHashTree + 'static {}
@@ -106,7 +107,17 @@ impl<K: NestedTreeKeyRequirements, V: NestedTreeValueRequirements> NestedTree<K,
             match self {
                 NestedTree::Leaf(_) => {}
                 NestedTree::Nested(tree) => {
-                    tree.modify(key.as_ref(), |child| child.delete(&path[1..]));
+                    let mut child_is_empty = false;
+                    tree.modify(key.as_ref(), |child| {
+                        child.delete(&path[1..]);
+                        child_is_empty = child.is_empty();
+                    });
+
+                    // remove subtrees that no longer contain anything, so the tree
+                    // hashes the same as a tree that never contained the deleted path
+                    if child_is_empty {
+                        tree.delete(key.as_ref());
+                    }
                 }
             }
         } else {
@@ -114,6 +125,10 @@ impl<K: NestedTreeKeyRequirements, V: NestedTreeValueRequirements> NestedTree<K,
         }
     }
 
+    fn is_empty(&self) -> bool {
+        matches!(self, NestedTree::Nested(tree) if tree.is_empty())
+    }
+
     pub fn witness(&self, path: &[K]) -> HashTree {
         if let Some(key) = path.get(0) {
             match self {
@@ -126,44 +141,49 @@ impl<K: NestedTreeKeyRequirements, V: NestedTreeValueRequirements> NestedTree<K,
             self.as_hash_tree()
         }
     }
-}
 
-pub fn merge_hash_trees(lhs: HashTree, rhs: HashTree) -> HashTree {
-    match (lhs.root, rhs.root) {
-        (HashTreeNode::Pruned(l), HashTreeNode::Pruned(r)) => {
-            if l != r {
-                panic!("merge_hash_trees: inconsistent hashes");
-            }
-            pruned(l)
-        }
-        (HashTreeNode::Pruned(_), r) => HashTree { root: r },
-        (l, HashTreeNode::Pruned(_)) => HashTree { root: l },
-        (HashTreeNode::Fork(l), HashTreeNode::Fork(r)) => fork(
-            merge_hash_trees(HashTree { root: l.0 }, HashTree { root: r.0 }),
-            merge_hash_trees(HashTree { root: l.1 }, HashTree { root: r.1 }),
-        ),
-        (HashTreeNode::Labeled(l_label, l), HashTreeNode::Labeled(r_label, r)) => {
-            if l_label != r_label {
-                panic!("merge_hash_trees: inconsistent hash tree labels");
-            }
-            labeled(
-                l_label,
-                merge_hash_trees(HashTree { root: *l }, HashTree { root: *r }),
-            )
+    /// Constructs a single witness for all of the provided paths in one traversal of the tree.
+    ///
+    /// The resulting tree is equivalent to merging the [witness](NestedTree::witness) of each
+    /// path, but avoids building and merging the intermediate witnesses.
+    pub fn witness_many<P: AsRef<[K]>>(&self, paths: &[P]) -> HashTree {
+        let paths: Vec<&[K]> = paths.iter().map(|path| path.as_ref()).collect();
+
+        self.witness_paths(&paths)
+    }
+
+    fn witness_paths(&self, paths: &[&[K]]) -> HashTree {
+        let mut child_paths: BTreeMap<&[u8], Vec<&[K]>> = BTreeMap::new();
+        for path in paths {
+            let Some((key, rest)) = path.split_first() else {
+                return self.as_hash_tree();
+            };
+
+            child_paths.entry(key.as_ref()).or_default().push(rest);
         }
-        (HashTreeNode::Empty(), HashTreeNode::Empty()) => empty(),
-        (HashTreeNode::Leaf(l), HashTreeNode::Leaf(r)) => {
-            if l != r {
-                panic!("merge_hash_trees: inconsistent leaves");
+
+        match self {
+            NestedTree::Leaf(value) => value.as_hash_tree(),
+            NestedTree::Nested(tree) => {
+                let keys: Vec<&[u8]> = child_paths.keys().copied().collect();
+
+                tree.nested_witness_many(&keys, |key, child| child.witness_paths(&child_paths[key]))
             }
-            leaf(l)
-        }
-        (_l, _r) => {
-            panic!("merge_hash_trees: inconsistent tree structure");
         }
     }
 }
 
+/// Merges two partial views of the same tree.
+///
+/// # Panics
+///
+/// Panics if the trees are not partial views of the same tree, use [HashTree::merge] to handle
+/// this case instead.
+pub fn merge_hash_trees(lhs: HashTree, rhs: HashTree) -> HashTree {
+    lhs.merge(&rhs)
+        .unwrap_or_else(|err| panic!("merge_hash_trees: {}", err))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,4 +222,56 @@ mod tests {
         assert!(!tree.contains_leaf(&["one", "two"]));
         assert!(!tree.contains_leaf(&["one"]));
     }
+
+    #[test]
+    fn nested_tree_delete_prunes_empty_subtrees() {
+        let mut tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        tree.insert(&["one", "two"], vec![2]);
+        let expected_root_hash = tree.root_hash();
+
+        tree.insert(&["one", "three", "four"], vec![4]);
+        tree.insert(&["five", "six"], vec![6]);
+        assert_ne!(tree.root_hash(), expected_root_hash);
+
+        tree.delete(&["one", "three", "four"]);
+        tree.delete(&["five", "six"]);
+        assert!(!tree.contains_path(&["one", "three"]));
+        assert!(!tree.contains_path(&["five"]));
+        assert_eq!(tree.root_hash(), expected_root_hash);
+
+        tree.delete(&["one", "two"]);
+        assert!(!tree.contains_path(&["one"]));
+        assert_eq!(
+            tree.root_hash(),
+            NestedTree::<&str, Vec<u8>>::default().root_hash()
+        );
+    }
+
+    #[test]
+    fn nested_tree_witness_many() {
+        let mut tree: NestedTree<&str, Vec<u8>> = NestedTree::default();
+        tree.insert(&["one", "two"], vec![2]);
+        tree.insert(&["one", "three"], vec![3]);
+        tree.insert(&["four", "five", "six"], vec![6]);
+        tree.insert(&["seven"], vec![7]);
+
+        let path_sets: [&[&[&str]]; 4] = [
+            &[&["one", "two"]],
+            &[&["one", "two"], &["one", "three"]],
+            &[&["one", "four"], &["four"], &["eight"]],
+            &[&["seven", "eight"], &["one"], &["four", "five", "six"], &[]],
+        ];
+        for paths in path_sets {
+            let witness = tree.witness_many(paths);
+
+            let merged = paths
+                .iter()
+                .map(|path| tree.witness(path))
+                .reduce(|lhs, rhs| lhs.merge(&rhs).unwrap())
+                .unwrap();
+
+            assert_eq!(witness.digest(), tree.root_hash(), "paths: {:?}", paths);
+            assert_eq!(witness, merged, "paths: {:?}", paths);
+        }
+    }
 }
@@ -435,6 +435,75 @@ impl<K: 'static + AsRef<[u8]>, V: AsHashTree + 'static> RbTree<K, V> {
         )
     }
 
+    /// Constructs a single hash tree that acts as a proof of the
+    /// presence or absence of each of the specified keys, in one
+    /// traversal of the map.
+    ///
+    /// The resulting tree is equivalent to merging the `witness` of
+    /// each key.
+    pub fn witness_many<Q: AsRef<[u8]>>(&self, keys: &[Q]) -> HashTree {
+        self.nested_witness_many(keys, |_, v| v.as_hash_tree())
+    }
+
+    /// Like `witness_many`, but gives the caller more control over the
+    /// construction of the value witnesses.  The function is called with
+    /// the key and value of each entry that is present in the map.
+    pub fn nested_witness_many<'a, Q: AsRef<[u8]>>(
+        &'a self,
+        keys: &[Q],
+        mut f: impl FnMut(&'a [u8], &'a V) -> HashTree,
+    ) -> HashTree {
+        // Returns the witness of the subtree, along with whether the
+        // smallest and largest keys still need a neighbor outside of
+        // the subtree to prove their absence.
+        fn go<'a, K: 'static + AsRef<[u8]>, V: AsHashTree + 'static>(
+            n: &'a NodeRef<K, V>,
+            keys: &[&[u8]],
+            f: &mut impl FnMut(&'a [u8], &'a V) -> HashTree,
+        ) -> (HashTree, bool, bool) {
+            let n = match n {
+                None => return (empty(), !keys.is_empty(), !keys.is_empty()),
+                Some(n) if keys.is_empty() => return (pruned(n.subtree_hash), false, false),
+                Some(n) => n,
+            };
+
+            let k = n.key.as_ref();
+            let left_len = keys.partition_point(|key| *key < k);
+            let right_start = keys.partition_point(|key| *key <= k);
+            let (left_keys, right_keys) = (&keys[..left_len], &keys[right_start..]);
+
+            let (left, left_needs_lower, left_needs_upper) = if left_keys.is_empty() {
+                (n.left_hash_tree(), false, false)
+            } else {
+                go(&n.left, left_keys, f)
+            };
+            let (right, right_needs_lower, right_needs_upper) = if right_keys.is_empty() {
+                (n.right_hash_tree(), false, false)
+            } else {
+                go(&n.right, right_keys, f)
+            };
+            let middle = if left_len < right_start {
+                n.subtree_with(|v| f(k, v))
+            } else if left_needs_upper || right_needs_lower {
+                n.witness_tree()
+            } else {
+                pruned(n.data_hash())
+            };
+
+            (
+                three_way_fork(left, middle, right),
+                left_needs_lower,
+                right_needs_upper,
+            )
+        }
+
+        let mut keys: Vec<&[u8]> = keys.iter().map(|key| key.as_ref()).collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        go(&self.root, &keys, &mut f).0
+    }
+
     /// Returns a witness enumerating all the keys in this map.  The
     /// resulting tree doesn't include values, they are replaced with
     /// "Pruned" nodes.
@@ -239,6 +239,49 @@ fn map_model_test() {
     }
 }
 
+#[test]
+fn test_witness_many() {
+    let mut t = TreeOfBytes::new();
+    for i in 0u64..10 {
+        insert(&mut t, (1 + 2 * i).to_be_bytes(), (1 + 2 * i).to_le_bytes());
+    }
+
+    for i in 0u64..21 {
+        let key = i.to_be_bytes();
+        assert_eq!(
+            t.witness_many(&[key]),
+            t.witness(&key[..]),
+            "key: {}",
+            hex::encode(key)
+        );
+    }
+
+    let key_sets: [&[u64]; 5] = [
+        &[1, 3],
+        &[0, 20],
+        &[4, 5, 6],
+        &[19, 2, 11, 2],
+        &[
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+        ],
+    ];
+    for key_set in key_sets {
+        let keys: Vec<[u8; 8]> = key_set.iter().map(|i| i.to_be_bytes()).collect();
+        let ht = t.witness_many(&keys);
+
+        let merged = keys
+            .iter()
+            .map(|key| t.witness(&key[..]))
+            .reduce(|lhs, rhs| lhs.merge(&rhs).unwrap())
+            .unwrap();
+
+        assert_eq!(ht.digest(), t.root_hash(), "keys: {:?}", key_set);
+        assert_eq!(ht, merged, "keys: {:?}", key_set);
+    }
+
+    assert_eq!(TreeOfBytes::new().witness_many(&[b"key"]), empty());
+}
+
 #[test]
 fn test_nested_witness() {
     let mut rb: RbTree<Vec<u8>, TreeOfBytes> = RbTree::new();
@@ -14,4 +14,32 @@ pub enum HttpCertificationError {
     /// Error converting UTF-8 string.
     #[error(r#"Error converting UTF8 string bytes: "{0}""#)]
     Utf8ConversionError(#[from] std::string::FromUtf8Error),
+
+    /// The wildcard path of a certification does not match the path of the request.
+    #[error(
+        r#"The wildcard path "{wildcard_path}" is not valid for the request path "{request_path}""#
+    )]
+    WildcardPathNotValidForRequestPath {
+        /// The wildcard path of the certification.
+        wildcard_path: String,
+        /// The path of the request.
+        request_path: String,
+    },
+
+    /// The exact path of a certification does not match the path of the request.
+    #[error(r#"The exact path "{exact_path}" is not valid for the request path "{request_path}""#)]
+    ExactPathNotValidForRequestPath {
+        /// The exact path of the certification.
+        exact_path: String,
+        /// The path of the request.
+        request_path: String,
+    },
+
+    /// The expression path was malformed and could not be parsed correctly.
+    #[error(r#"Failed to parse expression path: {0:?}"#)]
+    MalformedExpressionPath(Vec<String>),
+
+    /// Error decoding CBOR.
+    #[error(r#"Error decoding CBOR: "{0}""#)]
+    CborDecodingError(#[from] ic_cbor::CborError),
 }
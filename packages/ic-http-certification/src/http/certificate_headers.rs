@@ -0,0 +1,146 @@
+use crate::{HeaderField, HttpCertificationPath};
+use base64::{engine::general_purpose, Engine as _};
+use ic_cbor::HashTreeToCbor;
+use ic_certification::HashTree;
+
+/// The name of the header containing the certificate, witness and expression path of a response.
+pub const CERTIFICATE_HEADER_NAME: &str = "IC-Certificate";
+
+/// The name of the header containing the CEL expression that was used to certify a response.
+pub const CERTIFICATE_EXPRESSION_HEADER_NAME: &str = "IC-CertificateExpression";
+
+/// The version of the HTTP certification protocol that is used by [create_certificate_headers].
+const CERTIFICATE_VERSION: u8 = 2;
+
+/// Creates the `IC-Certificate` and `IC-CertificateExpression` headers that HTTP Gateways use to
+/// verify a certified [HTTP response](crate::HttpResponse).
+///
+/// The `data_certificate` is the raw certificate that is returned by the `ic0.data_certificate`
+/// system API, the `witness` is created by the [HTTP certification tree](crate::HttpCertificationTree)
+/// for the request that is being responded to, the `expr_path` is the [path](HttpCertificationPath)
+/// of the certification that was used to create the witness and `cel_expr` is the
+/// [CEL expression](crate::CelExpression) that was used to create the certification, in its
+/// [String] representation.
+///
+/// The returned headers can be appended to the headers of the [HTTP response](crate::HttpResponse)
+/// as they are, for example:
+///
+/// ```rust
+/// use ic_http_certification::{
+///     create_certificate_headers, Certification, DefaultCelBuilder, HttpCertificationTree,
+///     HttpCertificationTreeEntry, HttpResponse,
+/// };
+///
+/// let mut http_certification_tree = HttpCertificationTree::default();
+/// let entry = HttpCertificationTreeEntry::wildcard("/", Certification::skip().unwrap());
+/// http_certification_tree.insert(&entry);
+///
+/// // this would be returned by `ic_cdk::api::data_certificate()`
+/// let data_certificate = vec![1, 2, 3];
+/// let witness = http_certification_tree.witness(&entry, "/index.html").unwrap();
+/// let cel_expr = DefaultCelBuilder::skip_certification().to_string();
+///
+/// let mut response = HttpResponse {
+///     status_code: 404,
+///     headers: vec![],
+///     body: vec![],
+/// };
+/// response.headers.extend(create_certificate_headers(
+///     &data_certificate,
+///     &witness,
+///     entry.path(),
+///     &cel_expr,
+/// ));
+/// ```
+pub fn create_certificate_headers(
+    data_certificate: &[u8],
+    witness: &HashTree,
+    expr_path: &HttpCertificationPath,
+    cel_expr: &str,
+) -> [HeaderField; 2] {
+    let certificate_header = format!(
+        "certificate=:{}:, tree=:{}:, expr_path=:{}:, version={}",
+        general_purpose::STANDARD.encode(data_certificate),
+        general_purpose::STANDARD.encode(witness.to_cbor()),
+        general_purpose::STANDARD.encode(expr_path.to_cbor()),
+        CERTIFICATE_VERSION
+    );
+
+    [
+        (CERTIFICATE_HEADER_NAME.to_string(), certificate_header),
+        (
+            CERTIFICATE_EXPRESSION_HEADER_NAME.to_string(),
+            cel_expr.to_string(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Certification, DefaultCelBuilder, HttpCertificationTree, HttpCertificationTreeEntry,
+    };
+    use rstest::*;
+
+    fn decode_certificate_header_field<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+        header.split(", ").find_map(|field| {
+            field
+                .strip_prefix(name)
+                .and_then(|field| field.strip_prefix('='))
+        })
+    }
+
+    fn decode_base64_certificate_header_field(header: &str, name: &str) -> Vec<u8> {
+        let value = decode_certificate_header_field(header, name)
+            .and_then(|value| value.strip_prefix(':'))
+            .and_then(|value| value.strip_suffix(':'))
+            .unwrap();
+
+        general_purpose::STANDARD.decode(value).unwrap()
+    }
+
+    #[rstest]
+    fn certificate_headers() {
+        let certification = Certification::skip().unwrap();
+        let entry = HttpCertificationTreeEntry::wildcard("/app/", certification);
+        let mut tree = HttpCertificationTree::default();
+        tree.insert(&entry);
+
+        let data_certificate = b"certificate".to_vec();
+        let witness = tree.witness(&entry, "/app/index.html").unwrap();
+        let cel_expr = DefaultCelBuilder::skip_certification().to_string();
+
+        let [(certificate_header_name, certificate_header), (expression_header_name, expression_header)] =
+            create_certificate_headers(&data_certificate, &witness, entry.path(), &cel_expr);
+
+        assert_eq!(certificate_header_name, "IC-Certificate");
+        assert_eq!(
+            decode_base64_certificate_header_field(&certificate_header, "certificate"),
+            data_certificate
+        );
+        assert_eq!(
+            HashTree::from_cbor(&decode_base64_certificate_header_field(
+                &certificate_header,
+                "tree"
+            ))
+            .unwrap(),
+            witness
+        );
+        assert_eq!(
+            HttpCertificationPath::from_cbor(&decode_base64_certificate_header_field(
+                &certificate_header,
+                "expr_path"
+            ))
+            .unwrap(),
+            HttpCertificationPath::wildcard("/app/")
+        );
+        assert_eq!(
+            decode_certificate_header_field(&certificate_header, "version"),
+            Some("2")
+        );
+
+        assert_eq!(expression_header_name, "IC-CertificateExpression");
+        assert_eq!(expression_header, cel_expr);
+    }
+}
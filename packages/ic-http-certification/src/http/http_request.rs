@@ -19,13 +19,7 @@ pub struct HttpRequest {
 impl HttpRequest {
     /// Returns the path of the request URL, without domain, query parameters or fragments.
     pub fn get_path<'a>(&'a self) -> HttpCertificationResult<String> {
-        let uri = self
-            .url
-            .parse::<Uri>()
-            .map_err(|_| HttpCertificationError::MalformedUrl(self.url.clone()))?;
-
-        let decoded_path = urlencoding::decode(uri.path()).map(|path| path.into_owned())?;
-        Ok(decoded_path)
+        url_path(&self.url)
     }
 
     /// Returns the query parameters of the request URL, if any, as a string.
@@ -37,6 +31,16 @@ impl HttpRequest {
     }
 }
 
+/// Returns the decoded path of a URL, without domain, query parameters or fragments.
+pub(crate) fn url_path(url: &str) -> HttpCertificationResult<String> {
+    let uri = url
+        .parse::<Uri>()
+        .map_err(|_| HttpCertificationError::MalformedUrl(url.to_string()))?;
+
+    let decoded_path = urlencoding::decode(uri.path()).map(|path| path.into_owned())?;
+    Ok(decoded_path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
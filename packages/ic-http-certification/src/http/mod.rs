@@ -2,10 +2,12 @@
 //! These types are Candid-encodable and are used by canisters that implement the
 //! HTTP interface required by the HTTP Gateway Protocol.
 
+mod certificate_headers;
 mod header_field;
 mod http_request;
 mod http_response;
 
+pub use certificate_headers::*;
 pub use header_field::*;
 pub use http_request::*;
 pub use http_response::*;
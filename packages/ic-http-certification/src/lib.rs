@@ -7,7 +7,7 @@ This crate provides a foundation for implementing the HTTP Certification protoco
 
 1. [Defining CEL expressions](#defining-cel-expressions)
 2. [Creating certifications](#creating-certifications)
-3. ...coming soon!!!
+3. [Creating an HTTP certification tree](#creating-an-http-certification-tree)
 
 ## Defining CEL Expressions
 
@@ -242,6 +242,73 @@ use ic_http_certification::Certification;
 let certification = Certification::skip();
 ```
 
+## Creating an HTTP certification tree
+
+Once a [Certification] has been created, it can be paired with the URL path that it is valid for to create an [HttpCertificationTreeEntry] and inserted into an [HttpCertificationTree]. The tree lays out certifications in the format that is expected by HTTP Gateways, so the [root hash](HttpCertificationTree::root_hash()) of the tree can be set directly as the canister's certified data.
+
+An entry can either be valid for exactly one URL path, using [HttpCertificationTreeEntry::exact], or for a URL path and every path below it, using [HttpCertificationTreeEntry::wildcard]. When an HTTP Gateway verifies a response, it will expect the most specific entry in the tree for the request path to have been used, so an exact entry always takes precedence over a wildcard entry and longer wildcard paths take precedence over shorter ones.
+
+```rust
+use ic_http_certification::{Certification, HttpCertificationTree, HttpCertificationTreeEntry};
+
+let mut http_certification_tree = HttpCertificationTree::default();
+
+let certification = Certification::skip().unwrap();
+let index_html_entry = HttpCertificationTreeEntry::exact("/index.html", certification.clone());
+let fallback_entry = HttpCertificationTreeEntry::wildcard("/", certification);
+
+http_certification_tree.insert(&index_html_entry);
+http_certification_tree.insert(&fallback_entry);
+
+// this hash should be set as the canister's certified data
+let root_hash = http_certification_tree.root_hash();
+```
+
+Both of these associated functions are shorthands for creating an entry from an [HttpCertificationPath], which can also be converted to and from the labels that are used in the tree and the `expr_path` field of the `IC-Certificate` header.
+
+When responding to a request, create a witness for the entry that was used to certify the response. Alongside the entry itself, the witness proves that there is no entry in the tree that is more specific to the request's path. An error is returned if the entry is not valid for the request's path.
+
+```rust
+use ic_http_certification::{Certification, HttpCertificationTree, HttpCertificationTreeEntry};
+
+let mut http_certification_tree = HttpCertificationTree::default();
+
+let fallback_entry = HttpCertificationTreeEntry::wildcard("/", Certification::skip().unwrap());
+http_certification_tree.insert(&fallback_entry);
+
+let witness = http_certification_tree.witness(&fallback_entry, "/app/index.html").unwrap();
+```
+
+The witness is sent to the HTTP Gateway in the `IC-Certificate` header, alongside the canister's data certificate and the path of the entry, and the CEL expression of the certification is sent in the `IC-CertificateExpression` header. Both headers can be created using [create_certificate_headers]:
+
+```rust
+use ic_http_certification::{create_certificate_headers, Certification, DefaultCelBuilder, HttpCertificationTree, HttpCertificationTreeEntry, HttpResponse};
+
+let mut http_certification_tree = HttpCertificationTree::default();
+
+let fallback_entry = HttpCertificationTreeEntry::wildcard("/", Certification::skip().unwrap());
+http_certification_tree.insert(&fallback_entry);
+
+let witness = http_certification_tree.witness(&fallback_entry, "/app/index.html").unwrap();
+
+// this is returned by `ic_cdk::api::data_certificate()` in a canister
+let data_certificate = vec![1, 2, 3];
+
+let mut response = HttpResponse {
+    status_code: 200,
+    headers: vec![],
+    body: vec![],
+};
+response.headers.extend(create_certificate_headers(
+    &data_certificate,
+    &witness,
+    fallback_entry.path(),
+    &DefaultCelBuilder::skip_certification().to_string(),
+));
+```
+
+Entries can be removed from the tree again using [HttpCertificationTree::delete].
+
 ## Directly creating a CEL expression
 
 To define a CEL expression, start with the [CelExpression](cel::CelExpression) enum. This enum provides a set of variants that can be used to define different types of CEL expressions supported by Internet Computer HTTP Gateways. Currently only one variant is supported, known as the "default" certification expression, but more may be added in the future as the HTTP certification protocol evolves over time.
@@ -21,7 +21,7 @@ use ic_representation_independent_hash::hash;
 /// - The [Full](Certification::Full) variant includes both an [HTTP response](crate::HttpResponse) and
 /// the corresponding [HTTP request](crate::HttpRequest) in certification. Create this variant using
 /// the [full()](Certification::full()) function.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Certification {
     /// A certification that excludes both the [HTTP request](crate::HttpRequest) and
     /// the corresponding [HTTP response](crate::HttpResponse).
@@ -174,6 +174,31 @@ impl Certification {
             response_hash,
         })
     }
+
+    /// Returns the segments of the [HTTP certification tree](crate::HttpCertificationTree) path
+    /// that follow the request path, i.e. the CEL expression hash, request hash and response hash.
+    ///
+    /// An empty segment is used in place of the request hash for the
+    /// [ResponseOnly](Certification::ResponseOnly) variant and the path ends at the CEL expression hash
+    /// for the [Skip](Certification::Skip) variant.
+    pub(crate) fn to_tree_path(&self) -> Vec<Vec<u8>> {
+        match self {
+            Certification::Skip { cel_expr_hash } => vec![cel_expr_hash.to_vec()],
+            Certification::ResponseOnly {
+                cel_expr_hash,
+                response_hash,
+            } => vec![cel_expr_hash.to_vec(), vec![], response_hash.to_vec()],
+            Certification::Full {
+                cel_expr_hash,
+                request_hash,
+                response_hash,
+            } => vec![
+                cel_expr_hash.to_vec(),
+                request_hash.to_vec(),
+                response_hash.to_vec(),
+            ],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,458 @@
+use crate::{HttpCertificationError, HttpCertificationResult};
+use ic_cbor::{encode_cbor_string_array, parse_cbor_string_array};
+
+const HTTP_EXPR_LABEL: &str = "http_expr";
+const EXACT_PATH_TERMINATOR: &str = "<$>";
+const WILDCARD_PATH_TERMINATOR: &str = "<*>";
+
+/// Determines which requests an [HttpCertificationPath] is valid for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpCertificationPathType {
+    /// The path is only valid for requests with exactly the same URL path.
+    Exact,
+
+    /// The path is valid for requests with the same URL path, or any URL path below it.
+    Wildcard,
+}
+
+/// A URL path in the [HTTP certification tree](crate::HttpCertificationTree).
+///
+/// The path is stored in the tree, and sent to HTTP Gateways in the `expr_path` field of the
+/// `IC-Certificate` header, as a list of segments. The list starts with the `http_expr` label,
+/// followed by the segments of the URL path and ends with `<$>` for [exact](HttpCertificationPath::exact())
+/// paths or `<*>` for [wildcard](HttpCertificationPath::wildcard()) paths. A path with a trailing slash
+/// ends with an empty segment, so that a request for a directory is not confused with a request for a
+/// file, i.e. `/app/` becomes `["http_expr", "app", "", "<*>"]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpCertificationPath {
+    segments: Vec<String>,
+    path_type: HttpCertificationPathType,
+}
+
+impl HttpCertificationPath {
+    /// Creates a path that is only valid for requests with exactly the provided URL path,
+    /// for example `/index.html`.
+    pub fn exact(path: impl AsRef<str>) -> Self {
+        Self {
+            segments: path_segments(path.as_ref()),
+            path_type: HttpCertificationPathType::Exact,
+        }
+    }
+
+    /// Creates a path that is valid for requests with the provided URL path, or any URL path below it,
+    /// for example `/app/`.
+    pub fn wildcard(path: impl AsRef<str>) -> Self {
+        Self {
+            segments: path_segments(path.as_ref()),
+            path_type: HttpCertificationPathType::Wildcard,
+        }
+    }
+
+    /// Returns the [type](HttpCertificationPathType) of this path.
+    pub fn path_type(&self) -> HttpCertificationPathType {
+        self.path_type
+    }
+
+    /// Returns true if this path is only valid for requests with exactly the same URL path.
+    pub fn is_exact(&self) -> bool {
+        self.path_type == HttpCertificationPathType::Exact
+    }
+
+    /// Returns true if this path is valid for requests with the same URL path, or any URL path below it.
+    pub fn is_wildcard(&self) -> bool {
+        self.path_type == HttpCertificationPathType::Wildcard
+    }
+
+    /// Returns the URL path, without the `http_expr` label and the exact or wildcard terminator.
+    pub fn to_url_path(&self) -> String {
+        if self.segments.is_empty() {
+            return String::new();
+        }
+
+        format!("/{}", self.segments.join("/"))
+    }
+
+    /// Returns the path as a list of strings, as it is used in the `expr_path` field of the
+    /// `IC-Certificate` header, i.e. `["http_expr", "app", "", "<*>"]`.
+    pub fn to_expr_path(&self) -> Vec<String> {
+        let mut expr_path = Vec::with_capacity(self.segments.len() + 2);
+        expr_path.push(HTTP_EXPR_LABEL.to_string());
+        expr_path.extend(self.segments.iter().cloned());
+        expr_path.push(self.terminator().to_string());
+
+        expr_path
+    }
+
+    /// Parses a path from a list of strings, as it is used in the `expr_path` field of the
+    /// `IC-Certificate` header.
+    ///
+    /// The list must start with the `http_expr` label and end with either the `<$>` or `<*>` terminator.
+    pub fn from_expr_path<T: AsRef<str>>(expr_path: &[T]) -> HttpCertificationResult<Self> {
+        let malformed_expr_path = || {
+            HttpCertificationError::MalformedExpressionPath(
+                expr_path
+                    .iter()
+                    .map(|segment| segment.as_ref().to_string())
+                    .collect(),
+            )
+        };
+
+        let Some((first, rest)) = expr_path.split_first() else {
+            return Err(malformed_expr_path());
+        };
+        if first.as_ref() != HTTP_EXPR_LABEL {
+            return Err(malformed_expr_path());
+        }
+
+        let path_type = match rest.last().map(|last| last.as_ref()) {
+            Some(EXACT_PATH_TERMINATOR) => HttpCertificationPathType::Exact,
+            Some(WILDCARD_PATH_TERMINATOR) => HttpCertificationPathType::Wildcard,
+            _ => return Err(malformed_expr_path()),
+        };
+
+        let segments = rest[..rest.len() - 1]
+            .iter()
+            .map(|segment| segment.as_ref().to_string())
+            .collect();
+
+        Ok(Self {
+            segments,
+            path_type,
+        })
+    }
+
+    /// Returns the path as a list of tree labels, as it is used to look up the path in a
+    /// [HashTree](ic_certification::HashTree).
+    pub fn to_tree_path(&self) -> Vec<Vec<u8>> {
+        self.to_expr_path()
+            .into_iter()
+            .map(|segment| segment.into_bytes())
+            .collect()
+    }
+
+    /// Parses a path from a list of tree labels.
+    ///
+    /// The labels must be valid UTF-8, start with the `http_expr` label and end with either the
+    /// `<$>` or `<*>` terminator.
+    pub fn from_tree_path<T: AsRef<[u8]>>(tree_path: &[T]) -> HttpCertificationResult<Self> {
+        let expr_path = tree_path
+            .iter()
+            .map(|label| String::from_utf8(label.as_ref().to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_expr_path(&expr_path)
+    }
+
+    /// Returns the CBOR encoding of the path, as it is used in the `expr_path` field of the
+    /// `IC-Certificate` header, before base64 encoding.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        encode_cbor_string_array(&self.to_expr_path())
+    }
+
+    /// Parses a path from its CBOR encoding, as it is used in the `expr_path` field of the
+    /// `IC-Certificate` header, after base64 decoding.
+    pub fn from_cbor(cbor: &[u8]) -> HttpCertificationResult<Self> {
+        let expr_path = parse_cbor_string_array(cbor)?;
+
+        Self::from_expr_path(&expr_path)
+    }
+
+    /// Returns the paths that must be absent from the [HTTP certification tree](crate::HttpCertificationTree)
+    /// for this path to be the most specific path for a request with the provided URL path.
+    ///
+    /// For exact paths this list is empty. For wildcard paths it contains the exact path of the request
+    /// and every wildcard path that is more specific than this path, in the order that HTTP Gateways
+    /// check them.
+    ///
+    /// An error is returned if this path is not valid for the provided URL path.
+    pub fn more_specific_paths(
+        &self,
+        request_path: &str,
+    ) -> HttpCertificationResult<Vec<HttpCertificationPath>> {
+        let mut request_segments = path_segments(request_path);
+
+        match self.path_type {
+            HttpCertificationPathType::Exact => {
+                if request_segments != self.segments {
+                    return Err(HttpCertificationError::ExactPathNotValidForRequestPath {
+                        exact_path: self.to_url_path(),
+                        request_path: request_path.to_string(),
+                    });
+                }
+
+                Ok(vec![])
+            }
+            HttpCertificationPathType::Wildcard => {
+                if !self.is_wildcard_valid_for_request_segments(&request_segments) {
+                    return Err(HttpCertificationError::WildcardPathNotValidForRequestPath {
+                        wildcard_path: self.to_url_path(),
+                        request_path: request_path.to_string(),
+                    });
+                }
+
+                let mut more_specific_paths = vec![Self {
+                    segments: request_segments.clone(),
+                    path_type: HttpCertificationPathType::Exact,
+                }];
+
+                while request_segments.len() > self.segments.len()
+                    || request_segments.last() != self.segments.last()
+                {
+                    more_specific_paths.push(Self {
+                        segments: request_segments.clone(),
+                        path_type: HttpCertificationPathType::Wildcard,
+                    });
+
+                    if request_segments.ends_with(&[String::new()]) {
+                        request_segments.pop();
+                    } else {
+                        request_segments.pop();
+                        request_segments.push(String::new());
+                    }
+                }
+
+                Ok(more_specific_paths)
+            }
+        }
+    }
+
+    /// A wildcard path with a trailing slash also covers the directory path without a trailing slash,
+    /// i.e. `/app/` covers both `/app/index.js` and `/app`.
+    fn is_wildcard_valid_for_request_segments(&self, request_segments: &[String]) -> bool {
+        if request_segments.starts_with(&self.segments) {
+            return true;
+        }
+
+        match self.segments.split_last() {
+            Some((last, parent_segments)) if last.is_empty() => {
+                request_segments.starts_with(parent_segments)
+            }
+            _ => false,
+        }
+    }
+
+    fn terminator(&self) -> &'static str {
+        match self.path_type {
+            HttpCertificationPathType::Exact => EXACT_PATH_TERMINATOR,
+            HttpCertificationPathType::Wildcard => WILDCARD_PATH_TERMINATOR,
+        }
+    }
+}
+
+/// Splits a URL path into segments, appending an empty segment for paths with a trailing slash.
+fn path_segments(path: &str) -> Vec<String> {
+    let mut segments: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect();
+
+    if path.ends_with('/') {
+        segments.push(String::new());
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("", vec!["http_expr", "<$>"])]
+    #[case("/", vec!["http_expr", "", "<$>"])]
+    #[case("/index.html", vec!["http_expr", "index.html", "<$>"])]
+    #[case("/app", vec!["http_expr", "app", "<$>"])]
+    #[case("/app/", vec!["http_expr", "app", "", "<$>"])]
+    #[case("/app//js/index.js", vec!["http_expr", "app", "js", "index.js", "<$>"])]
+    fn exact_path(#[case] path: &str, #[case] expected_expr_path: Vec<&str>) {
+        let certification_path = HttpCertificationPath::exact(path);
+
+        assert!(certification_path.is_exact());
+        assert!(!certification_path.is_wildcard());
+        assert_eq!(certification_path.to_expr_path(), expected_expr_path);
+        assert_eq!(
+            HttpCertificationPath::from_expr_path(&expected_expr_path).unwrap(),
+            certification_path
+        );
+    }
+
+    #[rstest]
+    #[case("", vec!["http_expr", "<*>"])]
+    #[case("/", vec!["http_expr", "", "<*>"])]
+    #[case("/app", vec!["http_expr", "app", "<*>"])]
+    #[case("/app/", vec!["http_expr", "app", "", "<*>"])]
+    #[case("/app/js/", vec!["http_expr", "app", "js", "", "<*>"])]
+    fn wildcard_path(#[case] path: &str, #[case] expected_expr_path: Vec<&str>) {
+        let certification_path = HttpCertificationPath::wildcard(path);
+
+        assert!(certification_path.is_wildcard());
+        assert!(!certification_path.is_exact());
+        assert_eq!(certification_path.to_expr_path(), expected_expr_path);
+        assert_eq!(
+            HttpCertificationPath::from_expr_path(&expected_expr_path).unwrap(),
+            certification_path
+        );
+    }
+
+    #[rstest]
+    #[case(HttpCertificationPath::exact(""), "")]
+    #[case(HttpCertificationPath::exact("/"), "/")]
+    #[case(HttpCertificationPath::exact("/index.html"), "/index.html")]
+    #[case(HttpCertificationPath::wildcard("/app/"), "/app/")]
+    #[case(HttpCertificationPath::wildcard("/app//js"), "/app/js")]
+    fn to_url_path(#[case] certification_path: HttpCertificationPath, #[case] expected_path: &str) {
+        assert_eq!(certification_path.to_url_path(), expected_path);
+    }
+
+    #[rstest]
+    #[case(HttpCertificationPath::exact("/index.html"))]
+    #[case(HttpCertificationPath::wildcard("/app/"))]
+    #[case(HttpCertificationPath::wildcard(""))]
+    fn tree_path_round_trip(#[case] certification_path: HttpCertificationPath) {
+        let tree_path = certification_path.to_tree_path();
+
+        assert_eq!(
+            HttpCertificationPath::from_tree_path(&tree_path).unwrap(),
+            certification_path
+        );
+    }
+
+    #[rstest]
+    #[case(HttpCertificationPath::exact("/index.html"))]
+    #[case(HttpCertificationPath::wildcard("/app/"))]
+    #[case(HttpCertificationPath::wildcard(""))]
+    fn cbor_round_trip(#[case] certification_path: HttpCertificationPath) {
+        let cbor = certification_path.to_cbor();
+
+        assert_eq!(&cbor[..3], &[0xd9, 0xd9, 0xf7]);
+        assert_eq!(
+            parse_cbor_string_array(&cbor).unwrap(),
+            certification_path.to_expr_path()
+        );
+        assert_eq!(
+            HttpCertificationPath::from_cbor(&cbor).unwrap(),
+            certification_path
+        );
+    }
+
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec!["http_expr"])]
+    #[case(vec!["http_expr", "app"])]
+    #[case(vec!["http_assets", "app", "<*>"])]
+    #[case(vec!["app", "<$>"])]
+    fn from_malformed_expr_path(#[case] expr_path: Vec<&str>) {
+        let result = HttpCertificationPath::from_expr_path(&expr_path);
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::MalformedExpressionPath(path)) if path == expr_path
+        ));
+    }
+
+    #[rstest]
+    fn from_non_utf8_tree_path() {
+        let tree_path = vec![b"http_expr".to_vec(), vec![0xff, 0xfe], b"<$>".to_vec()];
+
+        let result = HttpCertificationPath::from_tree_path(&tree_path);
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::Utf8ConversionError(_))
+        ));
+    }
+
+    #[rstest]
+    fn from_malformed_cbor() {
+        let result = HttpCertificationPath::from_cbor(&[0xff, 0x00]);
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::CborDecodingError(_))
+        ));
+    }
+
+    #[rstest]
+    #[case(HttpCertificationPath::exact("/"), "/")]
+    #[case(HttpCertificationPath::exact("/index.html"), "/index.html")]
+    #[case(HttpCertificationPath::exact("/app/"), "/app/")]
+    fn exact_path_more_specific_paths(
+        #[case] certification_path: HttpCertificationPath,
+        #[case] request_path: &str,
+    ) {
+        let result = certification_path
+            .more_specific_paths(request_path)
+            .unwrap();
+
+        assert!(result.is_empty());
+    }
+
+    #[rstest]
+    #[case(HttpCertificationPath::wildcard("/app/"), "/app/js/app.js", vec![
+        HttpCertificationPath::exact("/app/js/app.js"),
+        HttpCertificationPath::wildcard("/app/js/app.js"),
+        HttpCertificationPath::wildcard("/app/js/"),
+        HttpCertificationPath::wildcard("/app/js"),
+    ])]
+    #[case(HttpCertificationPath::wildcard("/app"), "/app/", vec![
+        HttpCertificationPath::exact("/app/"),
+        HttpCertificationPath::wildcard("/app/"),
+    ])]
+    #[case(HttpCertificationPath::wildcard("/app/"), "/app/", vec![
+        HttpCertificationPath::exact("/app/"),
+    ])]
+    #[case(HttpCertificationPath::wildcard(""), "/a", vec![
+        HttpCertificationPath::exact("/a"),
+        HttpCertificationPath::wildcard("/a"),
+        HttpCertificationPath::wildcard("/"),
+    ])]
+    #[case(HttpCertificationPath::wildcard("/app/"), "/app", vec![
+        HttpCertificationPath::exact("/app"),
+        HttpCertificationPath::wildcard("/app"),
+    ])]
+    fn wildcard_path_more_specific_paths(
+        #[case] certification_path: HttpCertificationPath,
+        #[case] request_path: &str,
+        #[case] expected_paths: Vec<HttpCertificationPath>,
+    ) {
+        let result = certification_path
+            .more_specific_paths(request_path)
+            .unwrap();
+
+        assert_eq!(result, expected_paths);
+    }
+
+    #[rstest]
+    #[case("/index.html", "/about.html")]
+    #[case("/app/", "/app")]
+    #[case("/app", "/app/")]
+    fn exact_path_not_valid_for_request_path(#[case] path: &str, #[case] request_path: &str) {
+        let result = HttpCertificationPath::exact(path).more_specific_paths(request_path);
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::ExactPathNotValidForRequestPath {
+                exact_path,
+                request_path: actual_request_path
+            }) if exact_path == path && actual_request_path == request_path
+        ));
+    }
+
+    #[rstest]
+    #[case("/app/", "/application/index.html")]
+    #[case("/app", "/application/index.html")]
+    #[case("/app/js/", "/app/index.html")]
+    fn wildcard_path_not_valid_for_request_path(#[case] path: &str, #[case] request_path: &str) {
+        let result = HttpCertificationPath::wildcard(path).more_specific_paths(request_path);
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::WildcardPathNotValidForRequestPath {
+                wildcard_path,
+                request_path: actual_request_path
+            }) if wildcard_path == path && actual_request_path == request_path
+        ));
+    }
+}
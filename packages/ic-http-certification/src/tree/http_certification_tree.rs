@@ -0,0 +1,368 @@
+use crate::{url_path, HttpCertificationResult, HttpCertificationTreeEntry};
+use ic_certification::{labeled, labeled_hash, AsHashTree, Hash, HashTree, NestedTree};
+
+/// The label of the root of the HTTP certification tree.
+const HTTP_EXPR_LABEL: &[u8] = b"http_expr";
+
+/// A certification tree for HTTP [request](crate::HttpRequest) and [response](crate::HttpResponse)
+/// pairs, laid out as expected by HTTP Gateways that implement the
+/// [HTTP Gateway Protocol](https://internetcomputer.org/docs/current/references/http-gateway-protocol-spec).
+///
+/// [Certifications](crate::Certification) are stored under the `http_expr` label at the path
+/// `<url_path_segments>/<$ or *>/<cel_expr_hash>/<request_hash>/<response_hash>`, where the request
+/// hash is empty for [response only](crate::Certification::ResponseOnly) certifications and both
+/// the request and response hashes are omitted for [skipped](crate::Certification::Skip)
+/// certifications.
+///
+/// Entries are added with [insert()](HttpCertificationTree::insert()) and removed with
+/// [delete()](HttpCertificationTree::delete()). The [root_hash()](HttpCertificationTree::root_hash())
+/// should be set as the canister's certified data after every modification and
+/// [witness()](HttpCertificationTree::witness()) produces the tree that is sent to the HTTP Gateway
+/// alongside a response.
+#[derive(Debug, Clone)]
+pub struct HttpCertificationTree {
+    tree: NestedTree<Vec<u8>, Vec<u8>>,
+}
+
+impl Default for HttpCertificationTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpCertificationTree {
+    /// Creates a new, empty, HTTP certification tree.
+    pub fn new() -> Self {
+        Self {
+            tree: NestedTree::default(),
+        }
+    }
+
+    /// Inserts an [entry](HttpCertificationTreeEntry) into the tree.
+    ///
+    /// Inserting the same entry more than once has no effect.
+    pub fn insert(&mut self, entry: &HttpCertificationTreeEntry) {
+        self.tree
+            .insert(nested_tree_path(&entry.to_tree_path()), vec![]);
+    }
+
+    /// Deletes an [entry](HttpCertificationTreeEntry) from the tree.
+    ///
+    /// Deleting an entry that is not in the tree has no effect.
+    pub fn delete(&mut self, entry: &HttpCertificationTreeEntry) {
+        self.tree.delete(nested_tree_path(&entry.to_tree_path()));
+    }
+
+    /// Returns the root hash of the tree, this hash should be set as the canister's certified data.
+    pub fn root_hash(&self) -> Hash {
+        labeled_hash(HTTP_EXPR_LABEL, &self.tree.root_hash())
+    }
+
+    /// Creates a witness for an [entry](HttpCertificationTreeEntry) that is used to certify the
+    /// response to a request for `request_url`.
+    ///
+    /// Besides the entry itself, the witness proves the absence of any entry that is more
+    /// specific to the request path than the provided entry, see
+    /// [more_specific_paths()](crate::HttpCertificationPath::more_specific_paths()), so HTTP Gateways
+    /// can confirm that the most specific certification was used for the response.
+    ///
+    /// An error is returned if the entry is not valid for the path of `request_url`.
+    pub fn witness(
+        &self,
+        entry: &HttpCertificationTreeEntry,
+        request_url: &str,
+    ) -> HttpCertificationResult<HashTree> {
+        let request_path = url_path(request_url)?;
+        let more_specific_paths = entry.path().more_specific_paths(&request_path)?;
+
+        let entry_path = entry.to_tree_path();
+        let more_specific_paths: Vec<Vec<Vec<u8>>> = more_specific_paths
+            .iter()
+            .map(|path| path.to_tree_path())
+            .collect();
+        let paths: Vec<&[Vec<u8>]> = std::iter::once(&entry_path)
+            .chain(more_specific_paths.iter())
+            .map(|path| nested_tree_path(path))
+            .collect();
+
+        let witness = self.tree.witness_many(&paths);
+
+        Ok(labeled(HTTP_EXPR_LABEL, witness))
+    }
+}
+
+/// Strips the `http_expr` label from a tree path, the label is added to the root of the tree
+/// separately so that it is present even when the tree is empty.
+fn nested_tree_path(tree_path: &[Vec<u8>]) -> &[Vec<u8>] {
+    &tree_path[1..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Certification, DefaultCelBuilder, DefaultResponseCertification, HttpCertificationError,
+        HttpRequest, HttpResponse,
+    };
+    use ic_certification::{leaf, Label, SubtreeLookupResult};
+    use rstest::*;
+
+    #[fixture]
+    fn certification() -> Certification {
+        let cel_expr = DefaultCelBuilder::response_only_certification()
+            .with_response_certification(DefaultResponseCertification::certified_response_headers(
+                &["Cache-Control"],
+            ))
+            .build();
+        let response = HttpResponse {
+            status_code: 200,
+            headers: vec![("Cache-Control".to_string(), "no-cache".to_string())],
+            body: b"Hello World!".to_vec(),
+        };
+
+        Certification::response_only(&cel_expr, &response, None).unwrap()
+    }
+
+    fn full_tree_path(path: &[&str], certification: &Certification) -> Vec<Label> {
+        let mut tree_path: Vec<Label> = vec!["http_expr".into()];
+        tree_path.extend(path.iter().map(|segment| Label::from(*segment)));
+        tree_path.extend(certification.to_tree_path().into_iter().map(Label::from));
+
+        tree_path
+    }
+
+    fn lookup(witness: &HashTree, path: &[&str]) -> SubtreeLookupResult {
+        let path: Vec<Label> = path.iter().map(|segment| Label::from(*segment)).collect();
+
+        witness.lookup_subtree(&path)
+    }
+
+    #[rstest]
+    fn empty_tree_root_hash() {
+        let tree = HttpCertificationTree::default();
+
+        assert_eq!(
+            tree.root_hash(),
+            labeled_hash(
+                b"http_expr",
+                &NestedTree::<Vec<u8>, Vec<u8>>::default().root_hash()
+            )
+        );
+    }
+
+    #[rstest]
+    fn insert_and_delete(certification: Certification) {
+        let mut tree = HttpCertificationTree::default();
+        let empty_root_hash = tree.root_hash();
+        let exact_entry = HttpCertificationTreeEntry::exact("/index.html", certification.clone());
+        let wildcard_entry = HttpCertificationTreeEntry::wildcard("/app/", certification);
+
+        tree.insert(&exact_entry);
+        let exact_root_hash = tree.root_hash();
+        assert_ne!(exact_root_hash, empty_root_hash);
+
+        tree.insert(&wildcard_entry);
+        assert_ne!(tree.root_hash(), exact_root_hash);
+
+        tree.delete(&wildcard_entry);
+        assert_eq!(tree.root_hash(), exact_root_hash);
+
+        tree.delete(&exact_entry);
+        assert_eq!(tree.root_hash(), empty_root_hash);
+    }
+
+    #[rstest]
+    fn exact_witness(certification: Certification) {
+        let mut tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::exact("/index.html", certification.clone());
+        tree.insert(&entry);
+        tree.insert(&HttpCertificationTreeEntry::wildcard(
+            "/",
+            certification.clone(),
+        ));
+
+        let witness = tree
+            .witness(&entry, "https://canister.com/index.html?foo=bar")
+            .unwrap();
+
+        assert_eq!(witness.digest(), tree.root_hash());
+        assert!(matches!(
+            witness.lookup_subtree(&full_tree_path(&["index.html", "<$>"], &certification)),
+            SubtreeLookupResult::Found(subtree) if subtree.digest() == leaf(vec![]).digest()
+        ));
+        assert!(matches!(
+            lookup(&witness, &["http_expr", "", "<*>"]),
+            SubtreeLookupResult::Unknown
+        ));
+    }
+
+    #[rstest]
+    fn wildcard_witness(certification: Certification) {
+        let mut tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::wildcard("/app/", certification.clone());
+        tree.insert(&entry);
+        tree.insert(&HttpCertificationTreeEntry::wildcard(
+            "/",
+            certification.clone(),
+        ));
+        tree.insert(&HttpCertificationTreeEntry::exact(
+            "/app/index.html",
+            certification.clone(),
+        ));
+        tree.insert(&HttpCertificationTreeEntry::exact(
+            "/app/js/index.js",
+            certification.clone(),
+        ));
+
+        let witness = tree
+            .witness(&entry, "https://canister.com/app/js/app.js")
+            .unwrap();
+
+        assert_eq!(witness.digest(), tree.root_hash());
+        assert!(matches!(
+            witness.lookup_subtree(&full_tree_path(&["app", "", "<*>"], &certification)),
+            SubtreeLookupResult::Found(_)
+        ));
+        assert!(matches!(
+            lookup(&witness, &["http_expr", "app", "js", "app.js", "<$>"]),
+            SubtreeLookupResult::Absent
+        ));
+        assert!(matches!(
+            lookup(&witness, &["http_expr", "app", "js", "app.js", "<*>"]),
+            SubtreeLookupResult::Absent
+        ));
+        assert!(matches!(
+            lookup(&witness, &["http_expr", "app", "js", "", "<*>"]),
+            SubtreeLookupResult::Absent
+        ));
+        assert!(matches!(
+            lookup(&witness, &["http_expr", "app", "js", "<*>"]),
+            SubtreeLookupResult::Absent
+        ));
+        assert!(matches!(
+            lookup(&witness, &["http_expr", "app", "index.html", "<$>"]),
+            SubtreeLookupResult::Unknown
+        ));
+    }
+
+    #[rstest]
+    fn witness_for_deleted_entry_proves_absence(certification: Certification) {
+        let mut tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::exact("/index.html", certification.clone());
+        tree.insert(&entry);
+        tree.insert(&HttpCertificationTreeEntry::exact(
+            "/about.html",
+            certification,
+        ));
+        tree.delete(&entry);
+
+        let witness = tree.witness(&entry, "/index.html").unwrap();
+
+        assert_eq!(witness.digest(), tree.root_hash());
+        assert!(matches!(
+            lookup(&witness, &["http_expr", "index.html"]),
+            SubtreeLookupResult::Absent
+        ));
+    }
+
+    #[rstest]
+    #[case("/index.html", "/about.html")]
+    #[case("/app/", "/app")]
+    #[case("/app", "/app/")]
+    fn exact_witness_for_other_path_fails(
+        certification: Certification,
+        #[case] entry_path: &str,
+        #[case] request_path: &str,
+    ) {
+        let mut tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::exact(entry_path, certification);
+        tree.insert(&entry);
+
+        let result = tree.witness(&entry, request_path);
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::ExactPathNotValidForRequestPath {
+                exact_path,
+                request_path: actual_request_path
+            }) if exact_path == entry_path && actual_request_path == request_path
+        ));
+    }
+
+    #[rstest]
+    #[case("/app/", "/application/index.html")]
+    #[case("/app", "/application/index.html")]
+    #[case("/app/js/", "/app/index.html")]
+    fn wildcard_witness_for_other_path_fails(
+        certification: Certification,
+        #[case] entry_path: &str,
+        #[case] request_path: &str,
+    ) {
+        let mut tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::wildcard(entry_path, certification);
+        tree.insert(&entry);
+
+        let result = tree.witness(&entry, request_path);
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::WildcardPathNotValidForRequestPath {
+                wildcard_path,
+                request_path: actual_request_path
+            }) if wildcard_path == entry_path && actual_request_path == request_path
+        ));
+    }
+
+    #[rstest]
+    #[case("/", "/")]
+    #[case("/", "/index.html")]
+    #[case("/app", "/app")]
+    #[case("/app", "/app/")]
+    #[case("/app", "/app/index.html")]
+    #[case("/app/", "/app")]
+    #[case("/app/", "/app/")]
+    #[case("/app/", "/app/js/index.js")]
+    fn wildcard_witness_for_covered_path(
+        certification: Certification,
+        #[case] entry_path: &str,
+        #[case] request_path: &str,
+    ) {
+        let mut tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::wildcard(entry_path, certification);
+        tree.insert(&entry);
+
+        let witness = tree.witness(&entry, request_path).unwrap();
+
+        assert_eq!(witness.digest(), tree.root_hash());
+    }
+
+    #[rstest]
+    fn witness_for_malformed_url_fails(certification: Certification) {
+        let tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::exact("/", certification);
+
+        let result = tree.witness(&entry, "https://canister.com/index html");
+
+        assert!(matches!(
+            result,
+            Err(HttpCertificationError::MalformedUrl(_))
+        ));
+    }
+
+    #[rstest]
+    fn witness_matches_request_path(certification: Certification) {
+        let request = HttpRequest {
+            method: "GET".to_string(),
+            url: "/app/%73ample-asset.txt".to_string(),
+            headers: vec![],
+            body: vec![],
+        };
+        let mut tree = HttpCertificationTree::default();
+        let entry = HttpCertificationTreeEntry::exact("/app/sample-asset.txt", certification);
+        tree.insert(&entry);
+
+        let witness = tree.witness(&entry, &request.url).unwrap();
+
+        assert_eq!(witness.digest(), tree.root_hash());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{Certification, HttpCertificationPath};
+
+/// A [Certification] and the [path](HttpCertificationPath) that it is valid for. Entries are
+/// inserted into, deleted from and witnessed by an [HTTP certification tree](crate::HttpCertificationTree).
+///
+/// Create an entry from an existing [HttpCertificationPath] using the associated
+/// [new()](HttpCertificationTreeEntry::new()) function, or use the
+/// [exact()](HttpCertificationTreeEntry::exact()) and [wildcard()](HttpCertificationTreeEntry::wildcard())
+/// shorthands to create the path and the entry in one go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpCertificationTreeEntry {
+    path: HttpCertificationPath,
+    certification: Certification,
+}
+
+impl HttpCertificationTreeEntry {
+    /// Creates an entry for a [Certification] that is valid for the provided [path](HttpCertificationPath).
+    pub fn new(path: HttpCertificationPath, certification: Certification) -> Self {
+        Self {
+            path,
+            certification,
+        }
+    }
+
+    /// Creates an entry for a [Certification] that is only valid for requests with exactly the
+    /// provided URL path, for example `/index.html`.
+    pub fn exact(path: impl AsRef<str>, certification: Certification) -> Self {
+        Self::new(HttpCertificationPath::exact(path), certification)
+    }
+
+    /// Creates an entry for a [Certification] that is valid for requests with the provided URL path,
+    /// or any path below it, for example `/app/`. Requests are served by the most specific wildcard
+    /// entry that matches their path, if there is no exact entry for their path.
+    pub fn wildcard(path: impl AsRef<str>, certification: Certification) -> Self {
+        Self::new(HttpCertificationPath::wildcard(path), certification)
+    }
+
+    /// Returns the [path](HttpCertificationPath) of this entry.
+    pub fn path(&self) -> &HttpCertificationPath {
+        &self.path
+    }
+
+    /// Returns the [Certification] of this entry.
+    pub fn certification(&self) -> &Certification {
+        &self.certification
+    }
+
+    /// Returns the full path of this entry in the
+    /// [HTTP certification tree](crate::HttpCertificationTree).
+    pub(crate) fn to_tree_path(&self) -> Vec<Vec<u8>> {
+        let mut tree_path = self.path.to_tree_path();
+        tree_path.extend(self.certification.to_tree_path());
+
+        tree_path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn exact_entry_tree_path() {
+        let certification = Certification::skip().unwrap();
+        let entry = HttpCertificationTreeEntry::exact("/index.html", certification.clone());
+
+        let mut expected_tree_path = vec![
+            b"http_expr".to_vec(),
+            b"index.html".to_vec(),
+            b"<$>".to_vec(),
+        ];
+        expected_tree_path.extend(certification.to_tree_path());
+
+        assert_eq!(entry.path(), &HttpCertificationPath::exact("/index.html"));
+        assert_eq!(entry.to_tree_path(), expected_tree_path);
+    }
+
+    #[rstest]
+    fn wildcard_entry_tree_path() {
+        let certification = Certification::skip().unwrap();
+        let entry = HttpCertificationTreeEntry::wildcard("/app/", certification.clone());
+
+        let mut expected_tree_path = vec![
+            b"http_expr".to_vec(),
+            b"app".to_vec(),
+            b"".to_vec(),
+            b"<*>".to_vec(),
+        ];
+        expected_tree_path.extend(certification.to_tree_path());
+
+        assert_eq!(entry.path(), &HttpCertificationPath::wildcard("/app/"));
+        assert_eq!(entry.to_tree_path(), expected_tree_path);
+    }
+}
@@ -2,7 +2,18 @@
 //! [request](crate::HttpRequest) and [response](crate::HttpResponse) pairs in a
 //! purpose-build HTTP certification data structure.
 //!
-//! Certifications are prepared using the [Certification] enum.
+//! Certifications are prepared using the [Certification] enum, paired with the
+//! [HttpCertificationPath] that they are valid for using the [HttpCertificationTreeEntry] struct
+//! and stored in the [HttpCertificationTree] struct.
 
 mod certification;
 pub use certification::*;
+
+mod http_certification_path;
+pub use http_certification_path::*;
+
+mod http_certification_tree_entry;
+pub use http_certification_tree_entry::*;
+
+mod http_certification_tree;
+pub use http_certification_tree::*;
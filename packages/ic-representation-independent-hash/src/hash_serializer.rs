@@ -0,0 +1,732 @@
+use crate::{
+    hash,
+    representation_independent_hash::{hash_number, hash_of_map},
+    Sha256Digest,
+};
+use serde::{
+    ser::{
+        self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+        SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize,
+};
+use sha2::{Digest, Sha256};
+
+/// Hash serializer result type.
+pub type HashSerializerResult<T = Option<Sha256Digest>> = Result<T, HashSerializerError>;
+
+/// Hash serializer error type.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum HashSerializerError {
+    /// The type has no representation independent hash, for example floats or booleans.
+    #[error(r#"Type "{0}" is not supported by representation independent hashing"#)]
+    UnsupportedType(&'static str),
+
+    /// A map key was not a string.
+    #[error("Map keys must be strings")]
+    NonStringMapKey,
+
+    /// A value was omitted where only map values can be omitted, for example `None` in an array.
+    #[error("Only map values can be omitted, but a value was missing")]
+    MissingValue,
+
+    /// A custom error raised by a [Serialize] implementation.
+    #[error(r#"Failed to serialize value: "{0}""#)]
+    Custom(String),
+}
+
+impl ser::Error for HashSerializerError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        HashSerializerError::Custom(msg.to_string())
+    }
+}
+
+/// Calculates the [`Representation Independent Hash`] of any value that implements [Serialize].
+///
+/// See [HashSerializer] for how values are mapped to the hash.
+///
+/// [`Representation Independent Hash`]: https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map
+pub fn hash_serializable<T: Serialize + ?Sized>(value: &T) -> HashSerializerResult<Sha256Digest> {
+    value
+        .serialize(HashSerializer)?
+        .ok_or(HashSerializerError::MissingValue)
+}
+
+/// A [serde Serializer](ser::Serializer) that calculates the
+/// [`Representation Independent Hash`] of a value.
+///
+/// - structs and maps are hashed as maps, and their `None` fields are omitted,
+/// - sequences and tuples are hashed as arrays,
+/// - strings and characters are hashed as their UTF-8 encoding,
+/// - unsigned integers are hashed as their LEB128 encoding and signed integers as their SLEB128
+///   encoding,
+/// - bytes are hashed as they are, so byte arrays and vectors should be serialized with
+///   [serde_bytes](https://docs.rs/serde_bytes) to be hashed as blobs rather than arrays,
+/// - unit variants are hashed as their name, and other enum variants as a map from their name to
+///   their content.
+///
+/// Other types, such as floats, booleans and units, are rejected with
+/// [HashSerializerError::UnsupportedType].
+///
+/// The serializer returns `None` for values that are omitted from maps, such as `None` options.
+///
+/// [`Representation Independent Hash`]: https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map
+#[derive(Debug, Clone, Copy)]
+pub struct HashSerializer;
+
+impl ser::Serializer for HashSerializer {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    type SerializeSeq = ArrayHasher;
+    type SerializeTuple = ArrayHasher;
+    type SerializeTupleStruct = ArrayHasher;
+    type SerializeTupleVariant = ArrayHasher;
+    type SerializeMap = MapHasher;
+    type SerializeStruct = MapHasher;
+    type SerializeStructVariant = MapHasher;
+
+    fn serialize_bool(self, _v: bool) -> HashSerializerResult {
+        Err(HashSerializerError::UnsupportedType("bool"))
+    }
+
+    fn serialize_i8(self, v: i8) -> HashSerializerResult {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> HashSerializerResult {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> HashSerializerResult {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> HashSerializerResult {
+        let mut hasher = Sha256::new();
+        leb128::write::signed(&mut hasher, v).unwrap();
+
+        Ok(Some(hasher.finalize().into()))
+    }
+
+    fn serialize_u8(self, v: u8) -> HashSerializerResult {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> HashSerializerResult {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> HashSerializerResult {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> HashSerializerResult {
+        Ok(Some(hash_number(v)))
+    }
+
+    fn serialize_f32(self, _v: f32) -> HashSerializerResult {
+        Err(HashSerializerError::UnsupportedType("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> HashSerializerResult {
+        Err(HashSerializerError::UnsupportedType("f64"))
+    }
+
+    fn serialize_char(self, v: char) -> HashSerializerResult {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> HashSerializerResult {
+        Ok(Some(hash(v.as_bytes())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> HashSerializerResult {
+        Ok(Some(hash(v)))
+    }
+
+    fn serialize_none(self) -> HashSerializerResult {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> HashSerializerResult {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> HashSerializerResult {
+        Err(HashSerializerError::UnsupportedType("unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> HashSerializerResult {
+        Err(HashSerializerError::UnsupportedType("unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> HashSerializerResult {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> HashSerializerResult {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> HashSerializerResult {
+        let value_hash = value.serialize(self)?;
+
+        Ok(Some(hash_of_variant(variant, value_hash)))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> HashSerializerResult<ArrayHasher> {
+        Ok(ArrayHasher::new(None))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> HashSerializerResult<ArrayHasher> {
+        Ok(ArrayHasher::new(None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> HashSerializerResult<ArrayHasher> {
+        Ok(ArrayHasher::new(None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> HashSerializerResult<ArrayHasher> {
+        Ok(ArrayHasher::new(Some(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> HashSerializerResult<MapHasher> {
+        Ok(MapHasher::new(None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> HashSerializerResult<MapHasher> {
+        Ok(MapHasher::new(None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> HashSerializerResult<MapHasher> {
+        Ok(MapHasher::new(Some(variant)))
+    }
+}
+
+/// Hashes the elements of a sequence or tuple as an array, see [HashSerializer].
+#[derive(Debug)]
+pub struct ArrayHasher {
+    hasher: Sha256,
+    variant: Option<&'static str>,
+}
+
+impl ArrayHasher {
+    fn new(variant: Option<&'static str>) -> Self {
+        Self {
+            hasher: Sha256::new(),
+            variant,
+        }
+    }
+
+    fn add_element<T: Serialize + ?Sized>(&mut self, value: &T) -> HashSerializerResult<()> {
+        let value_hash = value
+            .serialize(HashSerializer)?
+            .ok_or(HashSerializerError::MissingValue)?;
+        self.hasher.update(value_hash);
+
+        Ok(())
+    }
+
+    fn finish(self) -> HashSerializerResult {
+        let array_hash = self.hasher.finalize().into();
+
+        Ok(Some(match self.variant {
+            Some(variant) => hash_of_variant(variant, Some(array_hash)),
+            None => array_hash,
+        }))
+    }
+}
+
+impl SerializeSeq for ArrayHasher {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> HashSerializerResult<()> {
+        self.add_element(value)
+    }
+
+    fn end(self) -> HashSerializerResult {
+        self.finish()
+    }
+}
+
+impl SerializeTuple for ArrayHasher {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> HashSerializerResult<()> {
+        self.add_element(value)
+    }
+
+    fn end(self) -> HashSerializerResult {
+        self.finish()
+    }
+}
+
+impl SerializeTupleStruct for ArrayHasher {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> HashSerializerResult<()> {
+        self.add_element(value)
+    }
+
+    fn end(self) -> HashSerializerResult {
+        self.finish()
+    }
+}
+
+impl SerializeTupleVariant for ArrayHasher {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> HashSerializerResult<()> {
+        self.add_element(value)
+    }
+
+    fn end(self) -> HashSerializerResult {
+        self.finish()
+    }
+}
+
+/// Hashes the entries of a map or the fields of a struct as a map, see [HashSerializer].
+#[derive(Debug)]
+pub struct MapHasher {
+    hashes: Vec<(Sha256Digest, Sha256Digest)>,
+    key_hash: Option<Sha256Digest>,
+    variant: Option<&'static str>,
+}
+
+impl MapHasher {
+    fn new(variant: Option<&'static str>) -> Self {
+        Self {
+            hashes: vec![],
+            key_hash: None,
+            variant,
+        }
+    }
+
+    fn add_entry<T: Serialize + ?Sized>(
+        &mut self,
+        key_hash: Sha256Digest,
+        value: &T,
+    ) -> HashSerializerResult<()> {
+        if let Some(value_hash) = value.serialize(HashSerializer)? {
+            self.hashes.push((key_hash, value_hash));
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> HashSerializerResult {
+        let map_hash = hash_of_map(self.hashes);
+
+        Ok(Some(match self.variant {
+            Some(variant) => hash_of_variant(variant, Some(map_hash)),
+            None => map_hash,
+        }))
+    }
+}
+
+impl SerializeMap for MapHasher {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> HashSerializerResult<()> {
+        self.key_hash = Some(key.serialize(MapKeyHasher)?);
+
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> HashSerializerResult<()> {
+        let key_hash = self
+            .key_hash
+            .take()
+            .expect("serialize_value called before serialize_key");
+
+        self.add_entry(key_hash, value)
+    }
+
+    fn end(self) -> HashSerializerResult {
+        self.finish()
+    }
+}
+
+impl SerializeStruct for MapHasher {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> HashSerializerResult<()> {
+        self.add_entry(hash(key.as_bytes()), value)
+    }
+
+    fn end(self) -> HashSerializerResult {
+        self.finish()
+    }
+}
+
+impl SerializeStructVariant for MapHasher {
+    type Ok = Option<Sha256Digest>;
+    type Error = HashSerializerError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> HashSerializerResult<()> {
+        self.add_entry(hash(key.as_bytes()), value)
+    }
+
+    fn end(self) -> HashSerializerResult {
+        self.finish()
+    }
+}
+
+fn hash_of_variant(variant: &'static str, value_hash: Option<Sha256Digest>) -> Sha256Digest {
+    hash_of_map(
+        value_hash
+            .map(|value_hash| (hash(variant.as_bytes()), value_hash))
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// Hashes map keys, which must be strings.
+struct MapKeyHasher;
+
+impl ser::Serializer for MapKeyHasher {
+    type Ok = Sha256Digest;
+    type Error = HashSerializerError;
+
+    type SerializeSeq = Impossible<Sha256Digest, HashSerializerError>;
+    type SerializeTuple = Impossible<Sha256Digest, HashSerializerError>;
+    type SerializeTupleStruct = Impossible<Sha256Digest, HashSerializerError>;
+    type SerializeTupleVariant = Impossible<Sha256Digest, HashSerializerError>;
+    type SerializeMap = Impossible<Sha256Digest, HashSerializerError>;
+    type SerializeStruct = Impossible<Sha256Digest, HashSerializerError>;
+    type SerializeStructVariant = Impossible<Sha256Digest, HashSerializerError>;
+
+    fn serialize_str(self, v: &str) -> HashSerializerResult<Sha256Digest> {
+        Ok(hash(v.as_bytes()))
+    }
+
+    fn serialize_char(self, v: char) -> HashSerializerResult<Sha256Digest> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> HashSerializerResult<Sha256Digest> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> HashSerializerResult<Sha256Digest> {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, _v: bool) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_i8(self, _v: i8) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_i16(self, _v: i16) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_i32(self, _v: i32) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_i64(self, _v: i64) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_u8(self, _v: u8) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_u16(self, _v: u16) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_u32(self, _v: u32) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_u64(self, _v: u64) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_f32(self, _v: f32) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_f64(self, _v: f64) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_none(self) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        _value: &T,
+    ) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_unit(self) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> HashSerializerResult<Sha256Digest> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> HashSerializerResult<Self::SerializeSeq> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> HashSerializerResult<Self::SerializeTuple> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> HashSerializerResult<Self::SerializeTupleStruct> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> HashSerializerResult<Self::SerializeTupleVariant> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> HashSerializerResult<Self::SerializeMap> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> HashSerializerResult<Self::SerializeStruct> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> HashSerializerResult<Self::SerializeStructVariant> {
+        Err(HashSerializerError::NonStringMapKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{representation_independent_hash, request_id, RequestContent, Value};
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct CallContent {
+        request_type: RequestType,
+        #[serde(with = "serde_bytes")]
+        canister_id: Vec<u8>,
+        method_name: String,
+        #[serde(with = "serde_bytes")]
+        arg: Vec<u8>,
+        #[serde(with = "serde_bytes")]
+        sender: Vec<u8>,
+        ingress_expiry: u64,
+        #[serde(with = "serde_bytes", skip_serializing_if = "Option::is_none")]
+        nonce: Option<Vec<u8>>,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum RequestType {
+        Call,
+    }
+
+    #[test]
+    fn hash_struct_as_request_id() {
+        let content = CallContent {
+            request_type: RequestType::Call,
+            canister_id: vec![0, 0, 0, 0, 0, 0, 0x04, 0xd2],
+            method_name: "hello".into(),
+            arg: b"DIDL\x00\xfd*".to_vec(),
+            sender: vec![0x04],
+            ingress_expiry: 1_685_570_400_000_000_000,
+            nonce: None,
+        };
+        let expected_hash = request_id(&RequestContent::Call {
+            canister_id: content.canister_id.clone(),
+            method_name: content.method_name.clone(),
+            arg: content.arg.clone(),
+            sender: content.sender.clone(),
+            ingress_expiry: content.ingress_expiry,
+            nonce: None,
+        });
+
+        let result = hash_serializable(&content).unwrap();
+
+        assert_eq!(result, expected_hash);
+    }
+
+    #[test]
+    fn hash_nested_values() {
+        #[derive(Serialize)]
+        struct Nested {
+            name: &'static str,
+            values: Vec<u64>,
+            labels: BTreeMap<&'static str, &'static str>,
+            missing: Option<u64>,
+        }
+
+        let nested = Nested {
+            name: "foo",
+            values: vec![1, 2, 3],
+            labels: BTreeMap::from([("key", "value")]),
+            missing: None,
+        };
+        let expected_hash = representation_independent_hash(&[
+            ("name".into(), Value::String("foo".into())),
+            (
+                "values".into(),
+                Value::Array(vec![Value::Number(1), Value::Number(2), Value::Number(3)]),
+            ),
+            (
+                "labels".into(),
+                Value::Map(vec![("key".into(), Value::String("value".into()))]),
+            ),
+        ]);
+
+        let result = hash_serializable(&nested).unwrap();
+
+        assert_eq!(result, expected_hash);
+    }
+
+    #[test]
+    fn hash_signed_integers() {
+        let mut hasher = Sha256::new();
+        leb128::write::signed(&mut hasher, -42).unwrap();
+        let expected_hash: Sha256Digest = hasher.finalize().into();
+
+        let result = hash_serializable(&-42i32).unwrap();
+
+        assert_eq!(result, expected_hash);
+    }
+
+    #[test]
+    fn hash_enum_variants() {
+        #[derive(Serialize)]
+        enum Status {
+            Rejected { reject_code: u64 },
+        }
+
+        let expected_hash = representation_independent_hash(&[(
+            "Rejected".into(),
+            Value::Map(vec![("reject_code".into(), Value::Number(3))]),
+        )]);
+
+        let result = hash_serializable(&Status::Rejected { reject_code: 3 }).unwrap();
+
+        assert_eq!(result, expected_hash);
+    }
+
+    #[test]
+    fn reject_unsupported_types() {
+        assert_eq!(
+            hash_serializable(&1.5f64),
+            Err(HashSerializerError::UnsupportedType("f64"))
+        );
+        assert_eq!(
+            hash_serializable(&true),
+            Err(HashSerializerError::UnsupportedType("bool"))
+        );
+        assert_eq!(
+            hash_serializable(&BTreeMap::from([(1u64, "value")])),
+            Err(HashSerializerError::NonStringMapKey)
+        );
+        assert_eq!(
+            hash_serializable(&vec![Some(1u64), None]),
+            Err(HashSerializerError::MissingValue)
+        );
+        assert_eq!(
+            hash_serializable(&None::<u64>),
+            Err(HashSerializerError::MissingValue)
+        );
+    }
+}
@@ -13,6 +13,12 @@ pub type Sha256Digest = [u8; 32];
 mod representation_independent_hash;
 pub use representation_independent_hash::*;
 
+mod request_id;
+pub use request_id::*;
+
+mod hash_serializer;
+pub use hash_serializer::*;
+
 use sha2::{Digest, Sha256};
 
 /// Calculates the SHA-256 hash of the given slice.
@@ -1,7 +1,7 @@
 use crate::{hash, Sha256Digest};
 use sha2::{Digest, Sha256};
 
-/// Represents a value to be hashed. Only UTF-8 strings, bytes and unsigned numbers are currently supported.
+/// Represents a value to be hashed.
 #[derive(Debug, Clone)]
 pub enum Value {
     /// An UTF-8 string to be hashed.
@@ -10,18 +10,27 @@ pub enum Value {
     Number(u64),
     /// Bytes to be hashed.
     Bytes(Vec<u8>),
+    /// An array of values to be hashed.
+    Array(Vec<Value>),
+    /// A nested map to be hashed.
+    Map(Vec<(String, Value)>),
 }
 
-/// A partial implementation of [`Representation Independent Hash`] that only supports
-/// UTF-8 strings or numbers as values.
+/// An implementation of [`Representation Independent Hash`] that supports UTF-8 strings,
+/// numbers, bytes, arrays and nested maps as values.
 ///
 /// [`Representation Independent Hash`]: https://internetcomputer.org/docs/current/references/ic-interface-spec/#hash-of-map
 pub fn representation_independent_hash(map: &[(String, Value)]) -> Sha256Digest {
-    let mut hashes: Vec<_> = map
+    let hashes = map
         .iter()
         .map(|(key, value)| (hash(key.as_bytes()), hash_value(value)))
         .collect();
 
+    hash_of_map(hashes)
+}
+
+/// Calculates the hash of a map from the hashes of its keys and values.
+pub(crate) fn hash_of_map(mut hashes: Vec<(Sha256Digest, Sha256Digest)>) -> Sha256Digest {
     hashes.sort_unstable();
 
     let mut hasher = Sha256::new();
@@ -33,15 +42,26 @@ pub fn representation_independent_hash(map: &[(String, Value)]) -> Sha256Digest
     hasher.finalize().into()
 }
 
+/// Calculates the hash of an unsigned number from its LEB128 encoding.
+pub(crate) fn hash_number(value: u64) -> Sha256Digest {
+    let mut hasher = Sha256::new();
+    leb128::write::unsigned(&mut hasher, value).unwrap();
+    hasher.finalize().into()
+}
+
 fn hash_value(value: &Value) -> Sha256Digest {
     match value {
         Value::String(value) => hash(value.as_bytes()),
         Value::Bytes(value) => hash(value),
-        Value::Number(value) => {
+        Value::Number(value) => hash_number(*value),
+        Value::Array(values) => {
             let mut hasher = Sha256::new();
-            leb128::write::unsigned(&mut hasher, value.to_owned()).unwrap();
+            for value in values.iter() {
+                hasher.update(hash_value(value));
+            }
             hasher.finalize().into()
         }
+        Value::Map(map) => representation_independent_hash(map),
     }
 }
 
@@ -112,4 +132,44 @@ mod tests {
 
         assert_eq!(result, expected_hash.as_slice());
     }
+
+    #[test]
+    fn hash_array() {
+        let map: Vec<(String, Value)> = vec![(
+            "array".into(),
+            Value::Array(vec![Value::String("foo".into()), Value::Number(42)]),
+        )];
+        let array_hash: Sha256Digest = Sha256::new()
+            .chain_update(hash("foo".as_bytes()))
+            .chain_update(hash_value(&Value::Number(42)))
+            .finalize()
+            .into();
+        let expected_hash: Sha256Digest = Sha256::new()
+            .chain_update(hash("array".as_bytes()))
+            .chain_update(array_hash)
+            .finalize()
+            .into();
+
+        let result = representation_independent_hash(&map);
+
+        assert_eq!(result, expected_hash);
+    }
+
+    #[test]
+    fn hash_nested_map() {
+        let nested_map: Vec<(String, Value)> = vec![
+            ("name".into(), Value::String("foo".into())),
+            ("answer".into(), Value::Number(42)),
+        ];
+        let map: Vec<(String, Value)> = vec![("map".into(), Value::Map(nested_map.clone()))];
+        let expected_hash: Sha256Digest = Sha256::new()
+            .chain_update(hash("map".as_bytes()))
+            .chain_update(representation_independent_hash(&nested_map))
+            .finalize()
+            .into();
+
+        let result = representation_independent_hash(&map);
+
+        assert_eq!(result, expected_hash);
+    }
 }
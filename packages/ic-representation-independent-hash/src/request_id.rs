@@ -0,0 +1,236 @@
+use crate::{representation_independent_hash, Sha256Digest, Value};
+
+/// The content of a request envelope, as described in the
+/// [Interface Specification](https://internetcomputer.org/docs/current/references/ic-interface-spec/#http-interface).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestContent {
+    /// The content of an update call to a canister.
+    Call {
+        /// The ID of the canister that is called.
+        canister_id: Vec<u8>,
+        /// The name of the canister method that is called.
+        method_name: String,
+        /// The Candid encoded argument of the call.
+        arg: Vec<u8>,
+        /// The principal of the sender of the call.
+        sender: Vec<u8>,
+        /// The time after which the call is no longer accepted, in nanoseconds since the UNIX epoch.
+        ingress_expiry: u64,
+        /// An optional nonce, to send otherwise identical calls more than once.
+        nonce: Option<Vec<u8>>,
+    },
+    /// The content of a query call to a canister.
+    Query {
+        /// The ID of the canister that is queried.
+        canister_id: Vec<u8>,
+        /// The name of the canister method that is queried.
+        method_name: String,
+        /// The Candid encoded argument of the query.
+        arg: Vec<u8>,
+        /// The principal of the sender of the query.
+        sender: Vec<u8>,
+        /// The time after which the query is no longer accepted, in nanoseconds since the UNIX
+        /// epoch.
+        ingress_expiry: u64,
+        /// An optional nonce, to send otherwise identical queries more than once.
+        nonce: Option<Vec<u8>>,
+    },
+    /// The content of a request to read the state tree.
+    ReadState {
+        /// The principal of the sender of the request.
+        sender: Vec<u8>,
+        /// The time after which the request is no longer accepted, in nanoseconds since the UNIX
+        /// epoch.
+        ingress_expiry: u64,
+        /// The paths of the state tree that are requested.
+        paths: Vec<Vec<Vec<u8>>>,
+        /// An optional nonce, to send otherwise identical requests more than once.
+        nonce: Option<Vec<u8>>,
+    },
+}
+
+impl RequestContent {
+    /// Returns the fields of the request content, as they are hashed to calculate its request ID.
+    pub fn to_map(&self) -> Vec<(String, Value)> {
+        let (request_type, mut map, nonce) = match self {
+            RequestContent::Call {
+                canister_id,
+                method_name,
+                arg,
+                sender,
+                ingress_expiry,
+                nonce,
+            } => (
+                "call",
+                canister_method_map(canister_id, method_name, arg, sender, *ingress_expiry),
+                nonce,
+            ),
+            RequestContent::Query {
+                canister_id,
+                method_name,
+                arg,
+                sender,
+                ingress_expiry,
+                nonce,
+            } => (
+                "query",
+                canister_method_map(canister_id, method_name, arg, sender, *ingress_expiry),
+                nonce,
+            ),
+            RequestContent::ReadState {
+                sender,
+                ingress_expiry,
+                paths,
+                nonce,
+            } => {
+                let paths = paths
+                    .iter()
+                    .map(|path| Value::Array(path.iter().cloned().map(Value::Bytes).collect()))
+                    .collect();
+
+                (
+                    "read_state",
+                    vec![
+                        ("sender".into(), Value::Bytes(sender.clone())),
+                        ("ingress_expiry".into(), Value::Number(*ingress_expiry)),
+                        ("paths".into(), Value::Array(paths)),
+                    ],
+                    nonce,
+                )
+            }
+        };
+
+        map.push(("request_type".into(), Value::String(request_type.into())));
+        if let Some(nonce) = nonce {
+            map.push(("nonce".into(), Value::Bytes(nonce.clone())));
+        }
+
+        map
+    }
+}
+
+fn canister_method_map(
+    canister_id: &[u8],
+    method_name: &str,
+    arg: &[u8],
+    sender: &[u8],
+    ingress_expiry: u64,
+) -> Vec<(String, Value)> {
+    vec![
+        ("canister_id".into(), Value::Bytes(canister_id.to_vec())),
+        ("method_name".into(), Value::String(method_name.into())),
+        ("arg".into(), Value::Bytes(arg.to_vec())),
+        ("sender".into(), Value::Bytes(sender.to_vec())),
+        ("ingress_expiry".into(), Value::Number(ingress_expiry)),
+    ]
+}
+
+/// Calculates the [`Request ID`] of the request content, which is the representation independent
+/// hash of its fields.
+///
+/// [`Request ID`]: https://internetcomputer.org/docs/current/references/ic-interface-spec/#request-id
+pub fn request_id(content: &RequestContent) -> Sha256Digest {
+    representation_independent_hash(&content.to_map())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+    use sha2::{Digest, Sha256};
+
+    const ANONYMOUS_SENDER: &[u8] = &[0x04];
+    const INGRESS_EXPIRY: u64 = 1_685_570_400_000_000_000;
+
+    fn create_call(nonce: Option<Vec<u8>>) -> RequestContent {
+        RequestContent::Call {
+            canister_id: vec![0, 0, 0, 0, 0, 0, 0x04, 0xd2],
+            method_name: "hello".into(),
+            arg: b"DIDL\x00\xfd*".to_vec(),
+            sender: ANONYMOUS_SENDER.to_vec(),
+            ingress_expiry: INGRESS_EXPIRY,
+            nonce,
+        }
+    }
+
+    #[test]
+    fn call_request_id() {
+        let expected_request_id =
+            hex::decode("1d1091364d6bb8a6c16b203ee75467d59ead468f523eb058880ae8ec80e2b101")
+                .unwrap();
+
+        let result = request_id(&create_call(None));
+
+        assert_eq!(result, expected_request_id.as_slice());
+    }
+
+    #[test]
+    fn request_id_depends_on_request_type_and_nonce() {
+        let RequestContent::Call {
+            canister_id,
+            method_name,
+            arg,
+            sender,
+            ingress_expiry,
+            nonce,
+        } = create_call(None) else {
+            unreachable!()
+        };
+        let query = RequestContent::Query {
+            canister_id,
+            method_name,
+            arg,
+            sender,
+            ingress_expiry,
+            nonce,
+        };
+
+        let call_request_id = request_id(&create_call(None));
+
+        assert_ne!(request_id(&query), call_request_id);
+        assert_ne!(request_id(&create_call(Some(vec![1]))), call_request_id);
+    }
+
+    #[test]
+    fn read_state_request_id() {
+        let content = RequestContent::ReadState {
+            sender: ANONYMOUS_SENDER.to_vec(),
+            ingress_expiry: INGRESS_EXPIRY,
+            paths: vec![vec![b"time".to_vec()], vec![b"subnet".to_vec(), vec![1, 2]]],
+            nonce: None,
+        };
+        let path_hash = |path: &[&[u8]]| -> Sha256Digest {
+            path.iter()
+                .fold(Sha256::new(), |hasher, label| {
+                    hasher.chain_update(hash(label))
+                })
+                .finalize()
+                .into()
+        };
+        let paths_hash: Sha256Digest = Sha256::new()
+            .chain_update(path_hash(&[b"time"]))
+            .chain_update(path_hash(&[b"subnet", &[1, 2]]))
+            .finalize()
+            .into();
+        let mut ingress_expiry = vec![];
+        leb128::write::unsigned(&mut ingress_expiry, INGRESS_EXPIRY).unwrap();
+        let mut fields = [
+            (hash(b"request_type"), hash(b"read_state")),
+            (hash(b"sender"), hash(ANONYMOUS_SENDER)),
+            (hash(b"ingress_expiry"), hash(&ingress_expiry)),
+            (hash(b"paths"), paths_hash),
+        ];
+        fields.sort_unstable();
+        let expected_request_id: Sha256Digest = fields
+            .iter()
+            .fold(Sha256::new(), |hasher, (key_hash, value_hash)| {
+                hasher.chain_update(key_hash).chain_update(value_hash)
+            })
+            .finalize()
+            .into();
+
+        let result = request_id(&content);
+
+        assert_eq!(result, expected_request_id);
+    }
+}
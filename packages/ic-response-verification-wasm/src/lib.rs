@@ -1,9 +1,12 @@
 use crate::request::request_from_js;
 use crate::response::response_from_js;
+use ic_certificate_verification::{NoSignatureCache, SizedSignatureCache};
 use ic_response_verification::{
-    types::VerificationInfo, verify_request_response_pair as verify_request_response_pair_impl,
-    ResponseVerificationJsError, MAX_VERIFICATION_VERSION, MIN_VERIFICATION_VERSION,
+    types::VerificationInfo, Clock, ResponseVerificationJsError,
+    ResponseVerifier as ResponseVerifierImpl, VerifierConfig, MAX_VERIFICATION_VERSION,
+    MIN_VERIFICATION_VERSION,
 };
+use std::sync::Arc;
 use wasm_bindgen::{prelude::*, JsCast};
 
 mod request;
@@ -38,8 +41,93 @@ pub fn main_js() {
     log::set_max_level(log::LevelFilter::Trace);
 }
 
-/// The primary entry point for verifying a request and response pair. This will verify the response
-/// with respect to the request, according the [Response Verification Spec]().
+/// A [Clock] that reads the current time from the JavaScript host environment.
+struct DateClock;
+
+impl Clock for DateClock {
+    fn current_time_ns(&self) -> u128 {
+        (js_sys::Date::now() * 1_000_000.0) as u128
+    }
+}
+
+/// Verifies request and response pairs against a configuration that is created once, reading the
+/// current time from the JavaScript host environment.
+#[wasm_bindgen(js_name = ResponseVerifier)]
+pub struct JsResponseVerifier {
+    verifier: ResponseVerifierImpl,
+}
+
+#[wasm_bindgen(js_class = ResponseVerifier)]
+impl JsResponseVerifier {
+    /// Creates a verifier for the provided root key. The maximum certificate time offset, the
+    /// minimum requested verification version, the limits on decoding response bodies and the
+    /// size of the signature cache fall back to their defaults when not provided. A signature
+    /// cache size of zero disables the signature cache.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        ic_public_key: &[u8],
+        max_cert_time_offset_ns: Option<u64>,
+        min_requested_verification_version: Option<u8>,
+        max_decoded_body_size: Option<usize>,
+        max_body_compression_ratio: Option<u32>,
+        signature_cache_size: Option<usize>,
+    ) -> JsResponseVerifier {
+        let mut config = VerifierConfig::new(ic_public_key, DateClock);
+
+        if let Some(max_cert_time_offset_ns) = max_cert_time_offset_ns {
+            config.with_max_cert_time_offset_ns(max_cert_time_offset_ns as u128);
+        }
+
+        if let Some(min_requested_verification_version) = min_requested_verification_version {
+            config.with_min_requested_verification_version(min_requested_verification_version);
+        }
+
+        if let Some(max_decoded_body_size) = max_decoded_body_size {
+            config.with_max_decoded_body_size(max_decoded_body_size);
+        }
+
+        if let Some(max_body_compression_ratio) = max_body_compression_ratio {
+            config.with_max_body_compression_ratio(max_body_compression_ratio);
+        }
+
+        match signature_cache_size {
+            Some(0) => {
+                config.with_signature_cache(Arc::new(NoSignatureCache));
+            }
+            Some(signature_cache_size) => {
+                config
+                    .with_signature_cache(Arc::new(SizedSignatureCache::new(signature_cache_size)));
+            }
+            None => {}
+        }
+
+        JsResponseVerifier {
+            verifier: config.build(),
+        }
+    }
+
+    /// Verifies the response with respect to the request, for the canister with the provided ID.
+    pub fn verify(
+        &self,
+        request: JsRequest,
+        response: JsResponse,
+        canister_id: &[u8],
+    ) -> Result<JsVerificationInfo, ResponseVerificationJsError> {
+        let request = request_from_js(JsValue::from(request));
+        let response = response_from_js(JsValue::from(response));
+
+        self.verifier
+            .verify(request, response, canister_id)
+            .map(|verification_result| {
+                JsValue::from(VerificationInfo::from(verification_result))
+                    .unchecked_into::<JsVerificationInfo>()
+            })
+            .map_err(ResponseVerificationJsError::from)
+    }
+}
+
+/// Verifies a request and response pair. This will verify the response with respect to the request,
+/// according the [Response Verification Spec]().
 #[wasm_bindgen(js_name = verifyRequestResponsePair)]
 pub fn verify_request_response_pair(
     request: JsRequest,
@@ -50,21 +138,10 @@ pub fn verify_request_response_pair(
     ic_public_key: &[u8],
     min_requested_verification_version: u8,
 ) -> Result<JsVerificationInfo, ResponseVerificationJsError> {
-    let request = request_from_js(JsValue::from(request));
-    let response = response_from_js(JsValue::from(response));
-
-    verify_request_response_pair_impl(
-        request.into(),
-        response.into(),
-        canister_id,
-        current_time_ns as u128,
-        max_cert_time_offset_ns as u128,
-        ic_public_key,
-        min_requested_verification_version,
-    )
-    .map(|verification_result| {
-        JsValue::from(VerificationInfo::from(verification_result))
-            .unchecked_into::<JsVerificationInfo>()
-    })
-    .map_err(|e| ResponseVerificationJsError::from(e))
+    let verifier = VerifierConfig::new(ic_public_key, move || current_time_ns as u128)
+        .with_max_cert_time_offset_ns(max_cert_time_offset_ns as u128)
+        .with_min_requested_verification_version(min_requested_verification_version)
+        .build();
+
+    JsResponseVerifier { verifier }.verify(request, response, canister_id)
 }
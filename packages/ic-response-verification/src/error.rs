@@ -2,6 +2,8 @@
 
 use ic_cbor::CborError;
 use ic_certificate_verification::CertificateVerificationError;
+use ic_certification::MalformedHashTreeError;
+use std::fmt;
 #[cfg(all(target_arch = "wasm32", feature = "js"))]
 use wasm_bindgen::prelude::*;
 
@@ -73,6 +75,10 @@ pub enum ResponseVerificationError {
     #[error("Tree not found")]
     MissingTree,
 
+    /// The tree or the certificate's tree from the certification header is not well formed
+    #[error(r#"Malformed tree: "{0}""#)]
+    MalformedTree(#[from] MalformedHashTreeError),
+
     /// The certificate expression path was missing from the certification header
     #[error("Certificate expression path not found")]
     MissingCertificateExpressionPath,
@@ -96,6 +102,37 @@ pub enum ResponseVerificationError {
     /// HTTP Certification error
     #[error(r#"HTTP Certification error: "{0}""#)]
     HttpCertificationError(#[from] ic_http_certification::HttpCertificationError),
+
+    /// The decoded response body exceeded the maximum decoded size or compression ratio
+    #[error(
+        r#"The decoded response body exceeded the limit of {max_decoded_size:?} bytes set by the {limit}"#
+    )]
+    DecodedBodyTooLarge {
+        /// The maximum number of bytes that the response body was allowed to decode to
+        max_decoded_size: usize,
+        /// The configured limit that `max_decoded_size` was derived from
+        limit: DecodedBodyLimit,
+    },
+}
+
+/// The configured limit that a decoded response body exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodedBodyLimit {
+    /// The maximum decoded size of the response body.
+    MaxDecodedSize,
+    /// The maximum ratio between the decoded and the encoded size of the response body.
+    MaxCompressionRatio(u32),
+}
+
+impl fmt::Display for DecodedBodyLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodedBodyLimit::MaxDecodedSize => write!(f, "maximum decoded size"),
+            DecodedBodyLimit::MaxCompressionRatio(ratio) => {
+                write!(f, "maximum compression ratio of {}", ratio)
+            }
+        }
+    }
 }
 
 /// JS Representation of the ResponseVerificationError code
@@ -127,6 +164,8 @@ pub enum ResponseVerificationJsErrorCode {
     MissingCertificate,
     /// The tree was missing from the certification header
     MissingTree,
+    /// The tree or the certificate's tree from the certification header is not well formed
+    MalformedTree,
     /// The certificate expression path was missing from the certification header
     MissingCertificateExpressionPath,
     /// The certificate expression was missing from the response headers
@@ -139,6 +178,8 @@ pub enum ResponseVerificationJsErrorCode {
     CertificateVerificationFailed,
     /// HTTP Certification error
     HttpCertificationError,
+    /// The decoded response body exceeded the maximum decoded size or compression ratio
+    DecodedBodyTooLarge,
 }
 
 /// JS Representation of the ResponseVerificationError
@@ -187,6 +228,9 @@ impl From<ResponseVerificationError> for ResponseVerificationJsError {
                 ResponseVerificationJsErrorCode::MissingCertificate
             }
             ResponseVerificationError::MissingTree => ResponseVerificationJsErrorCode::MissingTree,
+            ResponseVerificationError::MalformedTree(_) => {
+                ResponseVerificationJsErrorCode::MalformedTree
+            }
             ResponseVerificationError::MissingCertificateExpressionPath => {
                 ResponseVerificationJsErrorCode::MissingCertificateExpressionPath
             }
@@ -205,6 +249,9 @@ impl From<ResponseVerificationError> for ResponseVerificationJsError {
             ResponseVerificationError::HttpCertificationError(_) => {
                 ResponseVerificationJsErrorCode::HttpCertificationError
             }
+            ResponseVerificationError::DecodedBodyTooLarge { .. } => {
+                ResponseVerificationJsErrorCode::DecodedBodyTooLarge
+            }
         };
         let message = error.to_string();
 
@@ -444,6 +491,20 @@ mod tests {
         )
     }
 
+    #[wasm_bindgen_test]
+    fn error_into_malformed_tree_error() {
+        let error = ResponseVerificationError::MalformedTree(MalformedHashTreeError::LeafInFork);
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::MalformedTree,
+                message: format!(r#"Malformed tree: "Fork contains a leaf""#),
+            }
+        )
+    }
+
     #[wasm_bindgen_test]
     fn error_into_invalid_missing_tree_error() {
         let error = ResponseVerificationError::MissingTree;
@@ -533,4 +594,23 @@ mod tests {
             }
         )
     }
+
+    #[wasm_bindgen_test]
+    fn error_into_decoded_body_too_large_error() {
+        let error = ResponseVerificationError::DecodedBodyTooLarge {
+            max_decoded_size: 1024,
+            limit: DecodedBodyLimit::MaxCompressionRatio(10),
+        };
+        let result = ResponseVerificationJsError::from(error);
+
+        assert_eq!(
+            result,
+            ResponseVerificationJsError {
+                code: ResponseVerificationJsErrorCode::DecodedBodyTooLarge,
+                message: format!(
+                    r#"The decoded response body exceeded the limit of 1024 bytes set by the maximum compression ratio of 10"#
+                ),
+            }
+        )
+    }
 }
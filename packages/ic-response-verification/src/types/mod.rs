@@ -7,3 +7,7 @@ pub use verification_result::*;
 /// Types to represent a certified response that clients can use to determine which parts of a response are safe to use.
 mod verified_response;
 pub use verified_response::*;
+
+/// Types to represent a detailed report of the stages of verifying a request/response pair.
+mod verification_report;
+pub use verification_report::*;
@@ -0,0 +1,151 @@
+use crate::{error::ResponseVerificationResult, types::VerificationInfo};
+use std::fmt;
+
+/// A stage of response verification that is recorded in a [VerificationReport].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationStage {
+    /// Parsing the `IC-Certificate` and `IC-CertificateExpression` headers.
+    HeaderParse,
+    /// Checking that the time of the certificate is within the allowed offset of the current time.
+    CertificateTime,
+    /// Verifying the signature of the certificate, and its delegation if present.
+    CertificateSignature,
+    /// Checking that the certificate is not older than the newest certificate that was already
+    /// seen for the canister, when a freshness tracker is configured.
+    CertificateFreshness,
+    /// Checking that the root hash of the tree matches the certified data of the canister.
+    TreeRoot,
+    /// Searching the tree for the expression path, and for more specific paths that would
+    /// take precedence over it.
+    ExprPathSearch,
+    /// Comparing the hashes calculated from the request and response with the hashes in the tree.
+    HashComparison,
+}
+
+impl fmt::Display for VerificationStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VerificationStage::HeaderParse => "header parse",
+            VerificationStage::CertificateTime => "certificate time",
+            VerificationStage::CertificateSignature => "certificate signature",
+            VerificationStage::CertificateFreshness => "certificate freshness",
+            VerificationStage::TreeRoot => "tree root",
+            VerificationStage::ExprPathSearch => "expression path search",
+            VerificationStage::HashComparison => "hash comparison",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// The outcome of a [VerificationStage].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationStageOutcome {
+    /// The stage passed.
+    Passed,
+    /// The stage failed, with the message of the error that verification failed with.
+    Failed(String),
+}
+
+/// The record of a single [VerificationStage], with the inputs that it was run with and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationStageRecord {
+    /// The stage that was run.
+    pub stage: VerificationStage,
+    /// The inputs of the stage, as pairs of names and human readable values. Hashes and other
+    /// binary values are hex encoded.
+    pub inputs: Vec<(String, String)>,
+    /// The outcome of the stage.
+    pub outcome: VerificationStageOutcome,
+}
+
+/// A detailed report of verifying a request and response pair, created by
+/// [ResponseVerifier::verify_with_report](crate::ResponseVerifier::verify_with_report).
+///
+/// The report records every stage that was run, in order, up to and including the stage that
+/// failed, if any. Its [Display](fmt::Display) implementation prints a human readable summary.
+#[derive(Debug)]
+pub struct VerificationReport {
+    /// The stages that were run.
+    pub stages: Vec<VerificationStageRecord>,
+    /// The result of verification, the same as it would be returned by
+    /// [ResponseVerifier::verify](crate::ResponseVerifier::verify).
+    pub result: ResponseVerificationResult<VerificationInfo>,
+}
+
+impl VerificationReport {
+    /// Returns the record of the stage that failed, if any.
+    pub fn failed_stage(&self) -> Option<&VerificationStageRecord> {
+        self.stages
+            .iter()
+            .find(|record| matches!(record.outcome, VerificationStageOutcome::Failed(_)))
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for record in &self.stages {
+            match &record.outcome {
+                VerificationStageOutcome::Passed => writeln!(f, "[passed] {}", record.stage)?,
+                VerificationStageOutcome::Failed(error) => {
+                    writeln!(f, "[failed] {}: {}", record.stage, error)?
+                }
+            }
+
+            for (name, value) in &record.inputs {
+                writeln!(f, "    {}: {}", name, value)?;
+            }
+        }
+
+        match &self.result {
+            Ok(verification_info) => write!(
+                f,
+                "Verification passed with version {}",
+                verification_info.verification_version
+            ),
+            Err(error) => write!(f, "Verification failed: {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ResponseVerificationError;
+
+    #[test]
+    fn display_report() {
+        let report = VerificationReport {
+            stages: vec![
+                VerificationStageRecord {
+                    stage: VerificationStage::TreeRoot,
+                    inputs: vec![("tree root hash".into(), "abcd".into())],
+                    outcome: VerificationStageOutcome::Passed,
+                },
+                VerificationStageRecord {
+                    stage: VerificationStage::ExprPathSearch,
+                    inputs: vec![
+                        ("request path".into(), "/app".into()),
+                        ("expression path".into(), "[\"http_expr\", \"<*>\"]".into()),
+                    ],
+                    outcome: VerificationStageOutcome::Failed("Invalid expression path".into()),
+                },
+            ],
+            result: Err(ResponseVerificationError::InvalidExpressionPath),
+        };
+
+        assert_eq!(
+            report.failed_stage().map(|record| record.stage),
+            Some(VerificationStage::ExprPathSearch)
+        );
+        assert_eq!(
+            report.to_string(),
+            r#"[passed] tree root
+    tree root hash: abcd
+[failed] expression path search: Invalid expression path
+    request path: /app
+    expression path: ["http_expr", "<*>"]
+Verification failed: Invalid expression path"#
+        );
+    }
+}
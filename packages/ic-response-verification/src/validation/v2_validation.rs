@@ -1,7 +1,6 @@
 use ic_certification::hash_tree::HashTreeNode;
 use ic_certification::{hash_tree::Hash, HashTree, Label, SubtreeLookupResult};
-use ic_http_certification::cel::DefaultCelExpression;
-use ic_http_certification::CelExpression;
+use ic_http_certification::HttpCertificationPath;
 
 fn path_from_parts<T>(parts: &[T]) -> Vec<Label>
 where
@@ -14,114 +13,78 @@ where
         .collect()
 }
 
-fn path_might_exist_in_tree(path: &[Label], tree: &HashTree) -> bool {
-    !matches!(tree.lookup_subtree(path), SubtreeLookupResult::Absent)
+fn path_might_exist_in_tree(path: &HttpCertificationPath, tree: &HashTree) -> bool {
+    !matches!(
+        tree.lookup_subtree(&path_from_parts(&path.to_tree_path())),
+        SubtreeLookupResult::Absent
+    )
 }
 
-fn path_exists_in_tree(path: &[Label], tree: &HashTree) -> bool {
-    matches!(tree.lookup_subtree(path), SubtreeLookupResult::Found(_))
-}
-
-fn is_wildcard_path_valid_for_request_path(
-    wildcard_path: &[Label],
-    request_path: &[Label],
-) -> bool {
-    if request_path.starts_with(wildcard_path) {
-        return true;
-    }
-
-    if wildcard_path.ends_with(&["".into()]) {
-        return request_path.starts_with(&wildcard_path[..wildcard_path.len() - 1]);
-    }
-
-    false
-}
-
-fn expr_path_has_valid_suffix(expr_path: &[String]) -> bool {
-    expr_path.ends_with(&["<$>".to_string()]) || expr_path.ends_with(&["<*>".to_string()])
+fn path_exists_in_tree(path: &HttpCertificationPath, tree: &HashTree) -> bool {
+    matches!(
+        tree.lookup_subtree(&path_from_parts(&path.to_tree_path())),
+        SubtreeLookupResult::Found(_)
+    )
 }
 
 pub fn validate_expr_path(expr_path: &[String], request_path: &str, tree: &HashTree) -> bool {
-    // if a path does not end with a valid delimiter then it is invalid
-    if !expr_path_has_valid_suffix(expr_path) {
+    // if a path does not begin with `http_expr` or does not end with a valid delimiter,
+    // then it is invalid
+    let Ok(expr_path) = HttpCertificationPath::from_expr_path(expr_path) else {
         return false;
-    }
-
-    let mut request_url_parts = vec!["http_expr"];
-    request_url_parts.extend(request_path.split('/').filter(|e| !e.is_empty()));
-
-    // make sure to treat a request for a directory and a file as different paths
-    // i.e. /app is not the same as /app/
-    // we do this by inserting an empty space for directory paths
-    if request_path.ends_with('/') {
-        request_url_parts.push("");
-    }
-
-    let original_path = path_from_parts(expr_path);
-    let mut potential_path = path_from_parts(expr_path);
-    let mut request_url_path = path_from_parts(&request_url_parts);
-
-    // if the expr_path matches the full URL, there can't be a more precise path in the tree
-    request_url_path.push("<$>".into());
-    if potential_path.eq(&request_url_path) {
-        return path_exists_in_tree(&original_path, tree);
-    }
+    };
 
-    // at this point there are no more valid exact paths,
-    // so validation fails if the certified_path ends with an exact path suffix,
-    if potential_path.ends_with(&[Label::from("<$>")]) {
+    // validation fails if the expr_path is not valid for the request path
+    let Ok(more_specific_paths) = expr_path.more_specific_paths(request_path) else {
         return false;
-    }
+    };
 
-    // validation fails if the expr_path does not match full URL and the full URL exists in the tree
-    if path_might_exist_in_tree(&request_url_path, tree) {
+    // validation fails if a path that is more precise than the expr_path might exist in the tree
+    if more_specific_paths
+        .iter()
+        .any(|path| path_might_exist_in_tree(path, tree))
+    {
         return false;
     }
-    request_url_path.pop(); // pop "<$>"
 
-    // if the expr_path matches the full URL with a wildcard
-    // there can't be a more precise path in the tree
-    request_url_path.push("<*>".into());
-    if potential_path.eq(&request_url_path) {
-        return path_exists_in_tree(&original_path, tree);
-    }
-    request_url_path.pop(); // pop "<*>"
-    potential_path.pop(); // pop "<*>"
-
-    if !is_wildcard_path_valid_for_request_path(&potential_path, &request_url_path) {
-        return false;
-    }
+    // if we haven't found a more specific path in the tree,
+    // then the provided path is valid if it exists in the tree
+    path_exists_in_tree(&expr_path, tree)
+}
 
-    // recursively check for partial URL matches with wildcards that are more precise than the expr_path
-    while request_url_path.len() > potential_path.len()
-        || request_url_path.last() != potential_path.last()
-    {
-        // check wildcard
-        request_url_path.push("<*>".into());
-        if path_might_exist_in_tree(&request_url_path, tree) {
-            return false;
-        }
-        request_url_path.pop(); // pop "<*>"
+/// Returns the paths that are more specific than the `expr_path` for the request path and that
+/// might exist in the tree, any of which would take precedence over the `expr_path`.
+pub fn find_more_specific_expr_paths(
+    expr_path: &HttpCertificationPath,
+    request_path: &str,
+    tree: &HashTree,
+) -> Vec<HttpCertificationPath> {
+    expr_path
+        .more_specific_paths(request_path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| path_might_exist_in_tree(path, tree))
+        .collect()
+}
 
-        if request_url_path.ends_with(&[Label::from("")]) {
-            request_url_path.pop(); // pop empty string
-        } else {
-            request_url_path.pop(); // pop the last segment of the path
-            request_url_path.push("".into()); // append empty string to check trailing slash
-        }
+/// Returns the certifications that exist in the tree for the `expr_path`, as paths of
+/// expression hash, request hash and response hash labels.
+pub fn find_expr_path_certifications(
+    expr_path: &HttpCertificationPath,
+    tree: &HashTree,
+) -> Vec<Vec<Label>> {
+    match tree.lookup_subtree(&path_from_parts(&expr_path.to_tree_path())) {
+        SubtreeLookupResult::Found(expr_path_tree) => expr_path_tree.list_paths(),
+        _ => vec![],
     }
-
-    // if we haven't found a more specific path in the tree,
-    // then the provided path is valid if it exists in the tree
-    path_exists_in_tree(&original_path, tree)
 }
 
 pub fn validate_expr_hash(
-    expr_path: &[String],
+    expr_path: &HttpCertificationPath,
     expr_hash: &Hash,
     tree: &HashTree,
 ) -> Option<HashTree> {
-    let mut path = path_from_parts(expr_path);
+    let mut path = path_from_parts(&expr_path.to_tree_path());
     path.push(expr_hash.into());
 
     match tree.lookup_subtree(&path) {
@@ -130,22 +93,21 @@ pub fn validate_expr_hash(
     }
 }
 
+/// Validates the hashes of a response against the tree. The request hash must only be provided if
+/// the request is included in certification.
 pub fn validate_hashes(
     expr_hash: &Hash,
     request_hash: &Option<Hash>,
     response_hash: &Hash,
-    expr_path: &[String],
+    expr_path: &HttpCertificationPath,
     tree: &HashTree,
-    certification: &CelExpression,
 ) -> bool {
     let Some(expr_tree) = validate_expr_hash(expr_path, expr_hash, tree) else {
         return false;
     };
 
     let mut expr_tree_path: Vec<Label> = vec![];
-    if let (CelExpression::Default(DefaultCelExpression::Full(_)), Some(request_hash)) =
-        (&certification, request_hash)
-    {
+    if let Some(request_hash) = request_hash {
         expr_tree_path.push(request_hash.into());
     } else {
         expr_tree_path.push("".into());
@@ -165,13 +127,9 @@ mod tests {
     use super::*;
     use crate::test_utils::test_utils::{create_pruned, remove_whitespace, sha256_from_hex};
     use ic_certification::hash_tree::{fork, label, leaf};
-    use ic_http_certification::{
-        cel::{DefaultFullCelExpression, DefaultRequestCertification},
-        DefaultResponseCertification,
-    };
+    use ic_http_certification::{Certification, HttpCertificationTree, HttpCertificationTreeEntry};
     use ic_representation_independent_hash::hash;
     use ic_response_verification_test_utils::hex_decode;
-    use std::borrow::Cow;
 
     const REQUEST_HASH: &str = "5fac69685533f0650991441a2b818e8ad5ab2fec51de8cfdbea1276135494815";
     const RESPONSE_HASH: &str = "07b7c729f4083db0e266fef3f8f5acf1315135605bf38884c07ebb59fbf91ce8";
@@ -200,6 +158,52 @@ mod tests {
         )
     "#;
 
+    fn expr_path_from(expr_path: &[String]) -> HttpCertificationPath {
+        HttpCertificationPath::from_expr_path(expr_path).unwrap()
+    }
+
+    #[test]
+    fn http_certification_path_matches_expr_path_labels() {
+        let cases: Vec<(HttpCertificationPath, Vec<String>)> = vec![
+            (
+                HttpCertificationPath::exact("/assets/js/app.js"),
+                vec![
+                    "http_expr".into(),
+                    "assets".into(),
+                    "js".into(),
+                    "app.js".into(),
+                    "<$>".into(),
+                ],
+            ),
+            (
+                HttpCertificationPath::exact("/app/"),
+                vec!["http_expr".into(), "app".into(), "".into(), "<$>".into()],
+            ),
+            (
+                HttpCertificationPath::wildcard("/assets/js"),
+                vec![
+                    "http_expr".into(),
+                    "assets".into(),
+                    "js".into(),
+                    "<*>".into(),
+                ],
+            ),
+            (
+                HttpCertificationPath::wildcard("/app/"),
+                vec!["http_expr".into(), "app".into(), "".into(), "<*>".into()],
+            ),
+            (
+                HttpCertificationPath::wildcard("/"),
+                vec!["http_expr".into(), "".into(), "<*>".into()],
+            ),
+        ];
+
+        for (path, expr_path) in cases {
+            assert_eq!(path.to_expr_path(), expr_path);
+            assert_eq!(expr_path_from(&expr_path), path);
+        }
+    }
+
     #[test]
     fn validate_hashes_that_exist() {
         let expr_hash = hash(remove_whitespace(CEL_EXPRESSION).as_bytes());
@@ -228,15 +232,13 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
             &Some(request_hash),
             &response_hash,
-            &expr_path,
+            &expr_path_from(&expr_path),
             &tree,
-            &certification,
         );
 
         assert!(result);
@@ -276,15 +278,13 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
             &Some(request_hash),
             &response_hash,
-            &expr_path,
+            &expr_path_from(&expr_path),
             &tree,
-            &certification,
         );
 
         assert!(!result);
@@ -324,15 +324,13 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
             &Some(request_hash),
             &response_hash,
-            &expr_path,
+            &expr_path_from(&expr_path),
             &tree,
-            &certification,
         );
 
         assert!(!result);
@@ -366,15 +364,13 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
             &Some(request_hash),
             &response_hash,
-            &expr_path,
+            &expr_path_from(&expr_path),
             &tree,
-            &certification,
         );
 
         assert!(!result);
@@ -408,15 +404,13 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
             &Some(request_hash),
             &response_hash,
-            &expr_path,
+            &expr_path_from(&expr_path),
             &tree,
-            &certification,
         );
 
         assert!(!result);
@@ -450,15 +444,13 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
             &Some(request_hash),
             &response_hash,
-            &expr_path,
+            &expr_path_from(&expr_path),
             &tree,
-            &certification,
         );
 
         assert!(!result);
@@ -492,15 +484,13 @@ mod tests {
             ),
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
-        let certification = create_certification();
 
         let result = validate_hashes(
             &expr_hash,
             &Some(request_hash),
             &response_hash,
-            &expr_path,
+            &expr_path_from(&expr_path),
             &tree,
-            &certification,
         );
 
         assert!(!result);
@@ -530,7 +520,7 @@ mod tests {
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
 
-        let result = validate_expr_hash(&expr_path, &expr_hash, &tree);
+        let result = validate_expr_hash(&expr_path_from(&expr_path), &expr_hash, &tree);
 
         assert_eq!(result, Some(leaf("")));
     }
@@ -570,9 +560,12 @@ mod tests {
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
 
-        let result = validate_expr_hash(&expr_path, &expr_hash, &tree);
-        let no_certification_result =
-            validate_expr_hash(&expr_path, &no_certification_expr_hash, &tree);
+        let result = validate_expr_hash(&expr_path_from(&expr_path), &expr_hash, &tree);
+        let no_certification_result = validate_expr_hash(
+            &expr_path_from(&expr_path),
+            &no_certification_expr_hash,
+            &tree,
+        );
 
         assert_eq!(result, Some(leaf("")));
         assert_eq!(no_certification_result, Some(leaf("")));
@@ -602,7 +595,7 @@ mod tests {
             create_pruned("ea7fd1a6b0cac1fe118016ca3026e58d5ae67a6965478acb561edba542732e24"),
         );
 
-        let result = validate_expr_hash(&expr_path, &expr_hash, &tree);
+        let result = validate_expr_hash(&expr_path_from(&expr_path), &expr_hash, &tree);
 
         assert!(result.is_none());
     }
@@ -852,6 +845,57 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn find_more_specific_expr_paths_that_might_exist() {
+        let expr_path = HttpCertificationPath::wildcard("/assets");
+        let tree = fork(
+            label(
+                "http_expr",
+                label("assets", label("js", label("<*>", leaf("")))),
+            ),
+            create_pruned("c01f7c0681a684be0a016b800981951832b53d5ffb55c49c27f6e83f7d2749c3"),
+        );
+
+        let result = find_more_specific_expr_paths(&expr_path, "/assets/js/app.js", &tree);
+
+        assert_eq!(result, vec![HttpCertificationPath::wildcard("/assets/js")]);
+    }
+
+    #[test]
+    fn find_expr_path_certifications_in_tree() {
+        let expr_path = HttpCertificationPath::exact("/assets/js/app.js");
+        let tree = label(
+            "http_expr",
+            label(
+                "assets",
+                label(
+                    "js",
+                    label(
+                        "app.js",
+                        label(
+                            "<$>",
+                            label("expr_hash", label("", label("response_hash", leaf("")))),
+                        ),
+                    ),
+                ),
+            ),
+        );
+
+        let result = find_expr_path_certifications(&expr_path, &tree);
+        let other_result =
+            find_expr_path_certifications(&HttpCertificationPath::wildcard("/assets"), &tree);
+
+        assert_eq!(
+            result,
+            vec![vec![
+                Label::from("expr_hash"),
+                Label::from(""),
+                Label::from("response_hash"),
+            ]]
+        );
+        assert!(other_result.is_empty());
+    }
+
     #[test]
     fn validate_expr_path_that_does_not_begin_with_http_expr() {
         let expr_path = vec![
@@ -892,6 +936,23 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn validate_wildcard_expr_path_with_trailing_slash_for_directory_request() {
+        let expr_path = vec!["http_expr".into(), "app".into(), "".into(), "<*>".into()];
+        let request_uri = http::Uri::try_from("https://dapp.com/app").unwrap();
+        let tree = label(
+            "http_expr",
+            fork(
+                label("", label("<$>", leaf(""))),
+                label("app", label("", label("<*>", leaf("")))),
+            ),
+        );
+
+        let result = validate_expr_path(&expr_path, request_uri.path(), &tree);
+
+        assert!(result);
+    }
+
     #[test]
     fn validate_various_expr_paths() {
         let tree_a_b_slash = fork(
@@ -984,7 +1045,7 @@ mod tests {
             ("/", vec!["<*>"], tree_star.clone()),
         ] {
             path.insert(0, "http_expr");
-            let expr_path: Vec<String> = path.iter().map(|x| x.to_string()).collect();
+            let expr_path: Vec<String> = path.into_iter().map(String::from).collect();
             let result = validate_expr_path(
                 &expr_path,
                 http::Uri::try_from(request_uri).unwrap().path(),
@@ -1037,7 +1098,7 @@ mod tests {
             ("/a/b", vec!["c", "b", "<*>"], tree_a_b_slash.clone()),
         ] {
             path.insert(0, "http_expr");
-            let expr_path: Vec<String> = path.iter().map(|x| x.to_string()).collect();
+            let expr_path: Vec<String> = path.into_iter().map(String::from).collect();
             let result = validate_expr_path(
                 &expr_path,
                 http::Uri::try_from(request_uri).unwrap().path(),
@@ -1047,16 +1108,48 @@ mod tests {
         }
     }
 
-    fn create_certification<'a>() -> CelExpression<'a> {
-        CelExpression::Default(DefaultCelExpression::Full(DefaultFullCelExpression {
-            request: DefaultRequestCertification {
-                headers: Cow::Borrowed(&["Host"]),
-                query_parameters: Cow::Borrowed(&[]),
-            },
-            response: DefaultResponseCertification::certified_response_headers(&[
-                "Accept-Encoding",
-                "Cache-Control",
-            ]),
-        }))
+    #[test]
+    fn validate_http_certification_tree_witnesses() {
+        let skip_certification = Certification::skip().unwrap();
+        let Certification::Skip { cel_expr_hash } = skip_certification else {
+            panic!("Expected skip certification");
+        };
+        let index_html_entry =
+            HttpCertificationTreeEntry::exact("/index.html", skip_certification.clone());
+        let app_entry = HttpCertificationTreeEntry::wildcard("/app/", skip_certification.clone());
+        let fallback_entry = HttpCertificationTreeEntry::wildcard("/", skip_certification.clone());
+        let mut http_certification_tree = HttpCertificationTree::default();
+        http_certification_tree.insert(&index_html_entry);
+        http_certification_tree.insert(&app_entry);
+        http_certification_tree.insert(&fallback_entry);
+
+        for (entry, request_path) in [
+            (&index_html_entry, "/index.html"),
+            (&app_entry, "/app/"),
+            (&app_entry, "/app/js/app.js"),
+            (&fallback_entry, "/"),
+            (&fallback_entry, "/app"),
+            (&fallback_entry, "/about/index.html"),
+        ] {
+            let tree = http_certification_tree
+                .witness(entry, request_path)
+                .unwrap();
+
+            assert!(validate_expr_path(
+                &entry.path().to_expr_path(),
+                request_path,
+                &tree
+            ));
+            assert!(validate_expr_hash(entry.path(), &cel_expr_hash, &tree).is_some());
+        }
+
+        let tree = http_certification_tree
+            .witness(&fallback_entry, "/index.html")
+            .unwrap();
+        assert!(!validate_expr_path(
+            &fallback_entry.path().to_expr_path(),
+            "/index.html",
+            &tree
+        ));
     }
 }
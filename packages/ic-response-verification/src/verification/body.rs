@@ -1,18 +1,110 @@
-use crate::error::ResponseVerificationResult;
+use crate::error::{DecodedBodyLimit, ResponseVerificationError, ResponseVerificationResult};
 use flate2::read::{DeflateDecoder, GzDecoder};
 use std::io::Read;
 
 const MAX_CHUNK_SIZE_TO_DECOMPRESS: usize = 1_024;
 
-pub fn decode_body(body: &Vec<u8>, encoding: Option<&str>) -> ResponseVerificationResult<Vec<u8>> {
-    return match encoding {
-        Some("gzip") => body_from_decoder(GzDecoder::new(body.as_slice())),
-        Some("deflate") => body_from_decoder(DeflateDecoder::new(body.as_slice())),
-        _ => Ok(body.to_owned()),
+/// A content coding that can be decoded, as listed in the `Content-Encoding` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentEncoding {
+    Gzip,
+    Deflate,
+    #[cfg(feature = "brotli")]
+    Brotli,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl ContentEncoding {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(ContentEncoding::Gzip),
+            "deflate" => Some(ContentEncoding::Deflate),
+            #[cfg(feature = "brotli")]
+            "br" => Some(ContentEncoding::Brotli),
+            #[cfg(feature = "zstd")]
+            "zstd" => Some(ContentEncoding::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes a body according to the value of its `Content-Encoding` header.
+///
+/// Multiple encodings are listed in the order in which they were applied, so they are decoded in
+/// reverse order. If any of the listed encodings is not supported, the body is returned unchanged.
+///
+/// Decoding fails as soon as any intermediate or final body grows larger than `max_decoded_size`
+/// bytes, or larger than `max_compression_ratio` times the size of the encoded body.
+pub fn decode_body(
+    body: &Vec<u8>,
+    encoding: Option<&str>,
+    max_decoded_size: usize,
+    max_compression_ratio: u32,
+) -> ResponseVerificationResult<Vec<u8>> {
+    let Some(encoding) = encoding else {
+        return Ok(body.to_owned());
     };
+
+    let Some(encodings) = encoding
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && !name.eq_ignore_ascii_case("identity"))
+        .map(ContentEncoding::from_name)
+        .collect::<Option<Vec<_>>>() else {
+            return Ok(body.to_owned());
+        };
+
+    let max_ratio_size = body.len().saturating_mul(max_compression_ratio as usize);
+    let (max_decoded_size, limit) = if max_ratio_size < max_decoded_size {
+        (
+            max_ratio_size,
+            DecodedBodyLimit::MaxCompressionRatio(max_compression_ratio),
+        )
+    } else {
+        (max_decoded_size, DecodedBodyLimit::MaxDecodedSize)
+    };
+
+    encodings
+        .iter()
+        .rev()
+        .try_fold(body.to_owned(), |body, encoding| {
+            decode_with_encoding(&body, *encoding, max_decoded_size, limit)
+        })
 }
 
-fn body_from_decoder<D: Read>(mut decoder: D) -> ResponseVerificationResult<Vec<u8>> {
+fn decode_with_encoding(
+    body: &[u8],
+    encoding: ContentEncoding,
+    max_decoded_size: usize,
+    limit: DecodedBodyLimit,
+) -> ResponseVerificationResult<Vec<u8>> {
+    match encoding {
+        ContentEncoding::Gzip => body_from_decoder(GzDecoder::new(body), max_decoded_size, limit),
+        ContentEncoding::Deflate => {
+            body_from_decoder(DeflateDecoder::new(body), max_decoded_size, limit)
+        }
+        #[cfg(feature = "brotli")]
+        ContentEncoding::Brotli => body_from_decoder(
+            brotli_decompressor::Decompressor::new(body, MAX_CHUNK_SIZE_TO_DECOMPRESS),
+            max_decoded_size,
+            limit,
+        ),
+        #[cfg(feature = "zstd")]
+        ContentEncoding::Zstd => {
+            let decoder = ruzstd::StreamingDecoder::new(body)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+
+            body_from_decoder(decoder, max_decoded_size, limit)
+        }
+    }
+}
+
+fn body_from_decoder<D: Read>(
+    mut decoder: D,
+    max_decoded_size: usize,
+    limit: DecodedBodyLimit,
+) -> ResponseVerificationResult<Vec<u8>> {
     let mut decoded = Vec::new();
     let mut buffer = [0u8; MAX_CHUNK_SIZE_TO_DECOMPRESS];
 
@@ -23,6 +115,13 @@ fn body_from_decoder<D: Read>(mut decoder: D) -> ResponseVerificationResult<Vec<
             return Ok(decoded);
         }
 
+        if decoded.len() + bytes > max_decoded_size {
+            return Err(ResponseVerificationError::DecodedBodyTooLarge {
+                max_decoded_size,
+                limit,
+            });
+        }
+
         decoded.extend_from_slice(&buffer[..bytes]);
     }
 }
@@ -32,35 +131,236 @@ mod tests {
     use super::*;
     use flate2::write::{DeflateEncoder, GzEncoder};
     use flate2::Compression;
+    #[cfg(feature = "zstd")]
+    use ic_response_verification_test_utils::hex_decode;
     use std::io::Write;
 
     const BODY: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+    const MAX_DECODED_SIZE: usize = 1_024;
+    const MAX_COMPRESSION_RATIO: u32 = 10;
+
+    fn gzip_encode(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+
+        encoder.finish().unwrap()
+    }
+
+    fn deflate_encode(data: &[u8]) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+
+        encoder.finish().unwrap()
+    }
 
     #[test]
     fn decode_simple_body() {
-        let result = decode_body(&BODY.into(), None).unwrap();
+        let result =
+            decode_body(&BODY.into(), None, MAX_DECODED_SIZE, MAX_COMPRESSION_RATIO).unwrap();
 
         assert_eq!(result.as_slice(), BODY);
     }
 
     #[test]
     fn decode_gzip_body() {
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(BODY).unwrap();
-        let encoded_body = encoder.finish().unwrap();
+        let encoded_body = gzip_encode(BODY);
 
-        let result = decode_body(&encoded_body, Some("gzip")).unwrap();
+        let result = decode_body(
+            &encoded_body,
+            Some("gzip"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
 
         assert_eq!(result.as_slice(), BODY);
     }
 
     #[test]
     fn decode_deflate_body() {
-        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
-        encoder.write_all(BODY).unwrap();
-        let encoded_body = encoder.finish().unwrap();
+        let encoded_body = deflate_encode(BODY);
+
+        let result = decode_body(
+            &encoded_body,
+            Some("deflate"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
+
+        assert_eq!(result.as_slice(), BODY);
+    }
+
+    #[test]
+    fn decode_stacked_encodings() {
+        let encoded_body = deflate_encode(&gzip_encode(BODY));
+
+        let result = decode_body(
+            &encoded_body,
+            Some("gzip, deflate"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
+        assert_eq!(result.as_slice(), BODY);
+
+        let result = decode_body(
+            &encoded_body,
+            Some("GZIP,identity ,Deflate"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
+        assert_eq!(result.as_slice(), BODY);
+    }
+
+    #[test]
+    fn decode_unsupported_encoding_returns_body_unchanged() {
+        let encoded_body = gzip_encode(BODY);
+
+        let result = decode_body(
+            &encoded_body,
+            Some("gzip, unknown"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
+
+        assert_eq!(result, encoded_body);
+    }
+
+    #[test]
+    fn decode_body_with_mismatched_encoding_fails() {
+        let encoded_body = gzip_encode(BODY);
+
+        let result = decode_body(
+            &encoded_body,
+            Some("deflate"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn decode_body_larger_than_max_decoded_size_fails() {
+        let encoded_body = gzip_encode(&[0; 2 * MAX_DECODED_SIZE]);
+
+        let result = decode_body(&encoded_body, Some("gzip"), MAX_DECODED_SIZE, u32::MAX);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::DecodedBodyTooLarge {
+                max_decoded_size: MAX_DECODED_SIZE,
+                limit: DecodedBodyLimit::MaxDecodedSize,
+            })
+        ));
+    }
+
+    #[test]
+    fn decode_body_exceeding_max_compression_ratio_fails() {
+        let encoded_body = gzip_encode(&[0; MAX_DECODED_SIZE]);
+        let max_decoded_size = encoded_body.len() * MAX_COMPRESSION_RATIO as usize;
+
+        let result = decode_body(
+            &encoded_body,
+            Some("gzip"),
+            usize::MAX,
+            MAX_COMPRESSION_RATIO,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::DecodedBodyTooLarge {
+                max_decoded_size: size,
+                limit: DecodedBodyLimit::MaxCompressionRatio(MAX_COMPRESSION_RATIO),
+            }) if size == max_decoded_size
+        ));
+    }
+
+    #[test]
+    fn decode_stacked_body_is_limited_by_size_of_encoded_body() {
+        let encoded_body = gzip_encode(&gzip_encode(&[0; 64 * MAX_DECODED_SIZE]));
+
+        let result = decode_body(
+            &encoded_body,
+            Some("gzip, gzip"),
+            usize::MAX,
+            MAX_COMPRESSION_RATIO,
+        );
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::DecodedBodyTooLarge { .. })
+        ));
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn decode_brotli_body() {
+        let encoded_body = vec![139, 3, 128, 1, 2, 3, 4, 5, 6, 7, 8, 3];
+
+        let result = decode_body(
+            &encoded_body,
+            Some("br"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
+
+        assert_eq!(result.as_slice(), BODY);
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn decode_stacked_gzip_and_brotli_body() {
+        let encoded_body = vec![
+            139, 13, 128, 31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 99, 100, 98, 102, 97, 101, 99, 231, 0,
+            0, 197, 136, 202, 63, 8, 0, 0, 0, 3,
+        ];
+
+        let result = decode_body(
+            &encoded_body,
+            Some("gzip, br"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
+
+        assert_eq!(result.as_slice(), BODY);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn decode_zstd_body() {
+        let encoded_body = hex_decode("28b52ffd24084100000102030405060708146e6429");
+
+        let result = decode_body(
+            &encoded_body,
+            Some("zstd"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
+
+        assert_eq!(result.as_slice(), BODY);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn decode_stacked_gzip_and_zstd_body() {
+        let encoded_body = hex_decode(
+            "28b52ffd24252901001f8b08082fb4d26a0003626f64792e62696e0063646266616563e70000c588ca3f0800000007320a5a",
+        );
 
-        let result = decode_body(&encoded_body, Some("deflate")).unwrap();
+        let result = decode_body(
+            &encoded_body,
+            Some("gzip, zstd"),
+            MAX_DECODED_SIZE,
+            MAX_COMPRESSION_RATIO,
+        )
+        .unwrap();
 
         assert_eq!(result.as_slice(), BODY);
     }
@@ -101,6 +101,7 @@ mod tests {
     use crate::test_utils::test_utils::{
         create_encoded_header_field, create_header_field, create_tree,
     };
+    use ic_http_certification::{create_certificate_headers, HttpCertificationPath};
     use ic_response_verification_test_utils::{cbor_encode, create_certificate};
 
     fn base64_encode_no_padding(data: &[u8]) -> String {
@@ -130,6 +131,21 @@ mod tests {
         assert_eq!(certificate_header.expr_path.unwrap(), expr_path);
     }
 
+    #[test]
+    fn certificate_header_parses_header_from_http_certification() {
+        let certificate = cbor_encode(&create_certificate(None));
+        let tree = create_tree(None);
+        let expr_path = HttpCertificationPath::wildcard("/assets/");
+        let [(_, header), _] = create_certificate_headers(&certificate, &tree, &expr_path, "");
+
+        let certificate_header = CertificateHeader::from(header.as_str()).unwrap();
+
+        assert_eq!(certificate_header.certificate.unwrap(), certificate);
+        assert_eq!(certificate_header.tree.unwrap(), cbor_encode(&tree));
+        assert_eq!(certificate_header.version.unwrap(), 2);
+        assert_eq!(certificate_header.expr_path.unwrap(), expr_path.to_cbor());
+    }
+
     #[test]
     fn certificate_header_parsed_valid_header_with_unpadded_base64() {
         let certificate = cbor_encode(&create_certificate(None));
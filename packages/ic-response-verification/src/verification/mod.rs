@@ -6,3 +6,12 @@ mod certificate_header_field;
 
 mod verify_request_response_pair;
 pub use verify_request_response_pair::*;
+
+mod response_verifier;
+pub use response_verifier::*;
+
+mod streaming_verification;
+pub use streaming_verification::*;
+
+mod verification_trace;
+pub(crate) use verification_trace::*;
@@ -0,0 +1,616 @@
+use super::{
+    verify_request_response_pair_with_trace, verify_response_head_with_trace, ChunkReleasePolicy,
+    StreamingVerification, VerificationTrace,
+};
+use crate::{
+    error::ResponseVerificationResult,
+    types::{VerificationInfo, VerificationReport},
+    MIN_VERIFICATION_VERSION,
+};
+use ic_certificate_verification::{
+    CertificateFreshnessTracker, SignatureCache, SizedSignatureCache, TrustedRootKey,
+    TrustedRootKeys,
+};
+use ic_http_certification::{HttpRequest, HttpResponse};
+use std::{fmt, sync::Arc};
+
+/// The default maximum offset between the time of a certificate and the current time, five minutes.
+pub const DEFAULT_MAX_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000;
+
+/// The default maximum size of a decoded response body, 10 MiB.
+pub const DEFAULT_MAX_DECODED_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+/// The default maximum ratio between the size of a decoded response body and its encoded size.
+pub const DEFAULT_MAX_BODY_COMPRESSION_RATIO: u32 = 1_000;
+
+/// The label of the root key that a [VerifierConfig] is created with.
+pub const DEFAULT_ROOT_KEY_LABEL: &str = "default";
+
+/// A source of the current time, used by a [ResponseVerifier] to check the freshness of certificates.
+///
+/// Any `Fn() -> u128` closure that returns the current time in nanoseconds since the UNIX epoch
+/// can be used as a clock.
+pub trait Clock: Send + Sync {
+    /// Returns the current time in nanoseconds since the UNIX epoch.
+    fn current_time_ns(&self) -> u128;
+}
+
+impl<F: Fn() -> u128 + Send + Sync> Clock for F {
+    fn current_time_ns(&self) -> u128 {
+        self()
+    }
+}
+
+/// A [Clock] that reads the current time from the operating system.
+///
+/// This clock is not available on `wasm32` targets, where reading the system time panics at
+/// runtime, so a clock that reads the time from the host environment must be provided instead.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for SystemClock {
+    fn current_time_ns(&self) -> u128 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("System time is before the UNIX epoch")
+            .as_nanos()
+    }
+}
+
+/// Configuration for a [ResponseVerifier].
+///
+/// Only the root key of the Internet Computer and the [Clock] that the current time is read from
+/// are required, every other option has a default. Several root keys can be trusted instead, with
+/// [with_trusted_root_keys](VerifierConfig::with_trusted_root_keys).
+///
+/// - the maximum certificate time offset defaults to [DEFAULT_MAX_CERT_TIME_OFFSET_NS],
+/// - the minimum requested verification version defaults to [MIN_VERIFICATION_VERSION],
+/// - the maximum decoded body size defaults to [DEFAULT_MAX_DECODED_BODY_SIZE],
+/// - the maximum body compression ratio defaults to [DEFAULT_MAX_BODY_COMPRESSION_RATIO],
+/// - the signature cache defaults to the [global signature cache](SizedSignatureCache::global),
+/// - certificate freshness is not tracked,
+/// - trees are not validated to be well formed.
+///
+/// ```rust
+/// use ic_response_verification::VerifierConfig;
+///
+/// let ic_root_key = vec![1, 2, 3];
+/// let current_time_ns = 1_669_202_493_944_584_367;
+///
+/// let verifier = VerifierConfig::new(ic_root_key, move || current_time_ns)
+///     .with_max_cert_time_offset_ns(60_000_000_000)
+///     .with_min_requested_verification_version(2)
+///     .with_max_decoded_body_size(1024 * 1024)
+///     .with_max_body_compression_ratio(100)
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct VerifierConfig {
+    pub(crate) trusted_root_keys: Arc<TrustedRootKeys>,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) max_cert_time_offset_ns: u128,
+    pub(crate) min_requested_verification_version: u8,
+    pub(crate) max_decoded_body_size: usize,
+    pub(crate) max_body_compression_ratio: u32,
+    pub(crate) signature_cache: Arc<dyn SignatureCache>,
+    pub(crate) freshness_tracker: Option<Arc<CertificateFreshnessTracker>>,
+    pub(crate) validate_well_formed_trees: bool,
+}
+
+impl fmt::Debug for VerifierConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifierConfig")
+            .field("trusted_root_keys", &self.trusted_root_keys)
+            .field("max_cert_time_offset_ns", &self.max_cert_time_offset_ns)
+            .field(
+                "min_requested_verification_version",
+                &self.min_requested_verification_version,
+            )
+            .field("max_decoded_body_size", &self.max_decoded_body_size)
+            .field(
+                "max_body_compression_ratio",
+                &self.max_body_compression_ratio,
+            )
+            .field("freshness_tracker", &self.freshness_tracker)
+            .field(
+                "validate_well_formed_trees",
+                &self.validate_well_formed_trees,
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl VerifierConfig {
+    /// Creates a new configuration that verifies certificates against the provided DER encoded
+    /// root key of the Internet Computer, labeled [DEFAULT_ROOT_KEY_LABEL], and reads the current
+    /// time from the provided [Clock], such as the [SystemClock] outside of `wasm32` targets.
+    pub fn new(ic_public_key: impl Into<Vec<u8>>, clock: impl Clock + 'static) -> Self {
+        let mut trusted_root_keys = TrustedRootKeys::new();
+        trusted_root_keys.add(TrustedRootKey::new(DEFAULT_ROOT_KEY_LABEL, ic_public_key));
+
+        Self {
+            trusted_root_keys: Arc::new(trusted_root_keys),
+            clock: Arc::new(clock),
+            max_cert_time_offset_ns: DEFAULT_MAX_CERT_TIME_OFFSET_NS,
+            min_requested_verification_version: MIN_VERIFICATION_VERSION,
+            max_decoded_body_size: DEFAULT_MAX_DECODED_BODY_SIZE,
+            max_body_compression_ratio: DEFAULT_MAX_BODY_COMPRESSION_RATIO,
+            signature_cache: SizedSignatureCache::global(),
+            freshness_tracker: None,
+            validate_well_formed_trees: false,
+        }
+    }
+
+    /// Sets the maximum offset, in nanoseconds, that is allowed between the time of a certificate
+    /// and the current time.
+    pub fn with_max_cert_time_offset_ns(&mut self, max_cert_time_offset_ns: u128) -> &mut Self {
+        self.max_cert_time_offset_ns = max_cert_time_offset_ns;
+
+        self
+    }
+
+    /// Sets the minimum verification version that a response must be certified with.
+    pub fn with_min_requested_verification_version(
+        &mut self,
+        min_requested_verification_version: u8,
+    ) -> &mut Self {
+        self.min_requested_verification_version = min_requested_verification_version;
+
+        self
+    }
+
+    /// Sets the maximum size, in bytes, that a response body with a `Content-Encoding` is allowed
+    /// to decode to. Verification fails with
+    /// [DecodedBodyTooLarge](crate::ResponseVerificationError::DecodedBodyTooLarge) when the
+    /// limit is exceeded.
+    pub fn with_max_decoded_body_size(&mut self, max_decoded_body_size: usize) -> &mut Self {
+        self.max_decoded_body_size = max_decoded_body_size;
+
+        self
+    }
+
+    /// Sets the maximum ratio between the size that a response body with a `Content-Encoding` is
+    /// allowed to decode to and its encoded size. Verification fails with
+    /// [DecodedBodyTooLarge](crate::ResponseVerificationError::DecodedBodyTooLarge) when the
+    /// ratio is exceeded.
+    pub fn with_max_body_compression_ratio(
+        &mut self,
+        max_body_compression_ratio: u32,
+    ) -> &mut Self {
+        self.max_body_compression_ratio = max_body_compression_ratio;
+
+        self
+    }
+
+    /// Sets the [SignatureCache] that verified certificate signatures are cached in. The cache is
+    /// shared, so the caller can keep a reference to it to read its statistics.
+    pub fn with_signature_cache(&mut self, signature_cache: Arc<dyn SignatureCache>) -> &mut Self {
+        self.signature_cache = signature_cache;
+
+        self
+    }
+
+    /// Sets the [CertificateFreshnessTracker] that rejects certificates that are older than the
+    /// newest certificate that was already seen for the same canister. Verification fails with
+    /// [CertificateOlderThanNewestSeen](ic_certificate_verification::CertificateVerificationError::CertificateOlderThanNewestSeen)
+    /// when a certificate is rejected.
+    pub fn with_freshness_tracker(
+        &mut self,
+        freshness_tracker: Arc<CertificateFreshnessTracker>,
+    ) -> &mut Self {
+        self.freshness_tracker = Some(freshness_tracker);
+
+        self
+    }
+
+    /// Sets whether the tree from the `IC-Certificate` header, the certificate's tree and the tree
+    /// of the delegation's certificate are validated to be well formed before they are used to
+    /// verify the response. Verification fails with
+    /// [MalformedTree](crate::ResponseVerificationError::MalformedTree) when one is not.
+    ///
+    /// Absence proofs rely on the labels of the tree being strictly increasing, so a malformed
+    /// tree could otherwise prove the absence of a more specific certification that is present.
+    pub fn with_well_formed_tree_validation(
+        &mut self,
+        validate_well_formed_trees: bool,
+    ) -> &mut Self {
+        self.validate_well_formed_trees = validate_well_formed_trees;
+
+        self
+    }
+
+    /// Sets the [TrustedRootKeys] that certificates are verified against, replacing the root key
+    /// that the configuration was created with. The store is shared, so the caller can keep a
+    /// reference to it to find out which of its keys verified certificates.
+    pub fn with_trusted_root_keys(&mut self, trusted_root_keys: Arc<TrustedRootKeys>) -> &mut Self {
+        self.trusted_root_keys = trusted_root_keys;
+
+        self
+    }
+
+    /// Creates a [ResponseVerifier] with this configuration.
+    pub fn build(&self) -> ResponseVerifier {
+        ResponseVerifier {
+            config: self.clone(),
+        }
+    }
+}
+
+/// Verifies request and response pairs against a [configuration](VerifierConfig) that is created
+/// once and then shared, for example by an HTTP Gateway for the lifetime of its process.
+#[derive(Debug, Clone)]
+pub struct ResponseVerifier {
+    config: VerifierConfig,
+}
+
+impl ResponseVerifier {
+    /// Creates a new verifier with the provided configuration.
+    pub fn new(config: VerifierConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns the configuration of this verifier.
+    pub fn config(&self) -> &VerifierConfig {
+        &self.config
+    }
+
+    /// Verifies the response with respect to the request, for the canister with the provided ID,
+    /// according to the [Response Verification Spec](https://internetcomputer.org/docs/current/references/http-gateway-protocol-spec#response-verification).
+    pub fn verify(
+        &self,
+        request: HttpRequest,
+        response: HttpResponse,
+        canister_id: &[u8],
+    ) -> ResponseVerificationResult<VerificationInfo> {
+        verify_request_response_pair_with_trace(
+            request,
+            response,
+            canister_id,
+            &self.config,
+            &mut VerificationTrace::disabled(),
+        )
+    }
+
+    /// Verifies the headers and certificate of a response whose body is delivered in chunks, and
+    /// returns a [StreamingVerification] that verifies the body as its chunks are pushed, releasing
+    /// them according to the provided [ChunkReleasePolicy].
+    ///
+    /// The body of the provided response is ignored, it must be pushed as the first chunk.
+    pub fn verify_streaming(
+        &self,
+        request: &HttpRequest,
+        response: &HttpResponse,
+        canister_id: &[u8],
+        policy: ChunkReleasePolicy,
+    ) -> ResponseVerificationResult<StreamingVerification> {
+        let pending = verify_response_head_with_trace(
+            request,
+            response,
+            canister_id,
+            &self.config,
+            &mut VerificationTrace::disabled(),
+        )?;
+
+        Ok(StreamingVerification::new(
+            pending,
+            self.config.clone(),
+            policy,
+        ))
+    }
+
+    /// Verifies the response in the same way as [verify](ResponseVerifier::verify), and returns a
+    /// [VerificationReport] that records each stage of verification with its inputs and outcome.
+    ///
+    /// This is intended for diagnosing why the certification of a canister's response is broken,
+    /// regular verification should use [verify](ResponseVerifier::verify) instead.
+    pub fn verify_with_report(
+        &self,
+        request: HttpRequest,
+        response: HttpResponse,
+        canister_id: &[u8],
+    ) -> VerificationReport {
+        let mut trace = VerificationTrace::enabled();
+        let result = verify_request_response_pair_with_trace(
+            request,
+            response,
+            canister_id,
+            &self.config,
+            &mut trace,
+        );
+
+        VerificationReport {
+            stages: trace.into_stages(),
+            result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::test_utils::create_tree,
+        types::{VerificationStage, VerificationStageOutcome, VerificationStageRecord},
+        ResponseVerificationError,
+    };
+    use ic_certificate_verification::CertificateVerificationError;
+    use ic_certification::{empty, fork, labeled, Delegation, MalformedHashTreeError};
+    use ic_response_verification_test_utils::{
+        cbor_encode, create_certificate, create_certificate_delegation, create_certificate_header,
+        leb_encode_timestamp, CreateCertificateOptions,
+    };
+
+    const CERTIFICATE_TIME_NS: u128 = 1_669_202_493_944_584_367;
+    const MAX_CERT_TIME_OFFSET_NS: u128 = 60_000_000_000;
+
+    fn create_request_response_pair() -> (HttpRequest, HttpResponse) {
+        let certificate_time = leb_encode_timestamp(CERTIFICATE_TIME_NS);
+        let certificate = create_certificate(Some(CreateCertificateOptions {
+            time: Some(&certificate_time),
+            canister_id: None,
+            certified_data: None,
+        }));
+        let certificate_header =
+            create_certificate_header(&cbor_encode(&certificate), &cbor_encode(&create_tree(None)));
+
+        let request = HttpRequest {
+            url: "/".into(),
+            method: "GET".into(),
+            headers: vec![],
+            body: vec![],
+        };
+        let response = HttpResponse {
+            status_code: 200,
+            headers: vec![("IC-Certificate".into(), certificate_header)],
+            body: b"Hello World!".to_vec(),
+        };
+
+        (request, response)
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config = VerifierConfig::new(vec![1, 2, 3], || CERTIFICATE_TIME_NS);
+
+        assert_eq!(config.trusted_root_keys.keys().len(), 1);
+        assert_eq!(
+            config.trusted_root_keys.keys()[0].label(),
+            DEFAULT_ROOT_KEY_LABEL
+        );
+        assert_eq!(config.trusted_root_keys.keys()[0].der_key(), &[1, 2, 3]);
+        assert_eq!(
+            config.max_cert_time_offset_ns,
+            DEFAULT_MAX_CERT_TIME_OFFSET_NS
+        );
+        assert_eq!(
+            config.min_requested_verification_version,
+            MIN_VERIFICATION_VERSION
+        );
+        assert_eq!(config.max_decoded_body_size, DEFAULT_MAX_DECODED_BODY_SIZE);
+        assert_eq!(
+            config.max_body_compression_ratio,
+            DEFAULT_MAX_BODY_COMPRESSION_RATIO
+        );
+        assert!(config.freshness_tracker.is_none());
+        assert!(!config.validate_well_formed_trees);
+    }
+
+    #[test]
+    fn verify_uses_configured_clock() {
+        let (request, response) = create_request_response_pair();
+
+        let past_verifier =
+            VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS + 2 * MAX_CERT_TIME_OFFSET_NS)
+                .with_max_cert_time_offset_ns(MAX_CERT_TIME_OFFSET_NS)
+                .build();
+        let result = past_verifier.verify(request.clone(), response.clone(), &[]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(
+                CertificateVerificationError::TimeTooFarInThePast { .. }
+            ))
+        ));
+
+        let future_verifier =
+            VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS - 2 * MAX_CERT_TIME_OFFSET_NS)
+                .with_max_cert_time_offset_ns(MAX_CERT_TIME_OFFSET_NS)
+                .build();
+        let result = future_verifier.verify(request, response, &[]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(
+                CertificateVerificationError::TimeTooFarInTheFuture { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn verify_enforces_min_requested_verification_version() {
+        let (request, response) = create_request_response_pair();
+
+        let verifier = VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS)
+            .with_min_requested_verification_version(2)
+            .build();
+        let result = verifier.verify(request, response, &[]);
+
+        assert!(matches!(
+            result,
+            Err(
+                ResponseVerificationError::RequestedVerificationVersionMismatch {
+                    requested_version: 1,
+                    min_requested_verification_version: 2,
+                }
+            )
+        ));
+    }
+
+    #[test]
+    fn verify_validates_well_formed_trees() {
+        let (request, mut response) = create_request_response_pair();
+        let certificate_time = leb_encode_timestamp(CERTIFICATE_TIME_NS);
+        let certificate = create_certificate(Some(CreateCertificateOptions {
+            time: Some(&certificate_time),
+            canister_id: None,
+            certified_data: None,
+        }));
+        let malformed_tree = fork(labeled("b", empty()), labeled("a", empty()));
+        response.headers = vec![(
+            "IC-Certificate".into(),
+            create_certificate_header(&cbor_encode(&certificate), &cbor_encode(&malformed_tree)),
+        )];
+
+        let verifier = VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS)
+            .with_well_formed_tree_validation(true)
+            .build();
+        let result = verifier.verify(request.clone(), response.clone(), &[]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::MalformedTree(
+                MalformedHashTreeError::LabelsNotStrictlyIncreasing { .. }
+            ))
+        ));
+
+        let verifier = VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS).build();
+        let result = verifier.verify(request, response, &[]);
+
+        assert!(!matches!(
+            result,
+            Err(ResponseVerificationError::MalformedTree(_))
+        ));
+    }
+
+    #[test]
+    fn verify_validates_well_formed_certificate_trees() {
+        let (request, mut response) = create_request_response_pair();
+        let mut certificate = create_certificate(None);
+        certificate.tree = fork(labeled("time", empty()), certificate.tree);
+        response.headers = vec![(
+            "IC-Certificate".into(),
+            create_certificate_header(&cbor_encode(&certificate), &cbor_encode(&create_tree(None))),
+        )];
+
+        let verifier = VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS)
+            .with_well_formed_tree_validation(true)
+            .build();
+        let result = verifier.verify(request, response, &[]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::MalformedTree(
+                MalformedHashTreeError::LabelsNotStrictlyIncreasing { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn verify_validates_well_formed_delegation_certificate_trees() {
+        let (request, mut response) = create_request_response_pair();
+        let mut delegation_certificate = create_certificate(None);
+        delegation_certificate.tree = fork(labeled("time", empty()), delegation_certificate.tree);
+        let mut certificate = create_certificate(None);
+        certificate.delegation = Some(Delegation {
+            certificate: cbor_encode(&delegation_certificate),
+            ..create_certificate_delegation()
+        });
+        response.headers = vec![(
+            "IC-Certificate".into(),
+            create_certificate_header(&cbor_encode(&certificate), &cbor_encode(&create_tree(None))),
+        )];
+
+        let verifier = VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS)
+            .with_well_formed_tree_validation(true)
+            .build();
+        let result = verifier.verify(request, response, &[]);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::MalformedTree(
+                MalformedHashTreeError::LabelsNotStrictlyIncreasing { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn verify_with_report_records_stages_up_to_failure() {
+        let (request, response) = create_request_response_pair();
+
+        let verifier =
+            VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS + 2 * MAX_CERT_TIME_OFFSET_NS)
+                .with_max_cert_time_offset_ns(MAX_CERT_TIME_OFFSET_NS)
+                .build();
+        let report = verifier.verify_with_report(request, response, &[]);
+
+        assert!(matches!(
+            report.result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(
+                CertificateVerificationError::TimeTooFarInThePast { .. }
+            ))
+        ));
+        assert_eq!(report.stages.len(), 2);
+        assert_eq!(report.stages[0].stage, VerificationStage::HeaderParse);
+        assert_eq!(report.stages[0].outcome, VerificationStageOutcome::Passed);
+
+        let failed_stage = report.failed_stage().unwrap();
+        assert_eq!(failed_stage.stage, VerificationStage::CertificateTime);
+        assert!(failed_stage.inputs.contains(&(
+            "certificate time".to_string(),
+            CERTIFICATE_TIME_NS.to_string()
+        )));
+        assert!(failed_stage.inputs.contains(&(
+            "max certificate time offset".to_string(),
+            MAX_CERT_TIME_OFFSET_NS.to_string()
+        )));
+    }
+
+    #[test]
+    fn verify_with_report_records_header_parse_failure() {
+        let (request, mut response) = create_request_response_pair();
+        response.headers = vec![("Content-Encoding".into(), "gzip".into())];
+
+        let verifier = VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS).build();
+        let report = verifier.verify_with_report(request, response, &[]);
+
+        assert!(matches!(
+            report.result,
+            Err(ResponseVerificationError::MissingCertification)
+        ));
+        assert_eq!(
+            report.stages,
+            vec![VerificationStageRecord {
+                stage: VerificationStage::HeaderParse,
+                inputs: vec![("content-encoding".into(), "gzip".into())],
+                outcome: VerificationStageOutcome::Failed("Certification values not found".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn verifier_can_be_shared_between_threads() {
+        let verifier = Arc::new(
+            VerifierConfig::new(vec![], || CERTIFICATE_TIME_NS)
+                .with_min_requested_verification_version(2)
+                .build(),
+        );
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let verifier = Arc::clone(&verifier);
+
+                std::thread::spawn(move || {
+                    let (request, response) = create_request_response_pair();
+
+                    verifier.verify(request, response, &[]).is_err()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use super::{PendingResponseVerification, VerificationTrace, VerifierConfig};
+use crate::{
+    error::{DecodedBodyLimit, ResponseVerificationError, ResponseVerificationResult},
+    types::VerificationInfo,
+};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Controls when the chunks of a streamed response body are released to the caller by a
+/// [StreamingVerification].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkReleasePolicy {
+    /// Chunks are held by the verifier and released all at once, as the body of the verified
+    /// response, when [finish](StreamingVerification::finish) succeeds. No unverified data is
+    /// ever released, at the cost of holding the whole body in memory.
+    AfterVerification,
+
+    /// Chunks are released as soon as they are pushed, so they can be forwarded before the whole
+    /// body has been verified. The body of the verified response is left empty. Callers must abort
+    /// the response, rather than end it, if [finish](StreamingVerification::finish) fails.
+    Immediately,
+}
+
+/// The incremental verification of a response whose body is delivered in chunks, for example
+/// through the streaming callback of the HTTP Gateway Protocol. Created by
+/// [ResponseVerifier::verify_streaming](crate::ResponseVerifier::verify_streaming), which has
+/// already verified the headers and certificate of the response.
+///
+/// Each chunk of the body is pushed with [push_chunk](StreamingVerification::push_chunk), in
+/// order, and the body is verified against the tree by [finish](StreamingVerification::finish).
+///
+/// Verification version 1 responses with a `Content-Encoding` must be decoded before they are
+/// hashed, so their body is always held in memory until [finish](StreamingVerification::finish),
+/// whatever the [ChunkReleasePolicy]. Responses that opted out of certification have no body to
+/// verify, so their chunks are always released immediately.
+pub struct StreamingVerification {
+    pending: PendingResponseVerification,
+    config: VerifierConfig,
+    policy: ChunkReleasePolicy,
+    hasher: Sha256,
+    body: Vec<u8>,
+    body_size: usize,
+}
+
+impl fmt::Debug for StreamingVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingVerification")
+            .field("config", &self.config)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StreamingVerification {
+    pub(crate) fn new(
+        pending: PendingResponseVerification,
+        config: VerifierConfig,
+        policy: ChunkReleasePolicy,
+    ) -> Self {
+        Self {
+            pending,
+            config,
+            policy,
+            hasher: Sha256::new(),
+            body: Vec::new(),
+            body_size: 0,
+        }
+    }
+
+    /// Returns the policy that controls when chunks are released.
+    pub fn policy(&self) -> ChunkReleasePolicy {
+        self.policy
+    }
+
+    /// Pushes the next chunk of the body. The chunk is returned if it is released immediately,
+    /// according to the [ChunkReleasePolicy].
+    ///
+    /// Fails once the chunks that were pushed exceed the
+    /// [maximum decoded body size](VerifierConfig::with_max_decoded_body_size) of the
+    /// [VerifierConfig], the response must then be aborted.
+    pub fn push_chunk(&mut self, chunk: Vec<u8>) -> ResponseVerificationResult<Option<Vec<u8>>> {
+        if self.pending.is_skipped() {
+            return Ok(Some(chunk));
+        }
+
+        self.body_size = self.body_size.saturating_add(chunk.len());
+        if self.body_size > self.config.max_decoded_body_size {
+            return Err(ResponseVerificationError::DecodedBodyTooLarge {
+                max_decoded_size: self.config.max_decoded_body_size,
+                limit: DecodedBodyLimit::MaxDecodedSize,
+            });
+        }
+
+        self.hasher.update(&chunk);
+
+        match self.policy {
+            ChunkReleasePolicy::AfterVerification => {
+                self.body.extend(chunk);
+
+                Ok(None)
+            }
+            ChunkReleasePolicy::Immediately => {
+                if self.pending.requires_body() {
+                    self.body.extend_from_slice(&chunk);
+                }
+
+                Ok(Some(chunk))
+            }
+        }
+    }
+
+    /// Verifies the body that was pushed against the tree, once all of its chunks have been
+    /// pushed.
+    pub fn finish(self) -> ResponseVerificationResult<VerificationInfo> {
+        let body_hash = self.hasher.finalize().into();
+        let mut verification_info = self.pending.verify_body(
+            body_hash,
+            self.body,
+            &self.config,
+            &mut VerificationTrace::disabled(),
+        )?;
+
+        if self.policy == ChunkReleasePolicy::Immediately {
+            if let Some(response) = &mut verification_info.response {
+                response.body = Vec::new();
+            }
+        }
+
+        Ok(verification_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::test_utils::{create_tree, CreateTreeOptions},
+        verification::PendingBodyVerification,
+    };
+    use flate2::{write::GzEncoder, Compression};
+    use ic_certificate_verification::CertificateFreshnessTracker;
+    use ic_representation_independent_hash::hash;
+    use std::{io::Write, sync::Arc};
+
+    const BODY: &[u8] = b"Hello World!";
+    const CANISTER_ID: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 1, 1, 1];
+
+    fn create_v1_body(encoding: Option<&str>, body_sha: &[u8]) -> PendingBodyVerification {
+        PendingBodyVerification::V1 {
+            tree: create_tree(Some(CreateTreeOptions {
+                path: Some("/"),
+                body_sha: Some(body_sha),
+            })),
+            request_path: "/".into(),
+            encoding: encoding.map(Into::into),
+        }
+    }
+
+    fn create_v1_pending(encoding: Option<&str>, body_sha: &[u8]) -> PendingResponseVerification {
+        PendingResponseVerification::new(create_v1_body(encoding, body_sha), CANISTER_ID, 0)
+    }
+
+    fn push_chunks(verification: &mut StreamingVerification, body: &[u8]) -> Vec<u8> {
+        body.chunks(5)
+            .filter_map(|chunk| verification.push_chunk(chunk.to_vec()).unwrap())
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn verify_chunks_after_verification() {
+        let mut verification = StreamingVerification::new(
+            create_v1_pending(None, &hash(BODY)),
+            VerifierConfig::new(vec![], || 0),
+            ChunkReleasePolicy::AfterVerification,
+        );
+
+        let released = push_chunks(&mut verification, BODY);
+        let result = verification.finish().unwrap();
+
+        assert!(released.is_empty());
+        assert_eq!(result.response.unwrap().body, BODY);
+    }
+
+    #[test]
+    fn verify_chunks_released_immediately() {
+        let mut verification = StreamingVerification::new(
+            create_v1_pending(None, &hash(BODY)),
+            VerifierConfig::new(vec![], || 0),
+            ChunkReleasePolicy::Immediately,
+        );
+
+        let released = push_chunks(&mut verification, BODY);
+        let result = verification.finish().unwrap();
+
+        assert_eq!(released, BODY);
+        assert!(result.response.unwrap().body.is_empty());
+    }
+
+    #[test]
+    fn verify_encoded_chunks_released_immediately() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(BODY).unwrap();
+        let encoded_body = encoder.finish().unwrap();
+
+        let mut verification = StreamingVerification::new(
+            create_v1_pending(Some("gzip"), &hash(BODY)),
+            VerifierConfig::new(vec![], || 0),
+            ChunkReleasePolicy::Immediately,
+        );
+
+        let released = push_chunks(&mut verification, &encoded_body);
+        let result = verification.finish().unwrap();
+
+        assert_eq!(released, encoded_body);
+        assert!(result.response.unwrap().body.is_empty());
+    }
+
+    #[test]
+    fn verify_chunks_with_invalid_body() {
+        let mut verification = StreamingVerification::new(
+            create_v1_pending(None, &hash(BODY)),
+            VerifierConfig::new(vec![], || 0),
+            ChunkReleasePolicy::AfterVerification,
+        );
+
+        push_chunks(&mut verification, b"Hello Tampered World!");
+        let result = verification.finish();
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseBody)
+        ));
+    }
+
+    #[test]
+    fn certificate_time_is_only_recorded_once_the_body_is_verified() {
+        let freshness_tracker = Arc::new(CertificateFreshnessTracker::in_memory());
+        let mut config = VerifierConfig::new(vec![], || 0);
+        config.with_freshness_tracker(freshness_tracker.clone());
+        let verify_chunks_with_certificate_time = |certificate_time, body: &[u8]| {
+            let mut verification = StreamingVerification::new(
+                PendingResponseVerification::new(
+                    create_v1_body(None, &hash(BODY)),
+                    CANISTER_ID,
+                    certificate_time,
+                ),
+                config.clone(),
+                ChunkReleasePolicy::AfterVerification,
+            );
+
+            push_chunks(&mut verification, body);
+            verification.finish()
+        };
+
+        verify_chunks_with_certificate_time(200, b"Hello Tampered World!").unwrap_err();
+        freshness_tracker
+            .check_certificate_freshness(CANISTER_ID, 100)
+            .unwrap();
+
+        verify_chunks_with_certificate_time(200, BODY).unwrap();
+        freshness_tracker
+            .check_certificate_freshness(CANISTER_ID, 100)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn verify_chunks_exceeding_max_decoded_body_size() {
+        let mut config = VerifierConfig::new(vec![], || 0);
+        config.with_max_decoded_body_size(BODY.len() - 1);
+        let mut verification = StreamingVerification::new(
+            create_v1_pending(None, &hash(BODY)),
+            config,
+            ChunkReleasePolicy::Immediately,
+        );
+
+        let (head, tail) = BODY.split_at(BODY.len() - 1);
+        let released = verification.push_chunk(head.to_vec()).unwrap();
+        let result = verification.push_chunk(tail.to_vec());
+
+        assert_eq!(released, Some(head.to_vec()));
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::DecodedBodyTooLarge {
+                max_decoded_size,
+                limit: DecodedBodyLimit::MaxDecodedSize,
+            }) if max_decoded_size == BODY.len() - 1
+        ));
+    }
+
+    #[test]
+    fn skipped_certification_releases_chunks_immediately() {
+        let mut verification = StreamingVerification::new(
+            PendingResponseVerification::new(PendingBodyVerification::Skipped, CANISTER_ID, 0),
+            VerifierConfig::new(vec![], || 0),
+            ChunkReleasePolicy::AfterVerification,
+        );
+
+        let released = push_chunks(&mut verification, BODY);
+        let result = verification.finish().unwrap();
+
+        assert_eq!(released, BODY);
+        assert!(result.response.is_none());
+    }
+}
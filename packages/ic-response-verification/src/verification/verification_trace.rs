@@ -0,0 +1,65 @@
+use crate::{
+    error::ResponseVerificationResult,
+    types::{VerificationStage, VerificationStageOutcome, VerificationStageRecord},
+};
+
+/// Records the stages of response verification for a [VerificationReport](crate::types::VerificationReport).
+///
+/// A disabled trace records nothing and never evaluates the inputs of a stage, so regular
+/// verification does not pay for formatting them.
+pub(crate) struct VerificationTrace {
+    stages: Option<Vec<VerificationStageRecord>>,
+}
+
+impl VerificationTrace {
+    pub(crate) fn enabled() -> Self {
+        Self {
+            stages: Some(Vec::new()),
+        }
+    }
+
+    pub(crate) fn disabled() -> Self {
+        Self { stages: None }
+    }
+
+    /// Records the outcome of a stage and returns its result unchanged.
+    pub(crate) fn record<T>(
+        &mut self,
+        stage: VerificationStage,
+        result: ResponseVerificationResult<T>,
+        inputs: impl FnOnce() -> Vec<(String, String)>,
+    ) -> ResponseVerificationResult<T> {
+        if let Some(stages) = &mut self.stages {
+            let outcome = match &result {
+                Ok(_) => VerificationStageOutcome::Passed,
+                Err(error) => VerificationStageOutcome::Failed(error.to_string()),
+            };
+
+            stages.push(VerificationStageRecord {
+                stage,
+                inputs: inputs(),
+                outcome,
+            });
+        }
+
+        result
+    }
+
+    /// Records the outcome of a stage only if it failed, for stages that are made up of several
+    /// steps, and returns its result unchanged.
+    pub(crate) fn record_failure<T>(
+        &mut self,
+        stage: VerificationStage,
+        result: ResponseVerificationResult<T>,
+        inputs: impl FnOnce() -> Vec<(String, String)>,
+    ) -> ResponseVerificationResult<T> {
+        match result {
+            Ok(value) => Ok(value),
+            Err(error) => self.record(stage, Err(error), inputs),
+        }
+    }
+
+    pub(crate) fn into_stages(self) -> Vec<VerificationStageRecord> {
+        self.stages.unwrap_or_default()
+    }
+}
@@ -1,21 +1,27 @@
-use super::{body::decode_body, certificate_header::CertificateHeader};
+use super::{
+    body::decode_body, certificate_header::CertificateHeader, VerificationTrace, VerifierConfig,
+};
 use crate::{
     cel::{map_cel_ast, parse_cel_expression},
     error::{ResponseVerificationError, ResponseVerificationResult},
-    types::{VerificationInfo, VerifiedResponse},
+    types::{VerificationInfo, VerificationStage, VerifiedResponse},
     validation::{
-        validate_body, validate_expr_hash, validate_expr_path, validate_hashes, validate_tree,
+        find_expr_path_certifications, find_more_specific_expr_paths, validate_body,
+        validate_expr_hash, validate_expr_path, validate_hashes, validate_tree,
     },
 };
 use ic_cbor::{parse_cbor_string_array, CertificateToCbor, HashTreeToCbor};
-use ic_certificate_verification::{validate_certificate_time, VerifyCertificate};
-use ic_certification::{hash_tree::Hash, Certificate, HashTree};
+use ic_certificate_verification::{
+    validate_certificate_time, LookupCertificate, UnverifiedCertificate, VerifyCertificate,
+};
+use ic_certification::{hash_tree::Hash, Certificate, HashTree, Label, LookupResult};
 use ic_http_certification::{
     cel::{
         CelExpression, DefaultCelExpression, DefaultFullCelExpression,
         DefaultResponseOnlyCelExpression,
     },
-    filter_response_headers, request_hash, response_headers_hash, HttpRequest, HttpResponse,
+    filter_response_headers, request_hash, response_headers_hash, HttpCertificationPath,
+    HttpRequest, HttpResponse,
 };
 use ic_representation_independent_hash::hash;
 use std::collections::HashMap;
@@ -25,8 +31,11 @@ pub const MIN_VERIFICATION_VERSION: u8 = 1;
 /// The maximum verification version supported by this package.
 pub const MAX_VERIFICATION_VERSION: u8 = 2;
 
-/// The primary entry point for verifying a request and response pair. This will verify the response
-/// with respect to the request, according the [Response Verification Spec]().
+/// Verifies a request and response pair. This will verify the response with respect to the request,
+/// according the [Response Verification Spec]().
+///
+/// Consumers that verify more than one response should prefer a [ResponseVerifier](crate::ResponseVerifier),
+/// which is configured once with the root key, clock and verification policy.
 pub fn verify_request_response_pair(
     request: HttpRequest,
     response: HttpResponse,
@@ -36,6 +45,215 @@ pub fn verify_request_response_pair(
     ic_public_key: &[u8],
     min_requested_verification_version: u8,
 ) -> ResponseVerificationResult<VerificationInfo> {
+    VerifierConfig::new(ic_public_key, move || current_time_ns)
+        .with_max_cert_time_offset_ns(max_cert_time_offset_ns)
+        .with_min_requested_verification_version(min_requested_verification_version)
+        .build()
+        .verify(request, response, canister_id)
+}
+
+/// The tree, certificate and version parsed from the `IC-Certificate` header.
+struct ParsedCertificateHeader {
+    tree: HashTree,
+    certificate: Certificate,
+    version: u8,
+    expr_path: Option<Vec<u8>>,
+}
+
+/// A response whose headers and certificate have been verified, and whose body remains to be
+/// verified against the tree.
+pub(crate) enum PendingBodyVerification {
+    /// The response opted out of certification, so there is no body to verify.
+    Skipped,
+    V1 {
+        tree: HashTree,
+        request_path: String,
+        encoding: Option<String>,
+    },
+    V2 {
+        tree: HashTree,
+        expr_path: HttpCertificationPath,
+        expr_hash: Hash,
+        request_hash: Option<Hash>,
+        status_code: u16,
+        response_headers: Vec<(String, String)>,
+        response_headers_hash: Hash,
+    },
+}
+
+impl PendingBodyVerification {
+    /// Returns true if the whole body is needed to verify it, rather than only its hash, because
+    /// it must be decoded before it is hashed.
+    pub(crate) fn requires_body(&self) -> bool {
+        matches!(
+            self,
+            PendingBodyVerification::V1 {
+                encoding: Some(_),
+                ..
+            }
+        )
+    }
+
+    /// Returns true if the response opted out of certification.
+    pub(crate) fn is_skipped(&self) -> bool {
+        matches!(self, PendingBodyVerification::Skipped)
+    }
+
+    /// Verifies the hash of the body, as it was received, against the tree. The body itself is
+    /// returned as part of the verified response and must be provided if [requires_body] is true.
+    ///
+    /// [requires_body]: PendingBodyVerification::requires_body
+    pub(crate) fn verify_body(
+        self,
+        body_hash: Hash,
+        body: Vec<u8>,
+        config: &VerifierConfig,
+        trace: &mut VerificationTrace,
+    ) -> ResponseVerificationResult<VerificationInfo> {
+        match self {
+            PendingBodyVerification::Skipped => Ok(VerificationInfo {
+                response: None,
+                verification_version: 2,
+            }),
+            PendingBodyVerification::V1 {
+                tree,
+                request_path,
+                encoding,
+            } => v1_body_verification(
+                tree,
+                request_path,
+                encoding.as_deref(),
+                body_hash,
+                body,
+                config,
+                trace,
+            ),
+            PendingBodyVerification::V2 {
+                tree,
+                expr_path,
+                expr_hash,
+                request_hash,
+                status_code,
+                response_headers,
+                response_headers_hash,
+            } => {
+                let response_hash = hash([response_headers_hash, body_hash].concat().as_slice());
+
+                trace.record(
+                    VerificationStage::HashComparison,
+                    match validate_hashes(
+                        &expr_hash,
+                        &request_hash,
+                        &response_hash,
+                        &expr_path,
+                        &tree,
+                    ) {
+                        true => Ok(()),
+                        false => Err(ResponseVerificationError::InvalidResponseHashes),
+                    },
+                    || {
+                        vec![
+                            ("expression hash".into(), hex::encode(expr_hash)),
+                            (
+                                "request hash".into(),
+                                request_hash.map(hex::encode).unwrap_or_default(),
+                            ),
+                            ("response hash".into(), hex::encode(response_hash)),
+                            (
+                                "certifications in tree".into(),
+                                expr_path_certifications(&expr_path, &tree),
+                            ),
+                        ]
+                    },
+                )?;
+
+                Ok(VerificationInfo {
+                    response: Some(VerifiedResponse {
+                        status_code: Some(status_code),
+                        headers: response_headers,
+                        body,
+                    }),
+                    verification_version: 2,
+                })
+            }
+        }
+    }
+}
+
+/// A response whose headers and certificate have been verified, and whose body remains to be
+/// verified. The time of the certificate is only recorded by the freshness tracker once the body
+/// has been verified as well.
+pub(crate) struct PendingResponseVerification {
+    body: PendingBodyVerification,
+    canister_id: Vec<u8>,
+    certificate_time: u128,
+}
+
+impl PendingResponseVerification {
+    pub(crate) fn new(
+        body: PendingBodyVerification,
+        canister_id: &[u8],
+        certificate_time: u128,
+    ) -> Self {
+        Self {
+            body,
+            canister_id: canister_id.to_vec(),
+            certificate_time,
+        }
+    }
+
+    /// See [PendingBodyVerification::requires_body].
+    pub(crate) fn requires_body(&self) -> bool {
+        self.body.requires_body()
+    }
+
+    /// See [PendingBodyVerification::is_skipped].
+    pub(crate) fn is_skipped(&self) -> bool {
+        self.body.is_skipped()
+    }
+
+    /// Verifies the body, as described in [PendingBodyVerification::verify_body], and then records
+    /// the time of the certificate with the freshness tracker, if one is configured.
+    pub(crate) fn verify_body(
+        self,
+        body_hash: Hash,
+        body: Vec<u8>,
+        config: &VerifierConfig,
+        trace: &mut VerificationTrace,
+    ) -> ResponseVerificationResult<VerificationInfo> {
+        let verification_info = self.body.verify_body(body_hash, body, config, trace)?;
+
+        if let Some(freshness_tracker) = &config.freshness_tracker {
+            freshness_tracker.record_certificate_time(&self.canister_id, self.certificate_time);
+        }
+
+        Ok(verification_info)
+    }
+}
+
+pub(crate) fn verify_request_response_pair_with_trace(
+    request: HttpRequest,
+    response: HttpResponse,
+    canister_id: &[u8],
+    config: &VerifierConfig,
+    trace: &mut VerificationTrace,
+) -> ResponseVerificationResult<VerificationInfo> {
+    let pending = verify_response_head_with_trace(&request, &response, canister_id, config, trace)?;
+    let body_hash = hash(response.body.as_slice());
+
+    pending.verify_body(body_hash, response.body, config, trace)
+}
+
+/// Verifies everything about a response except for its body: the certificate headers, the
+/// certificate itself, the tree and, for verification version 2, the expression path and the
+/// request and response headers. The body of the response is ignored.
+pub(crate) fn verify_response_head_with_trace(
+    request: &HttpRequest,
+    response: &HttpResponse,
+    canister_id: &[u8],
+    config: &VerifierConfig,
+    trace: &mut VerificationTrace,
+) -> ResponseVerificationResult<PendingResponseVerification> {
     let headers: HashMap<_, _> = response
         .headers
         .iter()
@@ -46,6 +264,97 @@ pub fn verify_request_response_pair(
         .get("content-encoding")
         .map(|encoding| encoding.as_str());
 
+    let header_inputs = || {
+        [
+            "ic-certificate",
+            "ic-certificateexpression",
+            "content-encoding",
+        ]
+        .iter()
+        .filter_map(|name| {
+            headers
+                .get(*name)
+                .map(|value| (name.to_string(), value.to_string()))
+        })
+        .collect()
+    };
+
+    let ParsedCertificateHeader {
+        tree,
+        certificate,
+        version,
+        expr_path,
+    } = trace.record_failure(
+        VerificationStage::HeaderParse,
+        parse_certificate_header(&headers, config),
+        header_inputs,
+    )?;
+
+    match version {
+        1 => {
+            trace.record(VerificationStage::HeaderParse, Ok(()), header_inputs)?;
+
+            v1_head_verification(
+                request,
+                canister_id,
+                config,
+                tree,
+                certificate,
+                encoding,
+                trace,
+            )
+        }
+        _ => {
+            let certificate_expression_header = trace.record_failure(
+                VerificationStage::HeaderParse,
+                headers
+                    .get("ic-certificateexpression")
+                    .ok_or(ResponseVerificationError::MissingCertification),
+                header_inputs,
+            )?;
+
+            let expr_path = trace.record_failure(
+                VerificationStage::HeaderParse,
+                expr_path
+                    .ok_or(ResponseVerificationError::MissingCertificateExpressionPath)
+                    .and_then(|expr_path| parse_cbor_string_array(&expr_path).map_err(Into::into)),
+                header_inputs,
+            )?;
+
+            let cel_ast = trace.record_failure(
+                VerificationStage::HeaderParse,
+                parse_cel_expression(certificate_expression_header).map_err(Into::into),
+                header_inputs,
+            )?;
+            let certification = trace.record(
+                VerificationStage::HeaderParse,
+                map_cel_ast(&cel_ast).map_err(Into::into),
+                header_inputs,
+            )?;
+            let expr_hash = hash(certificate_expression_header.as_bytes());
+
+            v2_head_verification(
+                request,
+                response,
+                canister_id,
+                config,
+                tree,
+                certificate,
+                expr_path,
+                expr_hash,
+                certification,
+                trace,
+            )
+        }
+    }
+}
+
+fn parse_certificate_header(
+    headers: &HashMap<String, String>,
+    config: &VerifierConfig,
+) -> ResponseVerificationResult<ParsedCertificateHeader> {
+    let min_requested_verification_version = config.min_requested_verification_version;
+
     let Some(certificate_header) = headers.get("ic-certificate") else {
         return Err(ResponseVerificationError::MissingCertification);
     };
@@ -66,6 +375,11 @@ pub fn verify_request_response_pair(
             return Err(ResponseVerificationError::MissingCertificate);
         };
 
+    if config.validate_well_formed_trees {
+        tree.validate_well_formed()?;
+        validate_well_formed_certificate(&certificate)?;
+    }
+
     let version = certificate_header
         .version
         .unwrap_or(MIN_VERIFICATION_VERSION);
@@ -77,46 +391,12 @@ pub fn verify_request_response_pair(
                 min_requested_verification_version,
             },
         ),
-        1 => v1_verification(
-            request,
-            response,
-            canister_id,
-            current_time_ns,
-            max_cert_time_offset_ns,
+        MIN_VERIFICATION_VERSION..=MAX_VERIFICATION_VERSION => Ok(ParsedCertificateHeader {
             tree,
             certificate,
-            encoding,
-            ic_public_key,
-        ),
-        2 => match headers.get("ic-certificateexpression") {
-            Some(certificate_expression_header) => {
-                let Some(expr_path) = certificate_header
-                    .expr_path
-                    .map(|expr_path| parse_cbor_string_array(&expr_path))
-                    .transpose()? else {
-                        return Err(ResponseVerificationError::MissingCertificateExpressionPath);
-                    };
-
-                let cel_ast = parse_cel_expression(certificate_expression_header)?;
-                let certification = map_cel_ast(&cel_ast)?;
-                let expr_hash = hash(certificate_expression_header.as_bytes());
-
-                v2_verification(
-                    request,
-                    response,
-                    canister_id,
-                    current_time_ns,
-                    max_cert_time_offset_ns,
-                    tree,
-                    certificate,
-                    expr_path,
-                    expr_hash,
-                    certification,
-                    ic_public_key,
-                )
-            }
-            None => Err(ResponseVerificationError::MissingCertification),
-        },
+            version,
+            expr_path: certificate_header.expr_path,
+        }),
         _ => Err(ResponseVerificationError::UnsupportedVerificationVersion {
             min_supported_version: MIN_VERIFICATION_VERSION,
             max_supported_version: MAX_VERIFICATION_VERSION,
@@ -125,83 +405,269 @@ pub fn verify_request_response_pair(
     }
 }
 
-fn v1_verification(
-    request: HttpRequest,
-    response: HttpResponse,
+/// Validates that the certificate's tree, and the tree of its delegation's certificate if there is
+/// one, are well formed.
+fn validate_well_formed_certificate(certificate: &Certificate) -> ResponseVerificationResult {
+    certificate.tree.validate_well_formed()?;
+
+    if let Some(delegation) = &certificate.delegation {
+        Certificate::from_cbor(&delegation.certificate)?
+            .tree
+            .validate_well_formed()?;
+    }
+
+    Ok(())
+}
+
+/// Verifies the time and signature of the certificate, and that the tree matches the certified
+/// data of the canister, and returns the time of the certificate.
+fn verify_certificate(
     canister_id: &[u8],
-    current_time_ns: u128,
-    max_cert_time_offset_ns: u128,
+    config: &VerifierConfig,
+    tree: &HashTree,
+    certificate: &Certificate,
+    trace: &mut VerificationTrace,
+) -> ResponseVerificationResult<u128> {
+    let current_time_ns = config.clock.current_time_ns();
+    trace.record(
+        VerificationStage::CertificateTime,
+        validate_certificate_time(
+            certificate,
+            &current_time_ns,
+            &config.max_cert_time_offset_ns,
+        )
+        .map_err(Into::into),
+        || {
+            vec![
+                ("current time".into(), current_time_ns.to_string()),
+                (
+                    "max certificate time offset".into(),
+                    config.max_cert_time_offset_ns.to_string(),
+                ),
+                ("certificate time".into(), certificate_time(certificate)),
+            ]
+        },
+    )?;
+
+    let verification = certificate.verify_with_trusted_root_keys(
+        canister_id,
+        &config.trusted_root_keys,
+        config.signature_cache.as_ref(),
+    );
+    let root_key_label = verification
+        .as_ref()
+        .ok()
+        .map(|(_, root_key)| root_key.label().to_string());
+    let verified_certificate = trace.record(
+        VerificationStage::CertificateSignature,
+        verification
+            .map(|(verified_certificate, _)| verified_certificate)
+            .map_err(Into::into),
+        || {
+            let mut inputs = vec![("canister id".to_string(), hex::encode(canister_id))];
+            if let Some(delegation) = &certificate.delegation {
+                inputs.push((
+                    "delegation subnet id".into(),
+                    hex::encode(&delegation.subnet_id),
+                ));
+            }
+            if let Some(root_key_label) = root_key_label {
+                inputs.push(("root key".into(), root_key_label));
+            }
+
+            inputs
+        },
+    )?;
+
+    let certificate_time = verified_certificate.lookup_time()?;
+
+    if let Some(freshness_tracker) = &config.freshness_tracker {
+        trace.record(
+            VerificationStage::CertificateFreshness,
+            freshness_tracker
+                .check_certificate_freshness(canister_id, certificate_time)
+                .map_err(Into::into),
+            || {
+                vec![
+                    ("canister id".into(), hex::encode(canister_id)),
+                    ("certificate time".into(), certificate_time.to_string()),
+                    (
+                        "tolerance".into(),
+                        freshness_tracker.tolerance_ns().to_string(),
+                    ),
+                ]
+            },
+        )?;
+    }
+
+    trace.record(
+        VerificationStage::TreeRoot,
+        match validate_tree(canister_id, certificate, tree) {
+            true => Ok(()),
+            false => Err(ResponseVerificationError::InvalidTree),
+        },
+        || {
+            vec![
+                ("tree root hash".into(), hex::encode(tree.digest())),
+                (
+                    "certified data".into(),
+                    certified_data(canister_id, certificate),
+                ),
+            ]
+        },
+    )?;
+
+    Ok(certificate_time)
+}
+
+fn v1_head_verification(
+    request: &HttpRequest,
+    canister_id: &[u8],
+    config: &VerifierConfig,
     tree: HashTree,
     certificate: Certificate,
     encoding: Option<&str>,
-    ic_public_key: &[u8],
-) -> ResponseVerificationResult<VerificationInfo> {
-    validate_certificate_time(&certificate, &current_time_ns, &max_cert_time_offset_ns)?;
-    certificate.verify(canister_id, ic_public_key)?;
+    trace: &mut VerificationTrace,
+) -> ResponseVerificationResult<PendingResponseVerification> {
+    let certificate_time = verify_certificate(canister_id, config, &tree, &certificate, trace)?;
 
-    let request_path = request.get_path()?;
-    let decoded_body = decode_body(&response.body, encoding)?;
-    let decoded_body_sha = hash(decoded_body.as_slice());
+    Ok(PendingResponseVerification::new(
+        PendingBodyVerification::V1 {
+            tree,
+            request_path: request.get_path()?,
+            encoding: encoding.map(Into::into),
+        },
+        canister_id,
+        certificate_time,
+    ))
+}
 
-    if !validate_tree(canister_id, &certificate, &tree) {
-        return Err(ResponseVerificationError::InvalidTree);
-    }
+fn v1_body_verification(
+    tree: HashTree,
+    request_path: String,
+    encoding: Option<&str>,
+    body_hash: Hash,
+    body: Vec<u8>,
+    config: &VerifierConfig,
+    trace: &mut VerificationTrace,
+) -> ResponseVerificationResult<VerificationInfo> {
+    let decoded_body_sha = match encoding {
+        Some(_) => hash(
+            decode_body(
+                &body,
+                encoding,
+                config.max_decoded_body_size,
+                config.max_body_compression_ratio,
+            )?
+            .as_slice(),
+        ),
+        None => body_hash,
+    };
 
     let mut valid_body = validate_body(&tree, &request_path, &decoded_body_sha);
     if encoding.is_some() && !valid_body {
-        let body_sha = hash(response.body.as_slice());
-        valid_body = validate_body(&tree, &request_path, &body_sha);
+        valid_body = validate_body(&tree, &request_path, &body_hash);
     }
 
-    if !valid_body {
-        return Err(ResponseVerificationError::InvalidResponseBody);
-    }
+    trace.record(
+        VerificationStage::HashComparison,
+        match valid_body {
+            true => Ok(()),
+            false => Err(ResponseVerificationError::InvalidResponseBody),
+        },
+        || {
+            vec![
+                ("request path".into(), request_path.clone()),
+                ("decoded body hash".into(), hex::encode(decoded_body_sha)),
+                ("encoded body hash".into(), hex::encode(body_hash)),
+                (
+                    "certified body hash".into(),
+                    certified_body_hash(&tree, &request_path),
+                ),
+            ]
+        },
+    )?;
 
     Ok(VerificationInfo {
         response: Some(VerifiedResponse {
             status_code: None,
             headers: Vec::new(),
-            body: response.body,
+            body,
         }),
         verification_version: 1,
     })
 }
 
-fn v2_verification(
-    request: HttpRequest,
-    response: HttpResponse,
+#[allow(clippy::too_many_arguments)]
+fn v2_head_verification(
+    request: &HttpRequest,
+    response: &HttpResponse,
     canister_id: &[u8],
-    current_time_ns: u128,
-    max_cert_time_offset_ns: u128,
+    config: &VerifierConfig,
     tree: HashTree,
     certificate: Certificate,
     expr_path: Vec<String>,
     expr_hash: Hash,
     certification: CelExpression,
-    ic_public_key: &[u8],
-) -> ResponseVerificationResult<VerificationInfo> {
+    trace: &mut VerificationTrace,
+) -> ResponseVerificationResult<PendingResponseVerification> {
     let request_path = request.get_path()?;
 
-    validate_certificate_time(&certificate, &current_time_ns, &max_cert_time_offset_ns)?;
-    certificate.verify(canister_id, ic_public_key)?;
+    let certificate_time = verify_certificate(canister_id, config, &tree, &certificate, trace)?;
 
-    if !validate_tree(canister_id, &certificate, &tree) {
-        return Err(ResponseVerificationError::InvalidTree);
-    }
+    let expr_path_inputs = |expr_path: &[String]| {
+        let more_specific_paths = HttpCertificationPath::from_expr_path(expr_path)
+            .map(|expr_path| find_more_specific_expr_paths(&expr_path, &request_path, &tree))
+            .unwrap_or_default();
 
-    if !validate_expr_path(&expr_path, &request_path, &tree) {
-        return Err(ResponseVerificationError::InvalidExpressionPath);
-    }
+        let mut inputs = vec![
+            ("request path".to_string(), request_path.clone()),
+            ("expression path".into(), format!("{:?}", expr_path)),
+        ];
+        inputs.extend(more_specific_paths.iter().map(|path| {
+            (
+                "more specific path in tree".into(),
+                format!("{:?}", path.to_expr_path()),
+            )
+        }));
+
+        inputs
+    };
+
+    let expr_path = trace.record(
+        VerificationStage::ExprPathSearch,
+        match validate_expr_path(&expr_path, &request_path, &tree) {
+            true => HttpCertificationPath::from_expr_path(&expr_path)
+                .map_err(|_| ResponseVerificationError::InvalidExpressionPath),
+            false => Err(ResponseVerificationError::InvalidExpressionPath),
+        },
+        || expr_path_inputs(&expr_path),
+    )?;
 
     let (request_certification, response_certification) = match &certification {
         CelExpression::Default(DefaultCelExpression::Skip) => {
-            return match validate_expr_hash(&expr_path, &expr_hash, &tree).is_some() {
-                true => Ok(VerificationInfo {
-                    response: None,
-                    verification_version: 2,
-                }),
-                false => Err(ResponseVerificationError::InvalidExpressionPath),
-            };
+            trace.record(
+                VerificationStage::HashComparison,
+                match validate_expr_hash(&expr_path, &expr_hash, &tree).is_some() {
+                    true => Ok(()),
+                    false => Err(ResponseVerificationError::InvalidExpressionPath),
+                },
+                || {
+                    vec![
+                        ("expression hash".into(), hex::encode(expr_hash)),
+                        (
+                            "certifications in tree".into(),
+                            expr_path_certifications(&expr_path, &tree),
+                        ),
+                    ]
+                },
+            )?;
+
+            return Ok(PendingResponseVerification::new(
+                PendingBodyVerification::Skipped,
+                canister_id,
+                certificate_time,
+            ));
         }
         CelExpression::Default(DefaultCelExpression::ResponseOnly(
             DefaultResponseOnlyCelExpression { response },
@@ -214,33 +680,68 @@ fn v2_verification(
 
     let request_hash = request_certification
         .as_ref()
-        .map(|request_certification| request_hash(&request, request_certification))
+        .map(|request_certification| request_hash(request, request_certification))
         .transpose()?;
 
-    let body_hash = hash(&response.body);
-    let response_headers = filter_response_headers(&response, &response_certification);
+    let response_headers = filter_response_headers(response, response_certification);
     let response_headers_hash =
         response_headers_hash(&response.status_code.into(), &response_headers);
-    let response_hash = hash([response_headers_hash, body_hash].concat().as_slice());
-
-    let are_hashes_valid = validate_hashes(
-        &expr_hash,
-        &request_hash,
-        &response_hash,
-        &expr_path,
-        &tree,
-        &certification,
-    );
 
-    match are_hashes_valid {
-        true => Ok(VerificationInfo {
-            response: Some(VerifiedResponse {
-                status_code: Some(response.status_code),
-                headers: response_headers.headers,
-                body: response.body,
-            }),
-            verification_version: 2,
-        }),
-        false => Err(ResponseVerificationError::InvalidResponseHashes),
+    Ok(PendingResponseVerification::new(
+        PendingBodyVerification::V2 {
+            tree,
+            expr_path,
+            expr_hash,
+            request_hash,
+            status_code: response.status_code,
+            response_headers: response_headers.headers,
+            response_headers_hash,
+        },
+        canister_id,
+        certificate_time,
+    ))
+}
+
+fn certificate_time(certificate: &Certificate) -> String {
+    UnverifiedCertificate::new(certificate)
+        .lookup_time()
+        .map(|time| time.to_string())
+        .unwrap_or_else(|e| e.to_string())
+}
+
+fn certified_data(canister_id: &[u8], certificate: &Certificate) -> String {
+    UnverifiedCertificate::new(certificate)
+        .lookup_certified_data(canister_id)
+        .map(hex::encode)
+        .unwrap_or_else(|e| e.to_string())
+}
+
+fn certified_body_hash(tree: &HashTree, request_path: &str) -> String {
+    let asset_path = ["http_assets".as_bytes(), request_path.as_bytes()];
+    let index_fallback_path = ["http_assets".as_bytes(), "/index.html".as_bytes()];
+
+    match tree.lookup_path(&asset_path) {
+        LookupResult::Found(body_hash) => hex::encode(body_hash),
+        _ => match tree.lookup_path(&index_fallback_path) {
+            LookupResult::Found(body_hash) => format!("{} (/index.html)", hex::encode(body_hash)),
+            _ => "not found".into(),
+        },
+    }
+}
+
+fn expr_path_certifications(expr_path: &HttpCertificationPath, tree: &HashTree) -> String {
+    let certifications: Vec<_> = find_expr_path_certifications(expr_path, tree)
+        .iter()
+        .map(|path| {
+            path.iter()
+                .map(|label: &Label| hex::encode(label.as_bytes()))
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect();
+
+    match certifications.is_empty() {
+        true => "none".into(),
+        false => certifications.join(", "),
     }
 }
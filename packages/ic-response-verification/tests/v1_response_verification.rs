@@ -1,16 +1,20 @@
 #[cfg(not(target_arch = "wasm32"))]
 mod tests {
-    use ic_certificate_verification::CertificateVerificationError;
+    use ic_certificate_verification::{
+        CertificateFreshnessTracker, CertificateVerificationError, SignatureCache,
+        SignatureCacheStatistics, SizedSignatureCache, TrustedRootKey, TrustedRootKeys,
+    };
     use ic_certification_testing::{CertificateBuilder, CertificateData};
     use ic_http_certification::{HttpRequest, HttpResponse};
     use ic_response_verification::types::{VerificationInfo, VerifiedResponse};
     use ic_response_verification::verify_request_response_pair;
-    use ic_response_verification::ResponseVerificationError;
+    use ic_response_verification::{ResponseVerificationError, VerifierConfig};
     use ic_response_verification_test_utils::{
         create_canister_id, create_certificate_header, create_certified_data,
         get_current_timestamp, get_timestamp, AssetTree,
     };
     use std::ops::{Add, Sub};
+    use std::sync::Arc;
     use std::time::{Duration, SystemTime};
 
     const MAX_CERT_TIME_OFFSET_NS: u128 = 300_000_000_000;
@@ -651,4 +655,252 @@ mod tests {
             )
         ));
     }
+
+    #[test]
+    fn verification_uses_configured_signature_cache() {
+        let path = "/";
+        let body = "Hello World!";
+        let current_time = get_current_timestamp();
+        let canister_id = create_canister_id("rdmx6-jaaaa-aaaaa-aaadq-cai");
+
+        let mut asset_tree = AssetTree::new();
+        asset_tree.insert(path, body);
+        let certified_data = asset_tree.get_certified_data();
+        let tree_cbor = asset_tree.serialize_to_cbor(Some(path));
+
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(&canister_id.to_string(), &certified_data)
+            .unwrap()
+            .with_time(current_time)
+            .build()
+            .unwrap();
+
+        let certificate_header = create_certificate_header(&cbor_encoded_certificate, &tree_cbor);
+
+        let request = HttpRequest {
+            url: path.into(),
+            method: "GET".into(),
+            headers: vec![],
+            body: vec![],
+        };
+
+        let response = HttpResponse {
+            status_code: 200,
+            body: body.as_bytes().to_vec(),
+            headers: vec![("IC-Certificate".into(), certificate_header)],
+        };
+
+        let signature_cache = Arc::new(SizedSignatureCache::new(10));
+        let verifier = VerifierConfig::new(root_key, move || current_time)
+            .with_signature_cache(signature_cache.clone())
+            .build();
+
+        verifier
+            .verify(request.clone(), response.clone(), canister_id.as_ref())
+            .unwrap();
+        verifier
+            .verify(request, response, canister_id.as_ref())
+            .unwrap();
+
+        assert_eq!(
+            signature_cache.cache_statistics(),
+            SignatureCacheStatistics::new(1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn verification_uses_configured_trusted_root_keys() {
+        let path = "/";
+        let body = "Hello World!";
+        let current_time = get_current_timestamp();
+        let canister_id = create_canister_id("rdmx6-jaaaa-aaaaa-aaadq-cai");
+
+        let mut asset_tree = AssetTree::new();
+        asset_tree.insert(path, body);
+        let certified_data = asset_tree.get_certified_data();
+        let tree_cbor = asset_tree.serialize_to_cbor(Some(path));
+
+        let CertificateData {
+            cbor_encoded_certificate,
+            certificate: _,
+            root_key,
+        } = CertificateBuilder::new(&canister_id.to_string(), &certified_data)
+            .unwrap()
+            .with_time(current_time)
+            .build()
+            .unwrap();
+        let CertificateData {
+            cbor_encoded_certificate: _,
+            certificate: _,
+            root_key: rotated_root_key,
+        } = CertificateBuilder::new(&canister_id.to_string(), &certified_data)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let certificate_header = create_certificate_header(&cbor_encoded_certificate, &tree_cbor);
+
+        let request = HttpRequest {
+            url: path.into(),
+            method: "GET".into(),
+            headers: vec![],
+            body: vec![],
+        };
+
+        let response = HttpResponse {
+            status_code: 200,
+            body: body.as_bytes().to_vec(),
+            headers: vec![("IC-Certificate".into(), certificate_header)],
+        };
+
+        let trusted_root_keys: Arc<TrustedRootKeys> = Arc::new(
+            [
+                TrustedRootKey::new("rotated", rotated_root_key),
+                TrustedRootKey::new("current", root_key),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let verifier = VerifierConfig::new(vec![], move || current_time)
+            .with_trusted_root_keys(trusted_root_keys.clone())
+            .build();
+
+        verifier
+            .verify(request, response, canister_id.as_ref())
+            .unwrap();
+
+        assert_eq!(
+            trusted_root_keys
+                .get("rotated")
+                .unwrap()
+                .verified_certificates(),
+            0
+        );
+        assert_eq!(
+            trusted_root_keys
+                .get("current")
+                .unwrap()
+                .verified_certificates(),
+            1
+        );
+    }
+
+    #[test]
+    fn verification_rejects_certificate_older_than_newest_seen() {
+        let path = "/";
+        let body = "Hello World!";
+        let current_time = get_current_timestamp();
+        let previous_time = current_time - 60_000_000_000;
+        let canister_id = create_canister_id("rdmx6-jaaaa-aaaaa-aaadq-cai");
+
+        let mut asset_tree = AssetTree::new();
+        asset_tree.insert(path, body);
+        let certified_data = asset_tree.get_certified_data();
+        let tree_cbor = asset_tree.serialize_to_cbor(Some(path));
+
+        let request = HttpRequest {
+            url: path.into(),
+            method: "GET".into(),
+            headers: vec![],
+            body: vec![],
+        };
+
+        let freshness_tracker = Arc::new(CertificateFreshnessTracker::in_memory());
+        let verify_certificate_with_time = |certificate_time| {
+            let CertificateData {
+                cbor_encoded_certificate,
+                certificate: _,
+                root_key,
+            } = CertificateBuilder::new(&canister_id.to_string(), &certified_data)
+                .unwrap()
+                .with_time(certificate_time)
+                .build()
+                .unwrap();
+
+            let certificate_header =
+                create_certificate_header(&cbor_encoded_certificate, &tree_cbor);
+            let response = HttpResponse {
+                status_code: 200,
+                body: body.as_bytes().to_vec(),
+                headers: vec![("IC-Certificate".into(), certificate_header)],
+            };
+
+            VerifierConfig::new(root_key, move || current_time)
+                .with_freshness_tracker(freshness_tracker.clone())
+                .build()
+                .verify(request.clone(), response, canister_id.as_ref())
+        };
+
+        verify_certificate_with_time(previous_time).unwrap();
+        verify_certificate_with_time(current_time).unwrap();
+        let result = verify_certificate_with_time(previous_time);
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::CertificateVerificationFailed(
+                CertificateVerificationError::CertificateOlderThanNewestSeen {
+                    certificate_time,
+                    newest_certificate_time,
+                }
+            )) if certificate_time == previous_time && newest_certificate_time == current_time
+        ));
+    }
+
+    #[test]
+    fn verification_does_not_record_certificate_time_of_invalid_response() {
+        let path = "/";
+        let body = "Hello World!";
+        let current_time = get_current_timestamp();
+        let previous_time = current_time - 60_000_000_000;
+        let canister_id = create_canister_id("rdmx6-jaaaa-aaaaa-aaadq-cai");
+
+        let mut asset_tree = AssetTree::new();
+        asset_tree.insert(path, body);
+        let certified_data = asset_tree.get_certified_data();
+        let tree_cbor = asset_tree.serialize_to_cbor(Some(path));
+
+        let request = HttpRequest {
+            url: path.into(),
+            method: "GET".into(),
+            headers: vec![],
+            body: vec![],
+        };
+
+        let freshness_tracker = Arc::new(CertificateFreshnessTracker::in_memory());
+        let verify_certificate_with_time = |certificate_time, body: &str| {
+            let CertificateData {
+                cbor_encoded_certificate,
+                certificate: _,
+                root_key,
+            } = CertificateBuilder::new(&canister_id.to_string(), &certified_data)
+                .unwrap()
+                .with_time(certificate_time)
+                .build()
+                .unwrap();
+
+            let certificate_header =
+                create_certificate_header(&cbor_encoded_certificate, &tree_cbor);
+            let response = HttpResponse {
+                status_code: 200,
+                body: body.as_bytes().to_vec(),
+                headers: vec![("IC-Certificate".into(), certificate_header)],
+            };
+
+            VerifierConfig::new(root_key, move || current_time)
+                .with_freshness_tracker(freshness_tracker.clone())
+                .build()
+                .verify(request.clone(), response, canister_id.as_ref())
+        };
+
+        let result = verify_certificate_with_time(current_time, "Hello Tampered World!");
+
+        assert!(matches!(
+            result,
+            Err(ResponseVerificationError::InvalidResponseBody)
+        ));
+        verify_certificate_with_time(previous_time, body).unwrap();
+    }
 }
@@ -11,7 +11,10 @@ mod tests {
         request_hash, response_hash, CelExpression, DefaultFullCelExpression, HttpRequest,
         HttpResponse,
     };
-    use ic_response_verification::{verify_request_response_pair, ResponseVerificationError};
+    use ic_response_verification::{
+        types::{VerificationStage, VerificationStageOutcome},
+        verify_request_response_pair, ResponseVerificationError, VerifierConfig,
+    };
     use ic_response_verification_test_utils::{
         create_expr_tree_path, create_v2_certificate_fixture, create_v2_fixture, create_v2_header,
         create_v2_tree_fixture, get_current_timestamp, hash, hash_from_hex, ExprTree,
@@ -297,6 +300,92 @@ mod tests {
         ));
     }
 
+    #[rstest]
+    fn more_specific_expr_path_is_reported(
+        #[from(skip_certification_cel)] certification: CelExpression<'static>,
+    ) {
+        let cel_expr = certification.to_string();
+        let current_time = get_current_timestamp();
+
+        let request = HttpRequest {
+            url: "/assets/js/app.js".to_string(),
+            method: "GET".to_string(),
+            headers: vec![],
+            body: vec![],
+        };
+        let mut response = HttpResponse {
+            status_code: 200,
+            body: b"Hello World!".to_vec(),
+            headers: vec![("IC-CertificateExpression".to_string(), cel_expr.clone())],
+        };
+
+        let cel_expr_hash = hash(&cel_expr);
+        let mut expr_tree = ExprTree::new();
+
+        expr_tree.insert(&create_expr_tree_path(
+            &["assets", "<*>"],
+            &cel_expr_hash,
+            None,
+            None,
+        ));
+        expr_tree.insert(&create_expr_tree_path(
+            &["assets", "js", "<*>"],
+            &cel_expr_hash,
+            None,
+            None,
+        ));
+
+        let V2CertificateFixture {
+            root_key,
+            certificate_cbor,
+            canister_id,
+        } = create_v2_certificate_fixture(&expr_tree.get_certified_data(), &current_time);
+        let certificate_header = create_v2_header(
+            &["assets", "<*>"],
+            &certificate_cbor,
+            &expr_tree.serialize_to_cbor(),
+        );
+
+        response
+            .headers
+            .push(("IC-Certificate".to_string(), certificate_header));
+
+        let report = VerifierConfig::new(root_key, move || current_time)
+            .with_max_cert_time_offset_ns(MAX_CERT_TIME_OFFSET_NS)
+            .build()
+            .verify_with_report(request, response, canister_id.as_ref());
+
+        assert!(matches!(
+            report.result,
+            Err(ResponseVerificationError::InvalidExpressionPath)
+        ));
+        assert_eq!(
+            report
+                .stages
+                .iter()
+                .map(|record| record.stage)
+                .collect::<Vec<_>>(),
+            vec![
+                VerificationStage::HeaderParse,
+                VerificationStage::CertificateTime,
+                VerificationStage::CertificateSignature,
+                VerificationStage::TreeRoot,
+                VerificationStage::ExprPathSearch,
+            ]
+        );
+
+        let failed_stage = report.failed_stage().unwrap();
+        assert_eq!(failed_stage.stage, VerificationStage::ExprPathSearch);
+        assert_eq!(
+            failed_stage.outcome,
+            VerificationStageOutcome::Failed("Invalid expression path".to_string())
+        );
+        assert!(failed_stage.inputs.contains(&(
+            "more specific path in tree".to_string(),
+            r#"["http_expr", "assets", "js", "<*>"]"#.to_string()
+        )));
+    }
+
     #[rstest]
     #[case::invalid_root_key_certificate(
         invalid_root_key_certificate(),